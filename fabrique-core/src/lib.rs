@@ -1,3 +1,5 @@
+use std::pin::Pin;
+
 /// Trait for objects that can be persisted to a database or storage backend.
 ///
 /// This trait enables factories to create and persist objects using the `create()` method.
@@ -52,4 +54,607 @@ pub trait Persistable: Sized {
     fn all(
         connection: &Self::Connection,
     ) -> impl Future<Output = Result<Vec<Self>, Self::Error>> + Send;
+
+    /// Counts the instances of this model in the persistence layer.
+    ///
+    /// The default implementation counts the records returned by `all()`; override it to
+    /// count them without loading them.
+    fn count(
+        connection: &Self::Connection,
+    ) -> impl Future<Output = Result<i64, Self::Error>> + Send {
+        async move { Ok(Self::all(connection).await?.len() as i64) }
+    }
+}
+
+/// Trait for persisted objects whose changes can be saved.
+///
+/// Implemented by `#[derive(Persistable)]`, and kept apart from [`Persistable`] so that
+/// hand-written persistence layers only implement it when they support updates.
+///
+/// # Example
+///
+/// ```rust
+/// use fabrique_core::{Persistable, Updatable};
+///
+/// struct Anvil {
+///     id: u32,
+///     weight: u32,
+/// }
+///
+/// # impl Persistable for Anvil {
+/// #     type Connection = ();
+/// #     type Error = ();
+/// #
+/// #     async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+/// #         Ok(self)
+/// #     }
+/// #
+/// #     async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+/// #         Ok(vec![])
+/// #     }
+/// # }
+/// #
+/// impl Updatable for Anvil {
+///     async fn update(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+///         println!("updating anvil #{} in database...", &self.id);
+///         Ok(self)
+///     }
+/// }
+/// ```
+pub trait Updatable: Persistable {
+    /// Persists the changes made to this already created object.
+    ///
+    /// The record is matched on its primary key and returned as stored once updated.
+    fn update(
+        self,
+        connection: &Self::Connection,
+    ) -> impl Future<Output = Result<Self, Self::Error>> + Send;
+}
+
+/// Trait for persisted objects that can be deleted.
+///
+/// Implemented by `#[derive(Persistable)]`, and kept apart from [`Persistable`] so that
+/// hand-written persistence layers only implement it when they support deletes.
+///
+/// # Example
+///
+/// ```rust
+/// use fabrique_core::{Deletable, Persistable};
+///
+/// struct Anvil {
+///     id: u32,
+/// }
+///
+/// # impl Persistable for Anvil {
+/// #     type Connection = ();
+/// #     type Error = ();
+/// #
+/// #     async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+/// #         Ok(self)
+/// #     }
+/// #
+/// #     async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+/// #         Ok(vec![])
+/// #     }
+/// # }
+/// #
+/// impl Deletable for Anvil {
+///     async fn delete(self, _connection: &Self::Connection) -> Result<(), Self::Error> {
+///         println!("deleting anvil #{} from database...", &self.id);
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Deletable: Persistable {
+    /// Deletes this already created object, matched on its primary key.
+    fn delete(
+        self,
+        connection: &Self::Connection,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// Trait for objects that can create a related record and return its key.
+///
+/// This trait enables dynamically-resolved relations: a factory field marked with
+/// `#[fabrique(relation_trait = "dyn Creatable")]` accepts any implementor, so the concrete
+/// type of the related record can be chosen at runtime.
+///
+/// # Example
+///
+/// ```rust
+/// use std::pin::Pin;
+/// use fabrique_core::Creatable;
+///
+/// struct Hammer {
+///     id: u32,
+/// }
+///
+/// impl Creatable<u32, (), ()> for Hammer {
+///     fn create<'a>(
+///         self: Box<Self>,
+///         _connection: &'a (),
+///     ) -> Pin<Box<dyn Future<Output = Result<u32, ()>> + Send + 'a>> {
+///         Box::pin(async move { Ok(self.id) })
+///     }
+/// }
+/// ```
+pub trait Creatable<Key, Connection, Error>: Send {
+    /// Creates the related record using the provided connection and returns its key.
+    fn create<'a>(
+        self: Box<Self>,
+        connection: &'a Connection,
+    ) -> Pin<Box<dyn Future<Output = Result<Key, Error>> + Send + 'a>>;
+}
+
+/// Trait for objects that can check their own invariants.
+///
+/// Structs deriving `Factory` with `#[factory(validate)]` get a `try_build_validated()`
+/// method on their factory, running this validation on the built object.
+///
+/// # Example
+///
+/// ```rust
+/// use fabrique_core::Validate;
+///
+/// struct Anvil {
+///     weight: u32,
+/// }
+///
+/// impl Validate for Anvil {
+///     type Error = String;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         if self.weight == 0 {
+///             return Err("an anvil cannot be weightless".to_string());
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Validate {
+    /// The error type returned when the validation fails
+    type Error;
+
+    /// Checks the invariants of this object.
+    fn validate(&self) -> Result<(), Self::Error>;
+}
+
+/// Default value of the factory fields left unset and declaring no default attribute.
+///
+/// Implemented for every `Default` type. Going through this trait rather than `Default`
+/// lets the compiler explain which attributes rescue a field type without a default.
+#[diagnostic::on_unimplemented(
+    message = "the factory field type `{Self}` has no default value",
+    label = "`{Self}` does not implement `Default`",
+    note = "implement `Default` for `{Self}`, or set `#[factory(default = \"...\")]`, `#[factory(default_fn = \"...\")]` or `#[factory(no_default)]` on the field"
+)]
+pub trait FactoryDefault {
+    /// Returns the value of a field left unset.
+    fn factory_default() -> Self;
+}
+
+impl<T: Default> FactoryDefault for T {
+    fn factory_default() -> Self {
+        T::default()
+    }
+}
+
+/// Helpers backing the `Debug` implementation of the generated factories.
+///
+/// The values of the factory fields are printed when their type implements `Debug`, and
+/// rendered as `<opaque>` otherwise, so deriving `Factory` never requires `Debug`.
+pub mod debug {
+    use std::fmt::{Debug, Formatter, Result};
+
+    /// Wraps a factory field so its value is printed only when it implements `Debug`.
+    ///
+    /// Call `(&&Field(&value)).as_debug()` with both [`DebugField`] and [`OpaqueField`] in
+    /// scope: method resolution picks the former whenever the value implements `Debug`.
+    pub struct Field<'a, T>(pub &'a T);
+
+    /// Prints a field implementing `Debug`.
+    pub trait DebugField {
+        /// Returns the value to print.
+        fn as_debug(&self) -> &dyn Debug;
+    }
+
+    impl<T: Debug> DebugField for &Field<'_, T> {
+        fn as_debug(&self) -> &dyn Debug {
+            self.0
+        }
+    }
+
+    /// Prints a field not implementing `Debug` as `<opaque>`.
+    pub trait OpaqueField {
+        /// Returns the value to print.
+        fn as_debug(&self) -> &dyn Debug;
+    }
+
+    impl<T> OpaqueField for Field<'_, T> {
+        fn as_debug(&self) -> &dyn Debug {
+            &Opaque
+        }
+    }
+
+    struct Opaque;
+
+    impl Debug for Opaque {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            f.write_str("<opaque>")
+        }
+    }
+
+    /// Prints whether a callback or a related object was given to the factory.
+    pub struct Presence(pub bool);
+
+    impl Debug for Presence {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            match self.0 {
+                true => f.write_str("<set>"),
+                false => f.write_str("<unset>"),
+            }
+        }
+    }
+}
+
+/// Counters backing the `#[factory(sequence = "namespace")]` fields.
+///
+/// Fields sharing a namespace draw their values from the same counter, even across
+/// factories.
+pub mod sequence {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    static SEQUENCES: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+    /// Returns the next value of the given namespace, starting at 1.
+    pub fn next(namespace: &str) -> u64 {
+        let mut sequences = SEQUENCES.lock().unwrap_or_else(|error| error.into_inner());
+        let value = sequences.entry(namespace.to_string()).or_insert(0);
+        *value += 1;
+        *value
+    }
+}
+
+/// Storage backing the `all_cached()` of models with `#[fabrique(cache_all = "...")]`.
+///
+/// The records are cached per key: the generated code keys them by the URL of the pool, so
+/// that models fetched through two pools, e.g. one per test database, are never mixed.
+pub mod cache {
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    /// The last records fetched for every key, along with their fetch time.
+    pub struct AllCache<T> {
+        entries: Mutex<Vec<(String, Instant, Vec<T>)>>,
+    }
+
+    impl<T> AllCache<T> {
+        /// Creates an empty cache, usable in a `static`.
+        pub const fn new() -> Self {
+            Self {
+                entries: Mutex::new(Vec::new()),
+            }
+        }
+
+        /// Drops the records cached for every key.
+        pub fn invalidate(&self) {
+            self.entries
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .clear();
+        }
+    }
+
+    impl<T: Clone> AllCache<T> {
+        /// Returns the records cached for `key` when fetched less than `ttl` ago, and
+        /// awaits `fetch` to refresh them otherwise.
+        pub async fn get_or_fetch<E>(
+            &self,
+            key: String,
+            ttl: Duration,
+            fetch: impl Future<Output = Result<Vec<T>, E>>,
+        ) -> Result<Vec<T>, E> {
+            {
+                let entries = self
+                    .entries
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner());
+                if let Some((_, _, items)) = entries
+                    .iter()
+                    .find(|(cached, fetched_at, _)| *cached == key && fetched_at.elapsed() < ttl)
+                {
+                    return Ok(items.clone());
+                }
+            }
+
+            let items = fetch.await?;
+            let mut entries = self
+                .entries
+                .lock()
+                .unwrap_or_else(|error| error.into_inner());
+            entries.retain(|(cached, _, _)| *cached != key);
+            entries.push((key, Instant::now(), items.clone()));
+            Ok(items)
+        }
+    }
+
+    impl<T> Default for AllCache<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+/// Error reported when a required column is missing before it reaches the database.
+///
+/// Returned by the generated `create()` of models with `#[fabrique(validate_not_null)]`,
+/// in place of the generic error of the database constraint. With the `sqlx` feature, it is
+/// wrapped into `sqlx::Error::Database` with the `NotNullViolation` kind, as the constraint
+/// error it replaces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotNullViolation {
+    /// The table of the model
+    pub table: &'static str,
+    /// The column left empty
+    pub column: &'static str,
+}
+
+impl std::fmt::Display for NotNullViolation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "column `{}` of `{}` cannot be null",
+            self.column, self.table
+        )
+    }
+}
+
+impl std::error::Error for NotNullViolation {}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::error::DatabaseError for NotNullViolation {
+    fn message(&self) -> &str {
+        "null value violates not-null constraint"
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn table(&self) -> Option<&str> {
+        Some(self.table)
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::NotNullViolation
+    }
+}
+
+/// Error reported when an update is based on an outdated version of a record.
+///
+/// Returned by the generated `update()` of models with `#[fabrique(version = "...")]` when
+/// the record was updated or deleted since it was read. With the `sqlx` feature, it is
+/// wrapped into `sqlx::Error::Database` with the `Other` kind.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StaleWrite {
+    /// The table of the model
+    pub table: &'static str,
+}
+
+impl std::fmt::Display for StaleWrite {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "record of `{}` was modified since it was read",
+            self.table
+        )
+    }
+}
+
+impl std::error::Error for StaleWrite {}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::error::DatabaseError for StaleWrite {
+    fn message(&self) -> &str {
+        "record was modified since it was read"
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn table(&self) -> Option<&str> {
+        Some(self.table)
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::Other
+    }
+}
+
+/// Error reported when the database rejects a statement run by a generated method, e.g. an
+/// `INSERT` violating a constraint.
+///
+/// Wraps the error of the database along with the table, the operation and the statement,
+/// so a failure among the inserts of a factory and its relations points at its statement.
+/// The kind, code and constraint of the database error are kept.
+#[cfg(feature = "sqlx")]
+#[derive(Debug)]
+pub struct StatementError {
+    /// The table of the model
+    pub table: &'static str,
+    /// The operation of the statement (e.g., `INSERT`)
+    pub operation: &'static str,
+    /// The statement that failed
+    pub query: &'static str,
+    /// The number of parameters bound to the statement
+    pub params: usize,
+    /// The error reported by the database
+    pub source: Box<dyn sqlx::error::DatabaseError>,
+}
+
+#[cfg(feature = "sqlx")]
+impl StatementError {
+    /// Attaches the statement to a database error, leaving the other errors unchanged.
+    pub fn wrap(
+        error: sqlx::Error,
+        table: &'static str,
+        operation: &'static str,
+        query: &'static str,
+        params: usize,
+    ) -> sqlx::Error {
+        match error {
+            sqlx::Error::Database(source) => sqlx::Error::Database(Box::new(Self {
+                table,
+                operation,
+                query,
+                params,
+                source,
+            })),
+            error => error,
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl std::fmt::Display for StatementError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} on `{}` failed: {} (query: `{}`, {} bound parameters)",
+            self.operation,
+            self.table,
+            self.source.message(),
+            self.query,
+            self.params
+        )
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl std::error::Error for StatementError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_error())
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::error::DatabaseError for StatementError {
+    fn message(&self) -> &str {
+        self.source.message()
+    }
+
+    fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+        self.source.code()
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        self.source.constraint()
+    }
+
+    fn table(&self) -> Option<&str> {
+        self.source.table().or(Some(self.table))
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        self.source.kind()
+    }
+}
+
+/// Counts of the records inserted by the `create_with_stats()` method of a factory.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CreateStats {
+    /// The number of inserted rows, including the related objects
+    pub rows: u64,
+}
+
+impl std::ops::AddAssign for CreateStats {
+    fn add_assign(&mut self, other: Self) {
+        self.rows += other.rows;
+    }
+}
+
+/// Records created by the `create_tracked()` method of a factory, as pairs of table name and
+/// primary key, in creation order.
+///
+/// Deleting them in reverse order cleans up a seed without violating foreign keys.
+pub type SeedLog = Vec<(&'static str, String)>;
+
+/// Trait for persisted objects identified by their table and primary key.
+///
+/// Implemented by `#[derive(Persistable)]` under `#[fabrique(trackable)]`, it lets factories with
+/// `#[factory(tracked)]` record the objects they create into a [`SeedLog`].
+///
+/// # Example
+///
+/// ```rust
+/// use fabrique_core::Trackable;
+///
+/// struct Anvil {
+///     id: u32,
+/// }
+///
+/// impl Trackable for Anvil {
+///     const TABLE_NAME: &'static str = "anvils";
+///
+///     fn primary_key(&self) -> String {
+///         self.id.to_string()
+///     }
+/// }
+/// ```
+pub trait Trackable {
+    /// The table the records are stored in
+    const TABLE_NAME: &'static str;
+
+    /// Returns the primary key of this record, rendered as text.
+    fn primary_key(&self) -> String;
+}
+
+/// Direction of the ordering applied by sorted queries.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortDir {
+    /// Ascending order
+    #[default]
+    Asc,
+    /// Descending order
+    Desc,
+}
+
+impl SortDir {
+    /// Returns the SQL keyword for this direction.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortDir::Asc => "ASC",
+            SortDir::Desc => "DESC",
+        }
+    }
 }
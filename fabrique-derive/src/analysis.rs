@@ -1,8 +1,13 @@
 use crate::error::Error;
-use darling::FromDeriveInput;
+use darling::{FromDeriveInput, FromField, FromMeta};
+use std::time::Duration;
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, Ident};
+use syn::{
+    Data, DataStruct, DeriveInput, Expr, Field, Fields, FieldsNamed, GenericArgument, Ident,
+    LitStr, Path, PathArguments, Type,
+};
 
 /// Initial builder state for derive input analysis.
 pub struct AnalysisBuilder<'a> {
@@ -38,6 +43,190 @@ pub struct Analysis<'a> {
     /// The table name for this model.
     #[allow(dead_code)]
     pub table_name: String,
+
+    /// The custom query used by `all()`, if any.
+    pub all_query: Option<String>,
+
+    /// The primary key of the model, if any.
+    pub primary_key: Option<&'a Field>,
+
+    /// Whether an explicit `sqlx::FromRow` implementation is generated.
+    pub from_row: bool,
+
+    /// Whether `create_sql()` is generated.
+    pub dry_run_api: bool,
+
+    /// Whether `copy_in()` is generated.
+    pub copy_in: bool,
+
+    /// Whether `Trackable` is implemented.
+    pub trackable: bool,
+
+    /// Whether `reload_all()` is generated.
+    pub reload_all: bool,
+
+    /// Whether `all_by_id()` is generated.
+    pub all_by_id: bool,
+
+    /// Whether `all_for_update()` is generated.
+    pub all_for_update: bool,
+
+    /// Whether `which_exist()` is generated.
+    pub which_exist: bool,
+
+    /// Whether `paginate()` is generated.
+    pub paginate: bool,
+
+    /// Whether `modified_since()` is generated.
+    pub modified_since: bool,
+
+    /// The `WHERE` condition applied to every generated `SELECT`, if any.
+    pub default_scope: Option<String>,
+
+    /// The parameter bound by the default scope, if any.
+    pub scope_param: Option<ScopeParam>,
+
+    /// How long `all_cached()` serves the last fetched records, if generated.
+    pub cache_ttl: Option<Duration>,
+
+    /// The maximum number of parameters bound by each statement of `create_batch()`, if
+    /// generated.
+    pub batch_max_params: Option<usize>,
+
+    /// The `Option` fields checked for `None` before being inserted.
+    pub not_null_fields: Vec<&'a Field>,
+
+    /// The fields mapped to Postgres array columns.
+    pub array_fields: Vec<&'a Field>,
+
+    /// The fields assigned by the database, left out of the inserted and updated columns.
+    pub generated_fields: Vec<&'a Field>,
+
+    /// The fields mapped to a column of another name, along with that name.
+    pub renamed_columns: Vec<(&'a Field, String)>,
+
+    /// The read-only fields computed by an SQL expression, along with that expression.
+    pub computed_fields: Vec<(&'a Field, String)>,
+
+    /// The fields without a column, filled with their default value when reading rows.
+    pub skipped_fields: Vec<&'a Field>,
+
+    /// The fields whose distinct values are counted by a generated `count_distinct_<field>()`.
+    pub distinct_countable_fields: Vec<&'a Field>,
+
+    /// The fields `all_sorted_by()` can order by.
+    pub sortable_fields: Vec<&'a Field>,
+
+    /// The database backend targeted by the generated queries.
+    pub backend: Backend,
+
+    /// The placeholder style of the generated queries, the one of the backend unless overridden.
+    pub placeholder: Placeholder,
+
+    /// The version column checked and incremented by `update()`, if any.
+    pub version: Option<&'a Field>,
+
+    /// The timestamp columns set by the database, with `#[fabrique(timestamps)]`.
+    pub timestamps: Option<Timestamps<'a>>,
+
+    /// The `Option` field holding the deletion time of soft deleted records, if any.
+    pub soft_delete: Option<&'a Field>,
+
+    /// The event returned along with the created record by `create_with_event()`, if any.
+    pub event: Option<Type>,
+
+    /// The columns of the unique index deduplicated against by `get_or_create()`, if any.
+    pub unique_columns: Vec<String>,
+
+    /// The belongs-to relations eager-loaded by a generated `find_with_<relation>()`.
+    pub joined_relations: Vec<JoinedRelation<'a>>,
+}
+
+/// The timestamp columns managed by the generated queries.
+#[derive(Debug)]
+pub struct Timestamps<'a> {
+    /// The field set to the current time by `create()` (e.g., `created_at`)
+    pub created_at: &'a Field,
+    /// The field set to the current time by `create()` and `update()` (e.g., `updated_at`)
+    pub updated_at: &'a Field,
+}
+
+/// The named parameter of a default scope (e.g., `$TENANT`) and the function supplying its
+/// value each time a scoped query runs.
+#[derive(Debug)]
+pub struct ScopeParam {
+    /// The name of the parameter, without its `$` prefix (e.g., `TENANT`)
+    pub name: String,
+    /// The function returning the value bound to the parameter (e.g., `current_tenant`)
+    pub provider: Path,
+}
+
+/// A belongs-to relation whose related row is fetched along with the record in a join.
+#[derive(Debug)]
+pub struct JoinedRelation<'a> {
+    /// The field holding the foreign key (e.g., `hammer_id`)
+    pub field: &'a Field,
+    /// The related type (e.g., `Hammer`)
+    pub related_type: Path,
+    /// The column of the related table referenced by the foreign key (e.g., `id`)
+    pub referenced_key: Ident,
+    /// The table of the related type (e.g., `hammers`)
+    pub table: String,
+    /// The columns of the related table, along with the fields of the related type they load
+    pub columns: Vec<RelatedColumn>,
+    /// The base name of the relation (e.g., `hammer`)
+    pub name: String,
+}
+
+/// A column of a joined relation and the field of the related type it is loaded into.
+#[derive(Debug)]
+pub struct RelatedColumn {
+    /// The column of the related table (e.g., `forged_on`)
+    pub column: String,
+    /// The field of the related type, the column name unless mapped with `AS` (e.g., `made_at`)
+    pub field: Ident,
+}
+
+/// The database backends the `Persistable` derive can target.
+#[derive(FromMeta, Debug, Clone, Copy, Default, PartialEq)]
+#[darling(rename_all = "lowercase")]
+pub enum Backend {
+    /// `sqlx::Postgres`
+    #[default]
+    Postgres,
+    /// `sqlx::MySql`
+    MySql,
+    /// `sqlx::Sqlite`
+    Sqlite,
+}
+
+impl Backend {
+    /// Returns the maximum number of parameters a statement can bind on this backend.
+    pub fn max_params(self) -> usize {
+        match self {
+            Self::Postgres | Self::MySql => 65535,
+            Self::Sqlite => 32766,
+        }
+    }
+
+    /// Returns the placeholder style of this backend.
+    pub fn placeholder(self) -> Placeholder {
+        match self {
+            Self::Postgres => Placeholder::Numbered,
+            Self::MySql | Self::Sqlite => Placeholder::Positional,
+        }
+    }
+}
+
+/// The placeholder styles of the generated queries.
+#[derive(FromMeta, Debug, Clone, Copy, PartialEq)]
+pub enum Placeholder {
+    /// `$1`, `$2`, ...
+    #[darling(rename = "$")]
+    Numbered,
+    /// `?`
+    #[darling(rename = "?")]
+    Positional,
 }
 
 #[derive(FromDeriveInput)]
@@ -46,6 +235,184 @@ pub struct FabriqueAttrs {
     /// The table name for this model
     #[darling(default)]
     pub table: Option<String>,
+
+    /// A custom query replacing the generated `all()` query
+    #[darling(default)]
+    pub all_query: Option<String>,
+
+    /// Whether to generate an explicit `sqlx::FromRow` implementation
+    #[darling(default)]
+    pub from_row: bool,
+
+    /// Whether to generate `create_sql()`, exposing the `INSERT` run by `create()`
+    #[darling(default)]
+    pub dry_run_api: bool,
+
+    /// Whether to generate `copy_in()`, bulk loading records through Postgres `COPY`
+    #[darling(default)]
+    pub copy_in: bool,
+
+    /// Whether to implement `Trackable`, logging the records in a `SeedLog`
+    #[darling(default)]
+    pub trackable: bool,
+
+    /// Whether to generate `reload_all()`, refreshing many records in one Postgres query
+    #[darling(default)]
+    pub reload_all: bool,
+
+    /// Whether to generate `all_by_id()`, indexing every record by primary key
+    #[darling(default)]
+    pub all_by_id: bool,
+
+    /// Whether to generate `all_for_update()`, claiming the unlocked records of a work queue
+    #[darling(default)]
+    pub all_for_update: bool,
+
+    /// Whether to generate `which_exist()`, filtering the primary keys already stored
+    #[darling(default)]
+    pub which_exist: bool,
+
+    /// Whether to generate `paginate()`, fetching the records page by page
+    #[darling(default)]
+    pub paginate: bool,
+
+    /// Whether to generate `modified_since()`, fetching the records updated after a timestamp
+    #[darling(default)]
+    pub modified_since: bool,
+
+    /// A `WHERE` condition applied to every generated `SELECT`, e.g. `tenant_id = $TENANT`
+    #[darling(default)]
+    pub default_scope: Option<String>,
+
+    /// The function supplying the value of the parameter bound by the default scope
+    #[darling(default)]
+    pub scope_param: Option<Path>,
+
+    /// The time to live of the `all_cached()` results (e.g., `5s`)
+    #[darling(default)]
+    pub cache_all: Option<String>,
+
+    /// Whether to generate `create_batch()`, inserting several records per statement
+    #[darling(default)]
+    pub batch_insert: bool,
+
+    /// The maximum number of parameters bound by each `create_batch()` statement
+    #[darling(default)]
+    pub batch_max_params: Option<usize>,
+
+    /// Whether `create()` checks the non-nullable `Option` fields before inserting
+    #[darling(default)]
+    pub validate_not_null: bool,
+
+    /// The database backend targeted by the generated queries (e.g., `sqlite`)
+    #[darling(default)]
+    pub backend: Backend,
+
+    /// The placeholder style of the generated queries, overriding the one of the backend
+    /// (e.g., `?` for a proxy expecting it in front of Postgres)
+    #[darling(default)]
+    pub placeholder: Option<Placeholder>,
+
+    /// The column incremented by `update()` for optimistic locking (e.g., `lock_version`)
+    #[darling(default)]
+    pub version: Option<String>,
+
+    /// Whether `create()` and `update()` set the timestamp columns to the current time
+    #[darling(default)]
+    pub timestamps: bool,
+
+    /// The field holding the creation time, `created_at` by default
+    #[darling(default)]
+    pub created_at: Option<String>,
+
+    /// The field holding the time of the last update, `updated_at` by default
+    #[darling(default)]
+    pub updated_at: Option<String>,
+
+    /// The column marking records as soft deleted when set (e.g., `deleted_at`)
+    #[darling(default)]
+    pub soft_delete: Option<String>,
+
+    /// The event built from the created record by `create_with_event()` (e.g., `AnvilCreated`)
+    #[darling(default)]
+    pub emit_event: Option<Type>,
+
+    /// The columns of the unique index `get_or_create()` deduplicates against
+    #[darling(default)]
+    pub unique: Option<Vec<LitStr>>,
+}
+
+#[derive(FromField, Debug, Default, Clone)]
+#[darling(attributes(fabrique))]
+pub struct FabriqueFieldAttributes {
+    /// Whether the field is the primary key of the model
+    #[darling(default)]
+    pub primary_key: bool,
+
+    /// The related type (e.g., `Hammer` or `tools::Hammer`), or `Self` for self-referential
+    /// relations, given either as a bare path or as a string
+    #[darling(default)]
+    pub relation: Option<Path>,
+
+    /// The key of the related type this field references (e.g., `id`)
+    #[darling(default)]
+    pub referenced_key: Option<Ident>,
+
+    /// The trait implemented by the dynamically-resolved related objects
+    #[darling(default)]
+    pub relation_trait: Option<Type>,
+
+    /// The `cfg` predicate gating the relation (e.g., `feature = "hammers"`)
+    #[darling(default)]
+    pub cfg: Option<String>,
+
+    /// The group of relations sharing a single created related object (e.g., `pallet`)
+    #[darling(default)]
+    pub shared: Option<String>,
+
+    /// The closure configuring the related factory by default (e.g., `|f| f.weight(100)`)
+    #[darling(default)]
+    pub default_child: Option<Expr>,
+
+    /// Whether the column accepts `NULL`, checked by `#[fabrique(validate_not_null)]`
+    #[darling(default)]
+    pub nullable: Option<bool>,
+
+    /// Whether the field is a Postgres array column (e.g., `INTEGER[]`)
+    #[darling(default)]
+    pub array: bool,
+
+    /// Whether the column is assigned by the database (e.g., a serial primary key)
+    #[darling(default)]
+    pub generated: bool,
+
+    /// The name of the column, when it differs from the field name (e.g., `createdAt`)
+    #[darling(default)]
+    pub column: Option<String>,
+
+    /// Whether the field has no column and is left out of every generated query
+    #[darling(default)]
+    pub skip: bool,
+
+    /// The SQL expression the field is read from, e.g. `first_name || ' ' || last_name`
+    #[darling(default)]
+    pub returning_expr: Option<String>,
+
+    /// Whether to generate `count_distinct_<field>()`, counting the distinct values of the column
+    #[darling(default)]
+    pub distinct_countable: bool,
+
+    /// Whether `all_sorted_by()` can order by the column
+    #[darling(default)]
+    pub sortable: bool,
+
+    /// The table of the related type, to eager-load it with `find_with_<relation>()`
+    #[darling(default)]
+    pub related_table: Option<String>,
+
+    /// The columns of the related table, e.g. `id, name, forged_on AS made_at`
+    #[darling(default)]
+    pub related_columns: Option<String>,
 }
 
 impl<'a> AnalysisBuilder<'a> {
@@ -97,12 +464,151 @@ impl<'a> ParsedFields<'a> {
 
     /// Transistions to the next state.
     pub fn validate(self) -> Result<Analysis<'a>, Error> {
-        let table_name = FabriqueAttrs::from_derive_input(self.input)
-            .map_err(Error::UnparsableAttribute)?
+        let attrs =
+            FabriqueAttrs::from_derive_input(self.input).map_err(Error::UnparsableAttribute)?;
+
+        let scope_param = ScopeParam::new(&attrs)?;
+        let table_name = attrs
             .table
             .unwrap_or_else(|| format!("{}s", self.ident.to_string().to_lowercase()));
 
-        let analysis = Analysis::new(self.fields, self.ident, table_name);
+        let mut analysis = Analysis::new(self.fields, self.ident, table_name);
+        analysis.all_query = attrs.all_query;
+        analysis.primary_key = resolve_primary_key(self.fields)?;
+        analysis.from_row = attrs.from_row;
+        analysis.dry_run_api = attrs.dry_run_api;
+        analysis.copy_in = attrs.copy_in;
+        analysis.trackable = attrs.trackable;
+        analysis.reload_all = attrs.reload_all;
+        analysis.all_by_id = attrs.all_by_id;
+        analysis.all_for_update = attrs.all_for_update;
+        analysis.which_exist = attrs.which_exist;
+        analysis.paginate = attrs.paginate;
+        if analysis.paginate && analysis.all_query.is_some() {
+            return Err(Error::PaginatedCustomAllQuery);
+        }
+        analysis.backend = attrs.backend;
+        analysis.placeholder = attrs
+            .placeholder
+            .unwrap_or_else(|| attrs.backend.placeholder());
+        analysis.event = attrs.emit_event;
+        analysis.unique_columns = attrs
+            .unique
+            .unwrap_or_default()
+            .iter()
+            .map(LitStr::value)
+            .collect();
+
+        analysis.scope_param = scope_param;
+        analysis.default_scope = attrs.default_scope;
+
+        if let Some(ttl) = &attrs.cache_all {
+            let duration =
+                parse_duration(ttl).ok_or_else(|| Error::InvalidCacheTtl(ttl.clone()))?;
+            analysis.cache_ttl = Some(duration);
+        }
+
+        if attrs.batch_insert {
+            let max_params = attrs
+                .batch_max_params
+                .unwrap_or_else(|| attrs.backend.max_params());
+            analysis.batch_max_params = Some(max_params);
+        }
+
+        if let Some(version) = &attrs.version {
+            let field = self
+                .fields
+                .iter()
+                .find(|field| field.ident.as_ref().is_some_and(|ident| ident == version))
+                .ok_or_else(|| Error::UnknownVersionColumn(version.clone()))?;
+            analysis.version = Some(field);
+        }
+
+        if let Some(soft_delete) = &attrs.soft_delete {
+            let field = self
+                .fields
+                .iter()
+                .find(|field| {
+                    field
+                        .ident
+                        .as_ref()
+                        .is_some_and(|ident| ident == soft_delete)
+                })
+                .ok_or_else(|| Error::UnknownSoftDeleteColumn(soft_delete.clone()))?;
+            if !is_option(&field.ty) {
+                return Err(Error::NonOptionalSoftDeleteColumn(soft_delete.clone()));
+            }
+            analysis.soft_delete = Some(field);
+        }
+
+        if attrs.timestamps {
+            let find = |name: Option<String>, attribute: &'static str| {
+                let name = name.unwrap_or_else(|| attribute.to_owned());
+                self.fields
+                    .iter()
+                    .find(|field| field.ident.as_ref().is_some_and(|ident| ident == &name))
+                    .ok_or(Error::MissingTimestampField(name, attribute))
+            };
+            analysis.timestamps = Some(Timestamps {
+                created_at: find(attrs.created_at, "created_at")?,
+                updated_at: find(attrs.updated_at, "updated_at")?,
+            });
+        }
+
+        if attrs.modified_since && analysis.timestamps.is_none() {
+            return Err(Error::ModifiedSinceWithoutTimestamps);
+        }
+        analysis.modified_since = attrs.modified_since;
+
+        for field in self.fields {
+            let attributes = FabriqueFieldAttributes::from_field(field)?;
+            if attributes.array {
+                analysis.array_fields.push(field);
+            }
+            if attributes.generated {
+                analysis.generated_fields.push(field);
+            }
+            if let Some(column) = attributes.column.clone() {
+                analysis.renamed_columns.push((field, column));
+            }
+            if attributes.skip {
+                analysis.skipped_fields.push(field);
+            }
+            if let Some(expr) = attributes.returning_expr.clone() {
+                analysis.computed_fields.push((field, expr));
+            }
+            if attributes.distinct_countable {
+                analysis.distinct_countable_fields.push(field);
+            }
+            if attributes.sortable && !attributes.skip {
+                analysis.sortable_fields.push(field);
+            }
+            if attributes.related_table.is_some() {
+                analysis
+                    .joined_relations
+                    .push(JoinedRelation::new(field, attributes)?);
+            }
+        }
+
+        if let Some(found) = analysis
+            .all_query
+            .as_deref()
+            .and_then(count_selected_columns)
+        {
+            let expected = analysis.persisted_fields().count();
+            if found != expected {
+                return Err(Error::ColumnCountMismatch { expected, found });
+            }
+        }
+
+        if attrs.validate_not_null {
+            for field in self.fields {
+                let nullable = FabriqueFieldAttributes::from_field(field)?.nullable;
+                if nullable == Some(false) && is_option(&field.ty) {
+                    analysis.not_null_fields.push(field);
+                }
+            }
+        }
 
         Ok(analysis)
     }
@@ -115,6 +621,38 @@ impl<'a> Analysis<'a> {
             fields,
             ident,
             table_name,
+            all_query: None,
+            primary_key: None,
+            from_row: false,
+            dry_run_api: false,
+            copy_in: false,
+            trackable: false,
+            reload_all: false,
+            all_by_id: false,
+            all_for_update: false,
+            which_exist: false,
+            paginate: false,
+            modified_since: false,
+            default_scope: None,
+            scope_param: None,
+            cache_ttl: None,
+            batch_max_params: None,
+            not_null_fields: Vec::new(),
+            array_fields: Vec::new(),
+            generated_fields: Vec::new(),
+            renamed_columns: Vec::new(),
+            computed_fields: Vec::new(),
+            skipped_fields: Vec::new(),
+            distinct_countable_fields: Vec::new(),
+            sortable_fields: Vec::new(),
+            backend: Backend::Postgres,
+            placeholder: Placeholder::Numbered,
+            version: None,
+            timestamps: None,
+            soft_delete: None,
+            event: None,
+            unique_columns: Vec::new(),
+            joined_relations: Vec::new(),
         }
     }
 
@@ -127,6 +665,318 @@ impl<'a> Analysis<'a> {
 
         Ok(analysis)
     }
+
+    /// Returns the fields mapped to a column, i.e. every field but the skipped ones.
+    pub fn persisted_fields(&self) -> impl Iterator<Item = &'a Field> {
+        self.fields
+            .iter()
+            .filter(|field| !self.skipped_fields.contains(field))
+    }
+
+    /// Returns whether the given field is computed by `#[fabrique(returning_expr)]`.
+    pub fn is_computed(&self, field: &Field) -> bool {
+        self.computed_fields
+            .iter()
+            .any(|(computed, _)| *computed == field)
+    }
+
+    /// Returns the sql expression the given field is read from, i.e. the expression of a
+    /// computed field or the column name of the others.
+    pub fn column_expr(&self, field: &Field) -> String {
+        self.computed_fields
+            .iter()
+            .find(|(computed, _)| *computed == field)
+            .map(|(_, expr)| expr.clone())
+            .unwrap_or_else(|| self.column_name(field))
+    }
+
+    /// Returns whether the given field is a timestamp column set by the database.
+    pub fn is_timestamp(&self, field: &Field) -> bool {
+        self.timestamps.as_ref().is_some_and(|timestamps| {
+            timestamps.created_at == field || timestamps.updated_at == field
+        })
+    }
+
+    /// Returns the sql column name of the given field, honoring `#[fabrique(column)]`.
+    pub fn column_name(&self, field: &Field) -> String {
+        self.renamed_columns
+            .iter()
+            .find(|(renamed, _)| *renamed == field)
+            .map(|(_, column)| column.clone())
+            .or_else(|| field.ident.as_ref().map(|ident| ident.unraw().to_string()))
+            .unwrap_or_default()
+    }
+}
+
+impl ScopeParam {
+    /// Resolves the parameter bound by the default scope, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scope binds anything but a single named parameter supplied by
+    /// `scope_param`, if `scope_param` is set without such a parameter, or if it is combined
+    /// with another backend than Postgres, with `?` placeholders, which bind in the order they
+    /// appear, or with `cache_all`, whose records are shared by every value of the parameter.
+    fn new(attrs: &FabriqueAttrs) -> Result<Option<Self>, Error> {
+        let scope = attrs.default_scope.as_deref().unwrap_or_default();
+        let mut names = Vec::new();
+        for (index, _) in scope.match_indices('$') {
+            let name = scope[index + 1..]
+                .chars()
+                .take_while(|character| character.is_ascii_alphanumeric() || *character == '_')
+                .collect::<String>();
+            if !name
+                .starts_with(|character: char| character.is_ascii_alphabetic() || character == '_')
+            {
+                return Err(Error::ParameterizedDefaultScope(scope.to_owned()));
+            }
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        match (names.len(), &attrs.scope_param) {
+            (0, None) => Ok(None),
+            (1, Some(provider)) => {
+                if attrs.backend != Backend::Postgres
+                    || attrs.placeholder == Some(Placeholder::Positional)
+                    || attrs.cache_all.is_some()
+                {
+                    return Err(Error::UnsupportedScopeParam);
+                }
+                Ok(Some(Self {
+                    name: names.remove(0),
+                    provider: provider.clone(),
+                }))
+            }
+            (0, Some(_)) => Err(Error::UnusedScopeParam),
+            _ => Err(Error::ParameterizedDefaultScope(scope.to_owned())),
+        }
+    }
+}
+
+impl<'a> JoinedRelation<'a> {
+    /// Creates a joined relation from a field and its attributes.
+    ///
+    /// The relation name is derived by stripping the `referenced_key` suffix from the field
+    /// name if present, as for factory relations. Each related column is loaded into the field
+    /// of the same name, unless mapped to another one with `column AS field`.
+    fn new(field: &'a Field, attributes: FabriqueFieldAttributes) -> Result<Self, Error> {
+        let field_ident = field
+            .ident
+            .as_ref()
+            .ok_or(Error::UnsupportedDataStructureTupleStruct)?;
+        let field_name = field_ident.unraw().to_string();
+
+        let (Some(related_type), Some(referenced_key), Some(table), Some(columns)) = (
+            attributes.relation,
+            attributes.referenced_key,
+            attributes.related_table,
+            attributes.related_columns,
+        ) else {
+            return Err(Error::IncompleteJoinedRelation(field_name));
+        };
+
+        let name = field_name
+            .strip_suffix(&format!("_{referenced_key}"))
+            .unwrap_or(&field_name)
+            .to_owned();
+
+        let columns = columns
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                RelatedColumn::parse(entry).ok_or_else(|| {
+                    Error::InvalidRelatedColumn(
+                        field_name.clone(),
+                        entry.to_owned(),
+                        field_ident.span(),
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            field,
+            related_type,
+            referenced_key,
+            table,
+            columns,
+            name,
+        })
+    }
+}
+
+impl RelatedColumn {
+    /// Parses an entry of `related_columns`, either `column` or `column AS field`.
+    ///
+    /// Both sides must be identifiers, keywords such as `type` loading into raw fields.
+    fn parse(entry: &str) -> Option<Self> {
+        let parse_ident = |name: &str| {
+            syn::parse_str::<Ident>(name)
+                .or_else(|_| syn::parse_str::<Ident>(&format!("r#{name}")))
+                .ok()
+        };
+
+        let (column, field) = match entry.split_whitespace().collect::<Vec<_>>()[..] {
+            [column] => (parse_ident(column)?, parse_ident(column)?),
+            [column, keyword, field] if keyword.eq_ignore_ascii_case("as") => {
+                (parse_ident(column)?, parse_ident(field)?)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            column: column.unraw().to_string(),
+            field,
+        })
+    }
+}
+
+/// Resolves the primary key among the fields of a struct.
+///
+/// A field explicitly marked with `#[fabrique(primary_key)]` wins. When none is marked, a
+/// field named `id` is inferred as the primary key. Marking several fields is rejected, as
+/// composite keys are not supported.
+pub fn resolve_primary_key<'f>(
+    fields: impl IntoIterator<Item = &'f Field>,
+) -> Result<Option<&'f Field>, Error> {
+    let mut marked = Vec::new();
+    let mut inferred = None;
+
+    for field in fields {
+        if FabriqueFieldAttributes::from_field(field)?.primary_key {
+            marked.push(field);
+        }
+
+        if inferred.is_none() && field.ident.as_ref().is_some_and(|ident| ident == "id") {
+            inferred = Some(field);
+        }
+    }
+
+    if marked.len() > 1 {
+        let idents = marked.iter().filter_map(|field| field.ident.as_ref());
+        return Err(Error::MultiplePrimaryKeys(
+            idents.clone().map(ToString::to_string).collect(),
+            idents.map(|ident| ident.span()).collect(),
+        ));
+    }
+
+    Ok(marked.pop().or(inferred))
+}
+
+/// Whether the given type is an `Option`.
+pub fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Returns the item type of the given type if it is a `Vec`.
+pub fn vec_item(ty: &Type) -> Option<Type> {
+    wrapped_type(ty, "Vec")
+}
+
+/// Returns the inner type of the given type if it is an `Option`.
+pub fn option_item(ty: &Type) -> Option<Type> {
+    wrapped_type(ty, "Option")
+}
+
+/// Returns the single type argument of the given type if it is named `wrapper`.
+fn wrapped_type(ty: &Type, wrapper: &str) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(item) if arguments.args.len() == 1 => Some(item.clone()),
+        _ => None,
+    }
+}
+
+/// Parses a duration made of an integer and a unit (`ms`, `s`, `m` or `h`), e.g. `5s`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|character: char| !character.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+
+    match unit {
+        "ms" => Some(Duration::from_millis(amount)),
+        "s" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_secs(amount.checked_mul(60)?)),
+        "h" => Some(Duration::from_secs(amount.checked_mul(3600)?)),
+        _ => None,
+    }
+}
+
+/// Counts the columns selected by a `SELECT ... FROM ...` query.
+///
+/// This is a best-effort count of the top-level commas of the select list, returning `None`
+/// when the query cannot be analyzed (e.g. `SELECT *` or no `FROM` clause).
+fn count_selected_columns(query: &str) -> Option<usize> {
+    let query = query.trim();
+    let keyword = query.get(..6)?;
+    if !keyword.eq_ignore_ascii_case("select") {
+        return None;
+    }
+    let select_list = &query[6..];
+    let select_list = &select_list[..find_from_keyword(select_list)?];
+
+    if select_list.trim() == "*" {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    let mut columns = 1;
+    for character in select_list.chars() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => columns += 1,
+            _ => {}
+        }
+    }
+
+    Some(columns)
+}
+
+/// Finds the `FROM` keyword ending a select list, in any case and surrounded by whitespace of
+/// any kind, e.g. a line break.
+///
+/// The keywords of parenthesized subqueries are skipped, so the select list is only ended by
+/// the `FROM` clause of the query itself.
+fn find_from_keyword(select_list: &str) -> Option<usize> {
+    let bytes = select_list.as_bytes();
+    let mut depth = 0usize;
+    for (index, byte) in bytes.iter().enumerate() {
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            byte if depth == 0 && byte.is_ascii_whitespace() => {
+                let keyword = bytes.get(index + 1..index + 5)?;
+                let delimited = bytes.get(index + 5).is_some_and(u8::is_ascii_whitespace);
+                if keyword.eq_ignore_ascii_case(b"from") && delimited {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
 }
 
 #[cfg(test)]
@@ -286,4 +1136,912 @@ mod tests {
         // Assert the result is an error from darling (unknown field)
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_with_custom_all_query() {
+        // Arrange the analysis with a custom all query
+        let input = parse_quote! {
+            #[fabrique(all_query = "SELECT id, COALESCE(weight, 0) AS weight FROM anvils")]
+            struct Anvil {
+                id: u32,
+                weight: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result is ok and has the custom all query
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().all_query.as_deref(),
+            Some("SELECT id, COALESCE(weight, 0) AS weight FROM anvils")
+        );
+    }
+
+    #[test]
+    fn test_validate_with_paginated_custom_all_query_fails() {
+        // Arrange the analysis with a custom all query and pagination
+        let input = parse_quote! {
+            #[fabrique(all_query = "SELECT id FROM anvils ORDER BY id", paginate)]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result is a paginated custom all query error
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::PaginatedCustomAllQuery
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_mismatched_custom_all_query_fails() {
+        // Arrange the analysis with a custom all query selecting too few columns
+        let input = parse_quote! {
+            #[fabrique(all_query = "SELECT id FROM anvils")]
+            struct Anvil {
+                id: u32,
+                weight: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result is a column count mismatch
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ColumnCountMismatch {
+                expected: 2,
+                found: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_default_scope() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().default_scope.as_deref(),
+            Some("discontinued = false")
+        );
+    }
+
+    #[test]
+    fn test_validate_with_parameterized_default_scope() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(default_scope = "tenant_id = $TENANT", scope_param = "current_tenant")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        let scope_param = result.unwrap().scope_param.unwrap();
+        assert_eq!(scope_param.name, "TENANT");
+        assert_eq!(scope_param.provider, parse_quote!(current_tenant));
+    }
+
+    #[test]
+    fn test_validate_with_unsupplied_default_scope_parameter_fails() {
+        for scope in ["tenant_id = $1", "tenant_id = $TENANT"] {
+            // Arrange the analysis
+            let input: DeriveInput = parse_quote! {
+                #[fabrique(default_scope = #scope)]
+                struct Anvil {
+                    id: u32,
+                }
+            };
+
+            // Act the call to the Analysis::from method
+            let result = Analysis::from(&input);
+
+            // Assert the result
+            assert!(
+                matches!(result.unwrap_err(), Error::ParameterizedDefaultScope(_)),
+                "{scope}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_with_several_default_scope_parameters_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(
+                default_scope = "tenant_id = $TENANT AND region = $REGION",
+                scope_param = "current_tenant"
+            )]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ParameterizedDefaultScope(_)
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_unused_scope_param_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false", scope_param = "current_tenant")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnusedScopeParam));
+    }
+
+    #[test]
+    fn test_validate_with_scope_param_and_cache_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(
+                default_scope = "tenant_id = $TENANT",
+                scope_param = "current_tenant",
+                cache_all = "5s"
+            )]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnsupportedScopeParam));
+    }
+
+    #[test]
+    fn test_validate_with_scope_param_and_positional_placeholder_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(
+                default_scope = "tenant_id = $TENANT",
+                scope_param = "current_tenant",
+                placeholder = "?"
+            )]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnsupportedScopeParam));
+    }
+
+    #[test]
+    fn test_validate_with_unknown_soft_delete_column() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(soft_delete = "removed_at")]
+            struct Anvil {
+                id: u32,
+                deleted_at: Option<DateTime<Utc>>,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::UnknownSoftDeleteColumn(_))));
+    }
+
+    #[test]
+    fn test_validate_with_non_optional_soft_delete_column() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(soft_delete = "deleted_at")]
+            struct Anvil {
+                id: u32,
+                deleted_at: DateTime<Utc>,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::NonOptionalSoftDeleteColumn(_))));
+    }
+
+    #[test]
+    fn test_validate_with_timestamps() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(timestamps, updated_at = "reworked_at")]
+            struct Anvil {
+                id: u32,
+                created_at: DateTime<Utc>,
+                reworked_at: DateTime<Utc>,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert the default and overridden names are resolved
+        let timestamps = result.timestamps.unwrap();
+        assert_eq!(timestamps.created_at.ident.as_ref().unwrap(), "created_at");
+        assert_eq!(timestamps.updated_at.ident.as_ref().unwrap(), "reworked_at");
+    }
+
+    #[test]
+    fn test_validate_with_timestamps_without_field() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(timestamps)]
+            struct Anvil {
+                id: u32,
+                created_at: DateTime<Utc>,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(
+            result,
+            Err(Error::MissingTimestampField(name, _)) if name == "updated_at"
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_modified_since_without_timestamps_fails() {
+        // Arrange the analysis without timestamps
+        let input = parse_quote! {
+            #[fabrique(modified_since)]
+            struct Anvil {
+                id: u32,
+                updated_at: String,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::ModifiedSinceWithoutTimestamps)));
+    }
+
+    #[test]
+    fn test_validate_with_batch_insert() {
+        // Arrange the analyses
+        let default = parse_quote! {
+            #[fabrique(batch_insert, backend = "sqlite")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+        let configured = parse_quote! {
+            #[fabrique(batch_insert, batch_max_params = 1000)]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the calls to the Analysis::from method
+        let default = Analysis::from(&default).unwrap();
+        let configured = Analysis::from(&configured).unwrap();
+
+        // Assert the backend limit applies unless configured
+        assert_eq!(default.batch_max_params, Some(32766));
+        assert_eq!(configured.batch_max_params, Some(1000));
+    }
+
+    #[test]
+    fn test_validate_with_cache_all() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(cache_all = "500ms")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert_eq!(result.unwrap().cache_ttl, Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_validate_with_invalid_cache_all_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(cache_all = "5 seconds")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::InvalidCacheTtl(_)));
+    }
+
+    #[test]
+    fn test_validate_with_backend() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert_eq!(result.unwrap().backend, Backend::MySql);
+    }
+
+    #[test]
+    fn test_validate_with_unique_columns() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(unique = ["name", "city"])]
+            struct Workshop {
+                id: u32,
+                name: String,
+                city: String,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert_eq!(result.unwrap().unique_columns, vec!["name", "city"]);
+    }
+
+    #[test]
+    fn test_validate_with_placeholder() {
+        // Arrange the analyses
+        let default = parse_quote! {
+            #[fabrique(backend = "mysql")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+        let overridden = parse_quote! {
+            #[fabrique(backend = "mysql", placeholder = "$")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the calls to the Analysis::from method
+        let default = Analysis::from(&default);
+        let overridden = Analysis::from(&overridden);
+
+        // Assert the backend style applies unless overridden
+        assert_eq!(default.unwrap().placeholder, Placeholder::Positional);
+        assert_eq!(overridden.unwrap().placeholder, Placeholder::Numbered);
+    }
+
+    #[test]
+    fn test_validate_with_unknown_placeholder_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(placeholder = ":")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnparsableAttribute(_)));
+    }
+
+    #[test]
+    fn test_validate_with_unknown_backend_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(backend = "oracle")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnparsableAttribute(_)));
+    }
+
+    #[test]
+    fn test_validate_with_version() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(version = "lock_version")]
+            struct Anvil {
+                id: u32,
+                lock_version: i32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        let version = result
+            .unwrap()
+            .version
+            .and_then(|field| field.ident.clone());
+        assert_eq!(version.unwrap(), "lock_version");
+    }
+
+    #[test]
+    fn test_validate_with_unknown_version_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(version = "lock_version")]
+            struct Anvil {
+                id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::UnknownVersionColumn(column) if column == "lock_version"
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_joined_relation() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Stand {
+                id: u32,
+                #[fabrique(
+                    relation = "Hammer",
+                    referenced_key = "id",
+                    related_table = "hammers",
+                    related_columns = "id, name"
+                )]
+                hammer_id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        let analysis = result.unwrap();
+        assert_eq!(analysis.joined_relations.len(), 1);
+        let relation = &analysis.joined_relations[0];
+        assert_eq!(relation.name, "hammer");
+        assert_eq!(relation.table, "hammers");
+        let columns = relation
+            .columns
+            .iter()
+            .map(|column| (column.column.as_str(), column.field.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![("id", "id".to_owned()), ("name", "name".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_validate_with_joined_relation_mapped_columns() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Stand {
+                id: u32,
+                #[fabrique(
+                    relation = "Hammer",
+                    referenced_key = "id",
+                    related_table = "hammers",
+                    related_columns = "id, forged_on AS made_at, type"
+                )]
+                hammer_id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        let analysis = result.unwrap();
+        let columns = analysis.joined_relations[0]
+            .columns
+            .iter()
+            .map(|column| (column.column.as_str(), column.field.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                ("id", "id".to_owned()),
+                ("forged_on", "made_at".to_owned()),
+                ("type", "r#type".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_with_joined_relation_invalid_columns_fails() {
+        for columns in ["id, created-at", "id, \"order\"", "id AS", "id, self"] {
+            // Arrange the analysis
+            let input: DeriveInput = parse_quote! {
+                struct Stand {
+                    id: u32,
+                    #[fabrique(
+                        relation = "Hammer",
+                        referenced_key = "id",
+                        related_table = "hammers",
+                        related_columns = #columns
+                    )]
+                    hammer_id: u32,
+                }
+            };
+
+            // Act the call to the Analysis::from method
+            let result = Analysis::from(&input);
+
+            // Assert the result
+            assert!(
+                matches!(
+                    result.unwrap_err(),
+                    Error::InvalidRelatedColumn(field, _, _) if field == "hammer_id"
+                ),
+                "{columns}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_with_incomplete_joined_relation_fails() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Stand {
+                id: u32,
+                #[fabrique(relation = "Hammer", referenced_key = "id", related_table = "hammers")]
+                hammer_id: u32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::IncompleteJoinedRelation(field) if field == "hammer_id"
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_validate_not_null() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(validate_not_null)]
+            struct Hammer {
+                #[fabrique(nullable = false)]
+                id: Uuid,
+                #[fabrique(nullable = false)]
+                name: Option<String>,
+                handle: Option<String>,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert only the non-nullable Option field is checked
+        let names = result
+            .not_null_fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref());
+        assert_eq!(names.collect::<Vec<_>>(), ["name"]);
+    }
+
+    #[test]
+    fn test_validate_with_generated_primary_key() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key, generated)]
+                id: i64,
+                weight: i32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert only the generated field is left to the database
+        let names = result
+            .generated_fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref());
+        assert_eq!(names.collect::<Vec<_>>(), ["id"]);
+        assert_eq!(result.primary_key.unwrap().ident.as_ref().unwrap(), "id");
+    }
+
+    #[test]
+    fn test_validate_with_distinct_countable_fields() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Anvil {
+                id: i64,
+                #[fabrique(distinct_countable)]
+                material: String,
+                weight: i32,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert only the annotated field is counted
+        let names = result
+            .distinct_countable_fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref());
+        assert_eq!(names.collect::<Vec<_>>(), ["material"]);
+    }
+
+    #[test]
+    fn test_validate_without_generated_fields() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key)]
+                id: i64,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(result.unwrap().generated_fields.is_empty());
+    }
+
+    #[test]
+    fn test_validate_with_renamed_column() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Anvil {
+                id: i64,
+                #[fabrique(column = "forgedAt")]
+                forged_at: i64,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert only the renamed field is mapped to another column
+        let columns = result
+            .fields
+            .iter()
+            .map(|field| result.column_name(field))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, ["id", "forgedAt"]);
+        assert_eq!(result.renamed_columns.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_with_skipped_field() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            struct Hammer {
+                id: Uuid,
+                name: String,
+                #[fabrique(skip)]
+                display_name: String,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input).unwrap();
+
+        // Assert only the skipped field has no column
+        let names = result
+            .persisted_fields()
+            .filter_map(|field| field.ident.as_ref());
+        assert_eq!(names.collect::<Vec<_>>(), ["id", "name"]);
+        assert_eq!(result.skipped_fields.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_with_custom_all_query_ignores_skipped_fields() {
+        // Arrange the analysis
+        let input = parse_quote! {
+            #[fabrique(all_query = "SELECT id, name FROM hammers")]
+            struct Hammer {
+                id: Uuid,
+                name: String,
+                #[fabrique(skip)]
+                display_name: String,
+            }
+        };
+
+        // Act the call to the Analysis::from method
+        let result = Analysis::from(&input);
+
+        // Assert the result
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_count_selected_columns_skips_unanalyzable_queries() {
+        // Act the call to the count_selected_columns function
+        let wildcard = count_selected_columns("SELECT * FROM anvils");
+        let no_from = count_selected_columns("VALUES (1, 2)");
+
+        // Assert the queries are not analyzed
+        assert_eq!(wildcard, None);
+        assert_eq!(no_from, None);
+    }
+
+    #[test]
+    fn test_count_selected_columns_finds_any_from_keyword() {
+        // Act the calls to the count_selected_columns function
+        let multiline = count_selected_columns("select id,\n  weight\nFROM anvils");
+        let tabulated = count_selected_columns("SELECT id, weight\tFrom anvils");
+        let subquery = count_selected_columns(
+            "SELECT id, (SELECT MAX(weight) FROM anvils) AS weight FROM hammers",
+        );
+        let identifier = count_selected_columns("SELECT id, from_date FROM anvils");
+
+        // Assert every select list is delimited by the FROM clause of the query
+        assert_eq!(multiline, Some(2));
+        assert_eq!(tabulated, Some(2));
+        assert_eq!(subquery, Some(2));
+        assert_eq!(identifier, Some(2));
+    }
+
+    #[test]
+    fn test_resolve_primary_key_infers_id() {
+        // Arrange the fields
+        let input: DeriveInput = parse_quote! {
+            struct Anvil {
+                weight: u32,
+                id: u32,
+            }
+        };
+        let fields = AnalysisBuilder::new(&input)
+            .parse_struct()
+            .unwrap()
+            .parse_fields()
+            .unwrap()
+            .fields;
+
+        // Act the call to the resolve_primary_key function
+        let result = resolve_primary_key(fields);
+
+        // Assert the id field is detected as the primary key
+        let primary_key = result.unwrap().unwrap();
+        assert_eq!(primary_key.ident.as_ref().unwrap(), "id");
+    }
+
+    #[test]
+    fn test_resolve_primary_key_prefers_explicit_marking() {
+        // Arrange the fields
+        let input: DeriveInput = parse_quote! {
+            struct Anvil {
+                id: u32,
+                #[fabrique(primary_key)]
+                serial_number: u32,
+            }
+        };
+        let fields = AnalysisBuilder::new(&input)
+            .parse_struct()
+            .unwrap()
+            .parse_fields()
+            .unwrap()
+            .fields;
+
+        // Act the call to the resolve_primary_key function
+        let result = resolve_primary_key(fields);
+
+        // Assert the marked field is the primary key
+        let primary_key = result.unwrap().unwrap();
+        assert_eq!(primary_key.ident.as_ref().unwrap(), "serial_number");
+    }
+
+    #[test]
+    fn test_resolve_primary_key_handles_no_primary_key() {
+        // Arrange the fields
+        let input: DeriveInput = parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        };
+        let fields = AnalysisBuilder::new(&input)
+            .parse_struct()
+            .unwrap()
+            .parse_fields()
+            .unwrap()
+            .fields;
+
+        // Act the call to the resolve_primary_key function
+        let result = resolve_primary_key(fields);
+
+        // Assert no primary key is found
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_resolve_primary_key_rejects_multiple_markings() {
+        // Arrange the fields
+        let input: DeriveInput = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key)]
+                id: u32,
+                #[fabrique(primary_key)]
+                serial_number: u32,
+            }
+        };
+        let fields = AnalysisBuilder::new(&input)
+            .parse_struct()
+            .unwrap()
+            .parse_fields()
+            .unwrap()
+            .fields;
+
+        // Act the call to the resolve_primary_key function
+        let result = resolve_primary_key(fields);
+
+        // Assert both marked fields are reported
+        assert!(matches!(
+            result,
+            Err(Error::MultiplePrimaryKeys(names, spans))
+                if names == ["id", "serial_number"] && spans.len() == 2
+        ));
+    }
 }
@@ -1,3 +1,4 @@
+use proc_macro2::Span;
 use thiserror::Error as ThisError;
 
 /// Errors that can occur during factory derivation.
@@ -19,5 +20,131 @@ pub enum Error {
     UnsupportedDataStructureUnitStruct,
 
     #[error("Missing `referenced_key` attribute for relation {0}")]
-    MissingReferencedKey(String),
+    MissingReferencedKey(String, Span),
+
+    #[error("Custom `all_query` selects {found} columns but the struct has {expected} fields")]
+    ColumnCountMismatch { expected: usize, found: usize },
+
+    #[error(
+        "`paginate` cannot be combined with a custom `all_query`, whose order it cannot rely on"
+    )]
+    PaginatedCustomAllQuery,
+
+    #[error("`relation` and `relation_trait` cannot both be set on field {0}")]
+    ConflictingRelations(String, Span),
+
+    #[error("`rename` on field {0} must be a valid identifier, e.g. `kind`")]
+    InvalidRename(String, Span),
+
+    #[error("`relation_trait` on field {0} must name a single trait, e.g. `dyn Creatable`")]
+    InvalidRelationTrait(String, Span),
+
+    #[error(
+        "Only one of `default`, `default_fn`, `default_factory`, `sequence` and `no_default` can be set on field {0}"
+    )]
+    ConflictingDefaults(String),
+
+    #[error("`sequence_format` requires `sequence` to be set on field {0}")]
+    SequenceFormatWithoutSequence(String),
+
+    #[error("`no_default` cannot be set on relation field {0}")]
+    NoDefaultRelation(String),
+
+    #[error("`repeat` cannot be set on a factory with dynamic relations")]
+    RepeatWithDynamicRelations,
+
+    #[error("`tracked` cannot be set on a factory with dynamic relations")]
+    TrackedWithDynamicRelations,
+
+    #[error("`cfg` on field {0} must be a valid cfg predicate, e.g. `feature = \"hammers\"`")]
+    InvalidRelationCfg(String, Span),
+
+    #[error(
+        "`{0}` has no primary key, mark a field with `#[fabrique(primary_key)]` or name it `id`"
+    )]
+    MissingPrimaryKey(String),
+
+    #[error(
+        "Only one field can be marked with `#[fabrique(primary_key)]`, found `{}`",
+        .0.join("`, `")
+    )]
+    MultiplePrimaryKeys(Vec<String>, Vec<Span>),
+
+    #[error(
+        "`default_scope` can only bind one named parameter, e.g. `$TENANT`, supplied by `scope_param`, `{0}` given"
+    )]
+    ParameterizedDefaultScope(String),
+
+    #[error("`scope_param` requires a `default_scope` binding a named parameter, e.g. `$TENANT`")]
+    UnusedScopeParam,
+
+    #[error(
+        "`scope_param` requires the postgres backend and `$` placeholders, and cannot be combined with `cache_all`"
+    )]
+    UnsupportedScopeParam,
+
+    #[error("Relations of the shared group `{0}` must all reference the same type")]
+    MismatchedSharedRelations(String),
+
+    #[error("`cfg` and `shared` cannot both be set on field {0}")]
+    ConflictingSharedRelationCfg(String, Span),
+
+    #[error(
+        "Sortable fields {first} and {second} both map to the `{variant}` sort column, rename one of them"
+    )]
+    ConflictingSortColumns {
+        first: String,
+        second: String,
+        variant: String,
+        span: Span,
+    },
+
+    #[error("`version` must name a field of the struct, `{0}` given")]
+    UnknownVersionColumn(String),
+
+    #[error("`soft_delete` must name a field of the struct, `{0}` given")]
+    UnknownSoftDeleteColumn(String),
+
+    #[error("`soft_delete` field {0} must be an `Option`, as it is `NULL` until deleted")]
+    NonOptionalSoftDeleteColumn(String),
+
+    #[error("`timestamps` requires a `{0}` field, or another field named with `{1} = \"...\"`")]
+    MissingTimestampField(String, &'static str),
+
+    #[error("`modified_since` requires `timestamps`, as it filters on the `updated_at` field")]
+    ModifiedSinceWithoutTimestamps,
+
+    #[error(
+        "`related_table` on field {0} requires `relation`, `referenced_key` and `related_columns`"
+    )]
+    IncompleteJoinedRelation(String),
+
+    #[error(
+        "`related_columns` on field {0} must list identifiers, optionally mapped to a field as in `forged_on AS made_at`, `{1}` given"
+    )]
+    InvalidRelatedColumn(String, String, Span),
+
+    #[error("`cache_all` must be a duration such as `500ms`, `5s`, `1m` or `1h`, `{0}` given")]
+    InvalidCacheTtl(String),
+}
+
+impl Error {
+    /// Returns the span of the field the error originates from, if any.
+    ///
+    /// Errors without a span are reported against the whole derive input.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::MissingReferencedKey(_, span)
+            | Self::ConflictingRelations(_, span)
+            | Self::InvalidRelationTrait(_, span)
+            | Self::InvalidRename(_, span)
+            | Self::InvalidRelationCfg(_, span)
+            | Self::ConflictingSharedRelationCfg(_, span)
+            | Self::InvalidRelatedColumn(_, _, span)
+            | Self::ConflictingSortColumns { span, .. } => Some(*span),
+            // Point at the first duplicate rather than the key marked first
+            Self::MultiplePrimaryKeys(_, spans) => spans.get(1).copied(),
+            _ => None,
+        }
+    }
 }
@@ -1,6 +1,11 @@
-use darling::FromField;
-use syn::{Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, Ident, spanned::Spanned};
-
+use darling::util::Override;
+use darling::{FromDeriveInput, FromField, FromMeta};
+use syn::{
+    Data, DataStruct, DeriveInput, Expr, Field, Fields, FieldsNamed, Ident, Meta, Path, Type,
+    spanned::Spanned,
+};
+
+use crate::analysis::{FabriqueFieldAttributes, option_item, resolve_primary_key, vec_item};
 use crate::error::Error;
 
 /// Analyzes a derive input to extract factory-related information.
@@ -10,17 +15,91 @@ pub struct FactoryAnalysis {
     input: DeriveInput,
 }
 
+#[derive(FromDeriveInput, Debug, Default)]
+#[darling(attributes(factory))]
+pub struct FactoryAttrs {
+    /// Whether the struct implements `fabrique::Validate`
+    #[darling(default)]
+    validate: bool,
+
+    /// The type of the context shared by the field defaults
+    #[darling(default)]
+    context: Option<Type>,
+
+    /// The pointer wrapping the built objects
+    #[darling(default)]
+    output: Option<Output>,
+
+    /// Whether to generate a `mock()` constructor
+    #[darling(default)]
+    mock: bool,
+
+    /// Whether to delimit the generated items with markers
+    #[darling(default)]
+    pretty: bool,
+
+    /// Whether the setters and `build()` borrow the factory mutably instead of consuming it
+    #[darling(default)]
+    mut_builder: bool,
+
+    /// Whether to generate a `repeat()` method building several objects lazily
+    #[darling(default)]
+    repeat: bool,
+
+    /// The function building the template the unset fields are taken from
+    #[darling(default)]
+    build_with_defaults_from: Option<Path>,
+
+    /// The async function called with every persisted object before `create()` returns it
+    #[darling(default)]
+    after_create: Option<Path>,
+
+    /// Whether to generate a `create_tracked()` method logging the created objects
+    #[darling(default)]
+    tracked: bool,
+}
+
+/// The pointers `#[factory(output)]` can wrap the built objects into.
+#[derive(FromMeta, Debug, Clone, Copy, PartialEq)]
+#[darling(rename_all = "PascalCase")]
+pub enum Output {
+    /// `std::sync::Arc`
+    Arc,
+    /// `std::rc::Rc`
+    Rc,
+    /// `Box`
+    Box,
+}
+
 #[derive(FromField, Debug, Default, Clone)]
-#[darling(attributes(fabrique))]
-pub struct FabriqueFieldAttributes {
+#[darling(attributes(factory))]
+pub struct FactoryFieldAttributes {
+    #[darling(default)]
+    default: Option<Expr>,
+
+    #[darling(default)]
+    default_fn: Option<Path>,
+
+    #[darling(default)]
+    default_factory: Option<Path>,
+
+    #[darling(default)]
+    sequence: Option<Override<String>>,
+
     #[darling(default)]
-    primary_key: bool,
+    sequence_format: Option<String>,
 
     #[darling(default)]
-    relation: Option<Ident>,
+    validate_field: Option<Path>,
 
     #[darling(default)]
-    referenced_key: Option<Ident>,
+    validate_field_error: Option<Type>,
+
+    #[darling(default)]
+    no_default: bool,
+
+    #[darling(default)]
+    rename: Option<String>,
 }
 
 impl FactoryAnalysis {
@@ -31,10 +110,42 @@ impl FactoryAnalysis {
 
     /// Performs the analysis and returns the output.
     pub fn analyze(self) -> Result<FactoryAnalysisOutput, Error> {
-        Ok(FactoryAnalysisOutput {
+        let attributes = FactoryAttrs::from_derive_input(&self.input)?;
+
+        let output = FactoryAnalysisOutput {
             base_struct_ident: self.input.ident.clone(),
             fields: self.fields()?,
-        })
+            validate: attributes.validate,
+            context: attributes.context,
+            output: attributes.output,
+            mock: attributes.mock,
+            pretty: attributes.pretty,
+            mut_builder: attributes.mut_builder,
+            repeat: attributes.repeat,
+            build_with_defaults_from: attributes.build_with_defaults_from,
+            after_create: attributes.after_create,
+            tracked: attributes.tracked,
+        };
+
+        if output.repeat && output.dynamic_relations().next().is_some() {
+            return Err(Error::RepeatWithDynamicRelations);
+        }
+
+        if output.tracked && output.dynamic_relations().next().is_some() {
+            return Err(Error::TrackedWithDynamicRelations);
+        }
+
+        for (group, relations) in output.shared_relations() {
+            let (_, first) = relations[0];
+            if relations
+                .iter()
+                .any(|(_, relation)| relation.referenced_type != first.referenced_type)
+            {
+                return Err(Error::MismatchedSharedRelations(group.to_owned()));
+            }
+        }
+
+        Ok(output)
     }
 
     /// Returns the fields of a named struct.
@@ -60,15 +171,64 @@ impl FactoryAnalysis {
             Data::Union(_) => Err(Error::UnsupportedDataStructureUnion),
         }?;
 
+        let primary_key = resolve_primary_key(fields)?;
+
         fields
             .into_iter()
             .map(|field| -> Result<FactoryFieldAnalysisOutput, Error> {
                 let attributes = FabriqueFieldAttributes::from_field(field)?;
+                let factory_attributes = FactoryFieldAttributes::from_field(field)?;
+
+                if factory_attributes.no_default
+                    && (attributes.relation.is_some() || attributes.relation_trait.is_some())
+                {
+                    let name = field.ident.as_ref().expect("Named field").to_string();
+                    return Err(Error::NoDefaultRelation(name));
+                }
+
+                if factory_attributes.sequence_format.is_some()
+                    && factory_attributes.sequence.is_none()
+                {
+                    let name = field.ident.as_ref().expect("Named field").to_string();
+                    return Err(Error::SequenceFormatWithoutSequence(name));
+                }
+
+                // The rename of a relation field names the relation rather than the setter
+                let field_ident = field.ident.as_ref().expect("Named field");
+                let setter = match (&factory_attributes.rename, &attributes.relation) {
+                    (Some(rename), None) => {
+                        let mut setter = syn::parse_str::<Ident>(rename).map_err(|_| {
+                            Error::InvalidRename(field_ident.to_string(), field_ident.span())
+                        })?;
+                        setter.set_span(field_ident.span());
+                        setter
+                    }
+                    _ => field_ident.clone(),
+                };
 
                 Ok(FactoryFieldAnalysisOutput {
                     field: field.clone(),
-                    primary_key: attributes.primary_key,
-                    relation: Relation::new(field, attributes)?,
+                    setter,
+                    primary_key: primary_key == Some(field),
+                    sequence: Sequence::new(&self.input.ident, field, &factory_attributes)?,
+                    sequence_format: factory_attributes.sequence_format,
+                    default: factory_attributes.default,
+                    default_fn: factory_attributes.default_fn,
+                    default_factory: factory_attributes.default_factory,
+                    validate_field: factory_attributes.validate_field,
+                    validate_field_error: factory_attributes.validate_field_error,
+                    no_default: factory_attributes.no_default,
+                    vec_item: vec_item(&field.ty),
+                    option_item: option_item(&field.ty).filter(|_| {
+                        attributes.relation.is_none() && attributes.relation_trait.is_none()
+                    }),
+                    dynamic_relation: DynamicRelation::new(field, &attributes)?,
+                    relation: Relation::new(
+                        &self.input.ident,
+                        field,
+                        attributes,
+                        factory_attributes.rename,
+                    )?,
                 })
             })
             .collect::<Result<Vec<FactoryFieldAnalysisOutput>, Error>>()
@@ -82,6 +242,26 @@ pub struct FactoryAnalysisOutput {
     pub base_struct_ident: Ident,
     /// All named fields from the struct
     pub fields: Vec<FactoryFieldAnalysisOutput>,
+    /// Whether the built objects are validated through `fabrique::Validate`
+    pub validate: bool,
+    /// The type of the context the field defaults can read (e.g., `Workshop`)
+    pub context: Option<Type>,
+    /// The pointer wrapping the built objects (e.g., `Arc`)
+    pub output: Option<Output>,
+    /// Whether the original struct gets a `mock()` constructor
+    pub mock: bool,
+    /// Whether the generated items are delimited by markers for `cargo expand`
+    pub pretty: bool,
+    /// Whether the setters and `build()` take `&mut self` rather than `self`
+    pub mut_builder: bool,
+    /// Whether the factory gets a `repeat()` method
+    pub repeat: bool,
+    /// The function building the template the unset fields are taken from (e.g., `anvil_template`)
+    pub build_with_defaults_from: Option<Path>,
+    /// The async function called with every persisted object (e.g., `register_anvil`)
+    pub after_create: Option<Path>,
+    /// Whether the factory gets a `create_tracked()` method
+    pub tracked: bool,
 }
 
 impl FactoryAnalysisOutput {
@@ -93,14 +273,112 @@ impl FactoryAnalysisOutput {
                 .map(|relation| (&field.field, relation))
         })
     }
+
+    /// Returns the relations sharing a single created object, grouped in declaration order.
+    pub fn shared_relations(&self) -> Vec<(&str, Vec<(&Field, &Relation)>)> {
+        let mut groups: Vec<(&str, Vec<(&Field, &Relation)>)> = Vec::new();
+
+        for (field, relation) in self.relations() {
+            let Some(group) = relation.shared.as_deref() else {
+                continue;
+            };
+
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, relations)) => relations.push((field, relation)),
+                None => groups.push((group, vec![(field, relation)])),
+            }
+        }
+
+        groups
+    }
+
+    pub fn dynamic_relations(&self) -> impl Iterator<Item = (&Field, &DynamicRelation)> {
+        self.fields.iter().filter_map(|field| {
+            field
+                .dynamic_relation
+                .as_ref()
+                .map(|relation| (&field.field, relation))
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FactoryFieldAnalysisOutput {
     pub field: Field,
+    /// The name of the setter of the field, the field name unless `rename` is set
+    pub setter: Ident,
     #[allow(dead_code)]
     pub primary_key: bool,
+    /// The expression computing the default value of the field
+    pub default: Option<Expr>,
+    /// The function computing the default value of the field (e.g., `chrono::Utc::now`)
+    pub default_fn: Option<Path>,
+    /// The factory building the default value of the field (e.g., `HammerFactory`)
+    pub default_factory: Option<Path>,
+    /// The counter generating the default values of the field
+    pub sequence: Option<Sequence>,
+    /// The format string turning the sequence value `n` into the field value (e.g., `anvil-{n}`)
+    pub sequence_format: Option<String>,
+    /// The function validating the values set through the `try_` setter
+    pub validate_field: Option<Path>,
+    /// The error type of the validator, the one of `fabrique::Validate` unless set
+    pub validate_field_error: Option<Type>,
+    /// Whether the field has no default and must be given to the factory constructor
+    pub no_default: bool,
+    /// The item type of a `Vec` field, which gets an `extend_` setter
+    pub vec_item: Option<Type>,
+    /// The inner type of an `Option` field, which its setter takes unwrapped
+    pub option_item: Option<Type>,
     pub relation: Option<Relation>,
+    pub dynamic_relation: Option<DynamicRelation>,
+}
+
+/// Where the counter of a `#[factory(sequence)]` field lives.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sequence {
+    /// A counter owned by the field (e.g., `ANVIL_WEIGHT_SEQ`)
+    Owned(Ident),
+    /// A counter shared by every field declaring the same namespace
+    Shared(String),
+}
+
+impl Sequence {
+    /// Creates a sequence from the factory attributes of a field.
+    ///
+    /// Returns `None` if the field is not a sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the field declares more than one default.
+    pub fn new(
+        struct_ident: &Ident,
+        field: &Field,
+        attributes: &FactoryFieldAttributes,
+    ) -> Result<Option<Self>, Error> {
+        let name = field.ident.as_ref().expect("Named field").to_string();
+        let defaults = [
+            attributes.default.is_some(),
+            attributes.default_fn.is_some(),
+            attributes.default_factory.is_some(),
+            attributes.sequence.is_some(),
+            attributes.no_default,
+        ];
+        if defaults.into_iter().filter(|is_set| *is_set).count() > 1 {
+            return Err(Error::ConflictingDefaults(name));
+        }
+
+        let Some(sequence) = &attributes.sequence else {
+            return Ok(None);
+        };
+
+        Ok(Some(match sequence {
+            Override::Inherit => Self::Owned(Ident::new(
+                &format!("{}_{}_SEQ", struct_ident, name.trim_start_matches("r#")).to_uppercase(),
+                field.span(),
+            )),
+            Override::Explicit(namespace) => Self::Shared(namespace.clone()),
+        }))
+    }
 }
 
 /// Represents a factory relation extracted from struct field attributes.
@@ -108,50 +386,152 @@ pub struct FactoryFieldAnalysisOutput {
 pub struct Relation {
     /// The identifier for the factory field (e.g., `anvil_factory`)
     pub factory_field: Ident,
-    /// The type of the referenced object (e.g., `Anvil`)
-    pub referenced_type: Ident,
+    /// The identifier for the field holding the creation callback (e.g., `anvil_on_created`)
+    pub on_created_field: Ident,
+    /// The type of the referenced object (e.g., `Anvil` or `tools::Anvil`)
+    pub referenced_type: Path,
     /// The field of the referenced object referenced by this relation (e.g. `id`)
     pub referenced_key: Ident,
     /// The base name of the relation (e.g., `anvil`)
     pub name: String,
+    /// The `cfg` predicate gating the relation (e.g., `feature = "hammers"`)
+    pub cfg: Option<Meta>,
+    /// Whether the relation references the struct it is declared on
+    pub self_referential: bool,
+    /// The group of relations sharing a single created related object, if any
+    pub shared: Option<String>,
+    /// The closure configuring the related factory when the relation is not set explicitly
+    pub default_child: Option<Expr>,
 }
 
 impl Relation {
     /// Creates a new relation from a field and its factory type.
     ///
     /// Automatically derives the relation name by stripping the `referenced_key` suffix
-    /// from the field name if present.
-    pub fn new(field: &Field, attributes: FabriqueFieldAttributes) -> Result<Option<Self>, Error> {
+    /// from the field name if present, unless `rename` names the relation explicitly. A
+    /// `Self` relation resolves to the struct itself.
+    pub fn new(
+        struct_ident: &Ident,
+        field: &Field,
+        attributes: FabriqueFieldAttributes,
+        rename: Option<String>,
+    ) -> Result<Option<Self>, Error> {
         if attributes.relation.is_none() {
             return Ok(None);
         }
 
-        let referenced_type = attributes.relation.unwrap();
+        let referenced_type = match attributes.relation.unwrap() {
+            relation if relation.is_ident("Self") => Path::from(struct_ident.clone()),
+            relation => relation,
+        };
+        let self_referential = referenced_type.is_ident(struct_ident);
 
         let field = field.clone();
 
-        let field_name = field
+        let field_ident = field
             .ident
             .as_ref()
-            .ok_or(Error::UnsupportedDataStructureTupleStruct)?
-            .to_string();
+            .ok_or(Error::UnsupportedDataStructureTupleStruct)?;
+        let field_name = field_ident.to_string();
+        let field_span = field_ident.span();
 
         let referenced_key = attributes
             .referenced_key
-            .ok_or(Error::MissingReferencedKey(field_name.clone()))?;
+            .ok_or_else(|| Error::MissingReferencedKey(field_name.clone(), field_span))?;
 
-        let name = field_name
-            .strip_suffix(&format!("_{}", referenced_key))
-            .unwrap_or(&field_name)
-            .to_owned();
+        if let Some(rename) = &rename
+            && syn::parse_str::<Ident>(rename).is_err()
+        {
+            return Err(Error::InvalidRename(field_name, field_span));
+        }
+
+        let name = rename.unwrap_or_else(|| {
+            field_name
+                .strip_suffix(&format!("_{}", referenced_key))
+                .unwrap_or(&field_name)
+                .to_owned()
+        });
 
         let ident = Ident::new(&format!("{}_factory", &name), field.span());
+        let on_created_field = Ident::new(&format!("{}_on_created", &name), field.span());
+
+        if attributes.cfg.is_some() && attributes.shared.is_some() {
+            return Err(Error::ConflictingSharedRelationCfg(field_name, field_span));
+        }
+
+        let cfg = attributes
+            .cfg
+            .map(|predicate| syn::parse_str::<Meta>(&predicate))
+            .transpose()
+            .map_err(|_| Error::InvalidRelationCfg(field_name, field_span))?;
 
         Ok(Some(Self {
             factory_field: ident,
+            on_created_field,
             referenced_type,
             referenced_key,
             name,
+            cfg,
+            self_referential,
+            shared: attributes.shared,
+            default_child: attributes.default_child,
+        }))
+    }
+}
+
+/// Represents a relation resolved at runtime through a `Creatable` trait object.
+#[derive(Debug, Clone)]
+pub struct DynamicRelation {
+    /// The identifier for the factory field (e.g., `tool_creatable`)
+    pub factory_field: Ident,
+    /// The path of the trait the related object implements (e.g., `Creatable`)
+    pub trait_path: Path,
+    /// The base name of the relation (e.g., `tool`)
+    pub name: String,
+}
+
+impl DynamicRelation {
+    /// Creates a new dynamic relation from a field and its attributes.
+    ///
+    /// The relation name is derived by stripping the `_id` suffix from the field name if present.
+    pub fn new(field: &Field, attributes: &FabriqueFieldAttributes) -> Result<Option<Self>, Error> {
+        let Some(relation_trait) = &attributes.relation_trait else {
+            return Ok(None);
+        };
+
+        let field_ident = field
+            .ident
+            .as_ref()
+            .ok_or(Error::UnsupportedDataStructureTupleStruct)?;
+        let field_name = field_ident.to_string();
+        let field_span = field_ident.span();
+
+        if attributes.relation.is_some() {
+            return Err(Error::ConflictingRelations(field_name, field_span));
+        }
+
+        let trait_path = match relation_trait {
+            Type::Path(type_path) if type_path.qself.is_none() => type_path.path.clone(),
+            Type::TraitObject(trait_object) => match trait_object.bounds.first() {
+                Some(syn::TypeParamBound::Trait(bound)) if trait_object.bounds.len() == 1 => {
+                    bound.path.clone()
+                }
+                _ => return Err(Error::InvalidRelationTrait(field_name, field_span)),
+            },
+            _ => return Err(Error::InvalidRelationTrait(field_name, field_span)),
+        };
+
+        let name = field_name
+            .strip_suffix("_id")
+            .unwrap_or(&field_name)
+            .to_owned();
+
+        let ident = Ident::new(&format!("{}_creatable", &name), field.span());
+
+        Ok(Some(Self {
+            factory_field: ident,
+            trait_path,
+            name,
         }))
     }
 }
@@ -182,6 +562,7 @@ mod tests {
         let result = result.unwrap();
         assert_eq!(result.base_struct_ident.to_string(), "Anvil");
         assert_eq!(result.fields.len(), 3);
+        assert!(!result.validate);
 
         assert!(
             result
@@ -221,7 +602,8 @@ mod tests {
                     assert!(field.relation.is_some());
                     let relation = field.relation.as_ref().unwrap();
                     assert_eq!(relation.factory_field.to_string(), "hammer_factory");
-                    assert_eq!(relation.referenced_type.to_string(), "Hammer");
+                    assert_eq!(relation.on_created_field.to_string(), "hammer_on_created");
+                    assert!(relation.referenced_type.is_ident("Hammer"));
                     assert_eq!(relation.referenced_key.to_string(), "id");
                     assert_eq!(relation.name, "hammer");
 
@@ -231,6 +613,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_analyze_handles_validate_attribute() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            #[factory(validate)]
+            struct Anvil {
+                weight: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = analysis.analyze();
+
+        // Assert the result
+        assert!(result.is_ok());
+        assert!(result.unwrap().validate);
+    }
+
     #[test]
     fn test_analyze_fails_explicitly_on_unknown_attribute() {
         // Arrange the analysis
@@ -315,7 +715,7 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(
             result.unwrap_err(),
-            Error::MissingReferencedKey(rel) if rel == "hammer_id"
+            Error::MissingReferencedKey(rel, _) if rel == "hammer_id"
         ));
     }
 
@@ -384,53 +784,683 @@ mod tests {
     }
 
     #[test]
-    fn test_the_fields_method_handles_different_annotations() {
+    fn test_the_fields_method_handles_dynamic_relations() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result[0].relation.is_none());
+        let relation = result[0].dynamic_relation.as_ref().unwrap();
+        assert_eq!(relation.factory_field.to_string(), "tool_creatable");
+        assert!(relation.trait_path.is_ident("Creatable"));
+        assert_eq!(relation.name, "tool");
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_conflicting_relations() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Toolbox {
+                #[fabrique(relation = "Hammer", referenced_key = "id", relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ConflictingRelations(field, _) if field == "tool_id"
+        ));
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_invalid_relation_trait() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable + Sync")]
+                tool_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::InvalidRelationTrait(field, _) if field == "tool_id"
+        ));
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_default_functions() {
         // Arrange the analysis
         let analysis = FactoryAnalysis::from(parse_quote! {
             struct Anvil {
-                #[foo]
-                density: u32,
+                #[factory(default_fn = "chrono::Utc::now")]
+                forged_at: DateTime<Utc>,
+                weight: u32,
             }
         });
 
-        // Act the call to the analyze method
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        let result = result.unwrap();
+        let default_fn: Path = parse_quote!(chrono::Utc::now);
+        assert_eq!(result[0].default_fn, Some(default_fn));
+        assert!(result[1].default_fn.is_none());
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_default_with_default_fn() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(default = "500", default_fn = "heaviest_weight")]
+                weight: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ConflictingDefaults(field) if field == "weight"
+        ));
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_default_factories() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(default_factory = "HammerFactory")]
+                hammer: Hammer,
+                weight: u32,
+            }
+        });
+
+        // Act the call to the fields method
         let result = analysis.fields();
 
         // Assert the result
         assert!(result.is_ok());
-        assert_eq!(
-            result
+        let result = result.unwrap();
+        assert!(
+            result[0]
+                .default_factory
+                .as_ref()
                 .unwrap()
-                .iter()
-                .filter(|field| field.relation.is_none())
-                .count(),
-            1
+                .is_ident("HammerFactory")
         );
+        assert!(result[1].default_factory.is_none());
     }
 
     #[test]
-    fn test_a_relation_can_be_created() {
-        // Arrange the relation
-        let factory = FactoryAnalysis::from(parse_quote! {
+    fn test_the_fields_method_handles_sequences() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
             struct Anvil {
-                hammer_id: u32,
+                #[factory(sequence)]
+                serial_number: u32,
+                #[factory(sequence = "inventory")]
+                inventory_number: u32,
+                weight: u32,
             }
         });
-        let field = &factory.fields().unwrap()[0];
 
-        // Act the relation instantiation
-        let result = Relation::new(
-            &field.field,
-            FabriqueFieldAttributes {
-                relation: Some(Ident::new("Hammer", field.field.span())),
-                referenced_key: Some(Ident::new("id", field.field.span())),
-                ..Default::default()
-            },
-        );
+        // Act the call to the fields method
+        let result = analysis.fields();
 
         // Assert the result
         assert!(result.is_ok());
-        assert!(result.unwrap().is_some());
+        let result = result.unwrap();
+        assert_eq!(
+            result[0].sequence,
+            Some(Sequence::Owned(parse_quote!(ANVIL_SERIAL_NUMBER_SEQ)))
+        );
+        assert_eq!(
+            result[1].sequence,
+            Some(Sequence::Shared("inventory".to_string()))
+        );
+        assert!(result[2].sequence.is_none());
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_sequence_formats() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence, sequence_format = "anvil-{n}")]
+                name: String,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        let result = result.unwrap();
+        assert_eq!(result[0].sequence_format.as_deref(), Some("anvil-{n}"));
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_renamed_setters() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(rename = "kind")]
+                r#type: String,
+                weight: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        let result = result.unwrap();
+        assert_eq!(result[0].setter, "kind");
+        assert_eq!(result[1].setter, "weight");
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_invalid_setter_rename() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(rename = "type")]
+                r#type: String,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::InvalidRename(name, _)) if name == "r#type"));
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_multiple_primary_keys() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key)]
+                id: u32,
+                #[fabrique(primary_key)]
+                serial_number: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::MultiplePrimaryKeys(names, _)) if names.len() == 2));
+    }
+
+    #[test]
+    fn test_the_fields_method_detects_vec_fields() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                serial_numbers: Vec<u32>,
+                weights: std::vec::Vec<u32>,
+                weight: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        let result = result.unwrap();
+        let item: Type = parse_quote!(u32);
+        assert_eq!(result[0].vec_item, Some(item.clone()));
+        assert_eq!(result[1].vec_item, Some(item));
+        assert!(result[2].vec_item.is_none());
+    }
+
+    #[test]
+    fn test_the_fields_method_detects_option_fields() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                brand: Option<String>,
+                weight: u32,
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: Option<u32>,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert only the plain optional field is unwrapped
+        let result = result.unwrap();
+        let inner: Type = parse_quote!(String);
+        assert_eq!(result[0].option_item, Some(inner));
+        assert!(result[1].option_item.is_none());
+        assert!(result[2].option_item.is_none());
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_sequence_format_without_sequence() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence_format = "anvil-{n}")]
+                name: String,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::SequenceFormatWithoutSequence(field) if field == "name"
+        ));
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_conflicting_defaults() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence, default_factory = "HammerFactory")]
+                hammer: Hammer,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ConflictingDefaults(field) if field == "hammer"
+        ));
+    }
+
+    #[test]
+    fn test_the_fields_method_fails_explicitly_on_a_relation_without_default() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                #[factory(no_default)]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::NoDefaultRelation(field) if field == "hammer_id"
+        ));
+    }
+
+    #[test]
+    fn test_analyze_handles_context() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            #[factory(context = "Workshop")]
+            struct Anvil {
+                #[factory(default = "self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)")]
+                workshop_id: u32,
+                weight: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = analysis.analyze();
+
+        // Assert the result
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.context, Some(parse_quote!(Workshop)));
+        assert!(result.fields[0].default.is_some());
+        assert!(result.fields[1].default.is_none());
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_literal_defaults() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[factory(default = "5 * 100")]
+                weight: u32,
+                #[factory(default = "\"anvil\".to_string()")]
+                name: String,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = analysis.fields();
+
+        // Assert the defaults were parsed as expressions
+        let result = result.unwrap();
+        let weight: Expr = parse_quote!(5 * 100);
+        let name: Expr = parse_quote!("anvil".to_string());
+        assert_eq!(result[0].default, Some(weight));
+        assert_eq!(result[1].default, Some(name));
+    }
+
+    #[test]
+    fn test_analyze_handles_output() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            #[factory(output = "Rc")]
+            struct Anvil {
+                weight: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = analysis.analyze();
+
+        // Assert the result
+        assert_eq!(result.unwrap().output, Some(Output::Rc));
+    }
+
+    #[test]
+    fn test_analyze_fails_explicitly_on_unknown_output() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            #[factory(output = "Cow")]
+            struct Anvil {
+                weight: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = analysis.analyze();
+
+        // Assert the result
+        assert!(matches!(result.unwrap_err(), Error::UnparsableAttribute(_)));
+    }
+
+    #[test]
+    fn test_the_fields_method_handles_different_annotations() {
+        // Arrange the analysis
+        let analysis = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[foo]
+                density: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = analysis.fields();
+
+        // Assert the result
+        assert!(result.is_ok());
+        assert_eq!(
+            result
+                .unwrap()
+                .iter()
+                .filter(|field| field.relation.is_none())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_a_relation_can_be_created() {
+        // Arrange the relation
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                hammer_id: u32,
+            }
+        });
+        let field = &factory.fields().unwrap()[0];
+
+        // Act the relation instantiation
+        let result = Relation::new(
+            &parse_quote!(Anvil),
+            &field.field,
+            FabriqueFieldAttributes {
+                relation: Some(parse_quote!(Hammer)),
+                referenced_key: Some(Ident::new("id", field.field.span())),
+                ..Default::default()
+            },
+            None,
+        );
+
+        // Assert the result
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_relations_to_the_same_type_get_distinct_names() {
+        // Arrange the analysis
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Shipment {
+                #[fabrique(relation = "Warehouse", referenced_key = "id")]
+                source_warehouse_id: u32,
+                #[fabrique(relation = "Warehouse", referenced_key = "id")]
+                destination_warehouse_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields().unwrap();
+
+        // Assert the names keep the full field name minus the referenced key
+        let source = result[0].relation.clone().unwrap();
+        let destination = result[1].relation.clone().unwrap();
+        assert_eq!(source.name, "source_warehouse");
+        assert_eq!(destination.name, "destination_warehouse");
+        assert_eq!(source.factory_field, "source_warehouse_factory");
+        assert_eq!(destination.factory_field, "destination_warehouse_factory");
+        assert_eq!(source.on_created_field, "source_warehouse_on_created");
+        assert_eq!(
+            destination.on_created_field,
+            "destination_warehouse_on_created"
+        );
+    }
+
+    #[test]
+    fn test_a_relation_can_be_renamed() {
+        // Arrange the analysis
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                #[factory(rename = "striker")]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields();
+
+        // Assert the rename overrides the derived relation name
+        let relation = result.unwrap()[0].relation.clone().unwrap();
+        assert_eq!(relation.name, "striker");
+        assert_eq!(relation.factory_field, "striker_factory");
+        assert_eq!(relation.on_created_field, "striker_on_created");
+    }
+
+    #[test]
+    fn test_a_relation_rename_must_be_an_identifier() {
+        // Arrange the analysis
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                #[factory(rename = "heavy-hammer")]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result,
+            Err(Error::InvalidRename(name, _)) if name == "hammer_id"
+        ));
+    }
+
+    #[test]
+    fn test_a_relation_can_reference_self() {
+        // Arrange the relation
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Node {
+                id: u32,
+                #[fabrique(relation = "Self", referenced_key = "id")]
+                parent_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields();
+
+        // Assert the relation resolves to the struct itself
+        let relation = result.unwrap()[1].relation.clone().unwrap();
+        assert!(relation.referenced_type.is_ident("Node"));
+        assert!(relation.self_referential);
+    }
+
+    #[test]
+    fn test_a_relation_can_be_given_as_a_bare_path() {
+        // Arrange the relation
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = Hammer, referenced_key = "id")]
+                hammer_id: u32,
+                #[fabrique(relation = tools::Tongs, referenced_key = "id")]
+                tongs_id: u32,
+                #[fabrique(relation = Self, referenced_key = "id")]
+                parent_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields().unwrap();
+
+        // Assert the relations resolve to the given paths
+        let hammer = result[0].relation.clone().unwrap();
+        assert!(hammer.referenced_type.is_ident("Hammer"));
+        let tongs = result[1].relation.clone().unwrap();
+        let tongs_type: Path = parse_quote!(tools::Tongs);
+        assert_eq!(tongs.referenced_type, tongs_type);
+        let parent = result[2].relation.clone().unwrap();
+        assert!(parent.referenced_type.is_ident("Anvil"));
+        assert!(parent.self_referential);
+    }
+
+    #[test]
+    fn test_a_relation_can_be_gated_by_cfg() {
+        // Arrange the relation
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id", cfg = "feature = \"hammers\"")]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields();
+
+        // Assert the result
+        let relation = result.unwrap()[0].relation.clone().unwrap();
+        assert_eq!(relation.cfg, Some(parse_quote!(feature = "hammers")));
+    }
+
+    #[test]
+    fn test_a_relation_fails_explicitly_on_invalid_cfg() {
+        // Arrange the relation
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id", cfg = "feature =")]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the fields method
+        let result = factory.fields();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::InvalidRelationCfg(field, _) if field == "hammer_id"
+        ));
+    }
+
+    #[test]
+    fn test_relations_can_share_a_related_object() {
+        // Arrange the analysis
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Shipment {
+                #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+                pallet_id: u32,
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+                #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+                return_pallet_id: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = factory.analyze().unwrap();
+
+        // Assert the shared relations are grouped
+        let groups = result.shared_relations();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, "pallet");
+        let names = groups[0]
+            .1
+            .iter()
+            .map(|(_, relation)| relation.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["pallet", "return_pallet"]);
+    }
+
+    #[test]
+    fn test_shared_relations_fail_explicitly_on_mismatched_types() {
+        // Arrange the analysis
+        let factory = FactoryAnalysis::from(parse_quote! {
+            struct Shipment {
+                #[fabrique(relation = "Pallet", referenced_key = "id", shared = "cargo")]
+                pallet_id: u32,
+                #[fabrique(relation = "Hammer", referenced_key = "id", shared = "cargo")]
+                hammer_id: u32,
+            }
+        });
+
+        // Act the call to the analyze method
+        let result = factory.analyze();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::MismatchedSharedRelations(group) if group == "cargo"
+        ));
     }
 
     #[test]
@@ -457,12 +1487,14 @@ mod tests {
 
         // Act the relation instantiation
         let result = Relation::new(
+            &parse_quote!(Anvil),
             &field,
             FabriqueFieldAttributes {
-                relation: Some(Ident::new("Hammer", field.span())),
+                relation: Some(parse_quote!(Hammer)),
                 referenced_key: Some(Ident::new("id", field.span())),
                 ..Default::default()
             },
+            None,
         );
 
         // Assert the result
@@ -1,8 +1,26 @@
+use crate::analysis::option_item;
 use crate::error::Error;
-use crate::factory::analysis::{FactoryAnalysis, FactoryAnalysisOutput};
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{DeriveInput, Ident};
+use crate::factory::analysis::{
+    DynamicRelation, FactoryAnalysis, FactoryAnalysisOutput, FactoryFieldAnalysisOutput, Output,
+    Relation, Sequence,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{
+    DeriveInput, Field, GenericArgument, Ident, Path, PathArguments, Type, ext::IdentExt,
+    spanned::Spanned,
+};
+
+/// The variants of the generated methods creating an object along with its relations.
+#[derive(Clone, Copy, PartialEq)]
+enum CreateMode {
+    /// `create()`, returning the created object
+    Plain,
+    /// `create_with_stats()`, also counting the inserted rows
+    WithStats,
+    /// `create_tracked()`, also logging the created objects into a `fabrique::SeedLog`
+    Tracked,
+}
 
 /// Code generator for factory struct implementations.
 pub struct FactoryCodegen {
@@ -27,270 +45,3736 @@ impl FactoryCodegen {
         let base_struct_ident = &self.analysis.base_struct_ident;
         let factory_ident = Self::generate_factory_ident(&self.input.ident);
         let factory_fields = self.generate_factory_fields();
+        let factory_method_build = self.generate_factory_method_build();
+        let factory_method_finalize = self.generate_factory_method_finalize();
+        let factory_method_repeat = self.generate_factory_method_repeat();
+        let factory_method_try_build_validated = self.generate_factory_method_try_build_validated();
         let factory_method_create = self.generate_factory_method_create();
+        let factory_method_create_with_stats = self.generate_factory_method_create_with_stats();
+        let factory_method_create_without_relations =
+            self.generate_factory_method_create_without_relations();
+        let factory_method_create_tracked = self.generate_factory_method_create_tracked();
+        let factory_method_create_many = self.generate_factory_method_create_many();
+        let factory_method_create_many_with = self.generate_factory_method_create_many_with();
         let factory_method_new = self.generate_factory_method_new();
+        let factory_const_relations = self.generate_factory_const_relations();
         let factory_method_fields = self.generate_factory_method_fields();
+        let factory_methods_try_fields = self.generate_factory_methods_try_fields();
+        let factory_methods_extend_fields = self.generate_factory_methods_extend_fields();
         let factory_methods_for_relation = self.generate_factory_methods_for_relation();
+        let factory_methods_for_relation_opt = self.generate_factory_methods_for_relation_opt();
+        let factory_methods_with_relation = self.generate_factory_methods_with_relation();
         let factory_relation_fields = self.generate_factory_relation_fields();
+        let factory_relation_callback_fields = self.generate_factory_relation_callback_fields();
+        let factory_methods_on_relation_created =
+            self.generate_factory_methods_on_relation_created();
+        let factory_methods_for_dynamic_relation =
+            self.generate_factory_methods_for_dynamic_relation();
+        let factory_dynamic_relation_fields = self.generate_factory_dynamic_relation_fields();
+        let factory_context_field = self.generate_factory_context_field();
+        let factory_method_with_context = self.generate_factory_method_with_context();
+
+        let factory_items = std::iter::once(factory_const_relations)
+            .chain(std::iter::once(factory_method_new))
+            .chain(std::iter::once(factory_method_build))
+            .chain(std::iter::once(factory_method_finalize))
+            .chain(factory_method_repeat)
+            .chain(factory_method_try_build_validated)
+            .chain(std::iter::once(factory_method_create))
+            .chain(std::iter::once(factory_method_create_with_stats))
+            .chain(std::iter::once(factory_method_create_without_relations))
+            .chain(factory_method_create_tracked)
+            .chain(factory_method_create_many)
+            .chain(factory_method_create_many_with)
+            .chain(factory_method_fields)
+            .chain(factory_methods_try_fields)
+            .chain(factory_methods_extend_fields)
+            .chain(factory_methods_for_relation)
+            .chain(factory_methods_for_relation_opt)
+            .chain(factory_methods_with_relation)
+            .chain(factory_methods_on_relation_created)
+            .chain(factory_methods_for_dynamic_relation)
+            .chain(factory_method_with_context)
+            .map(|item| self.generate_pretty_marker(item));
+
+        let method_mock = self.generate_method_mock();
+
+        let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
+        let required_params = self.generate_required_params();
+        let required_names = self.required_fields().map(|field| &field.field.ident);
+        let derive_clone = self.analysis.repeat.then(|| quote! { #[derive(Clone)] });
+        let impl_default = self.generate_factory_impl_default();
+        let impl_debug = self.generate_factory_impl_debug();
+        let sequence_counters = self.generate_sequence_counters();
 
         quote! {
-            impl #base_struct_ident {
-                pub fn factory() -> #factory_ident {
-                    #factory_ident::new()
+            #[automatically_derived]
+            impl #impl_generics #base_struct_ident #ty_generics #where_clause {
+                pub fn factory(#(#required_params),*) -> #factory_ident #ty_generics {
+                    #factory_ident::new(#(#required_names),*)
                 }
+
+                #method_mock
             }
 
-            pub struct #factory_ident {
+            #derive_clone
+            pub struct #factory_ident #impl_generics #where_clause {
                 #(#factory_fields,)*
                 #(#factory_relation_fields,)*
+                #(#factory_relation_callback_fields,)*
+                #(#factory_dynamic_relation_fields,)*
+                #factory_context_field
+            }
+
+            #(#sequence_counters)*
+
+            #[automatically_derived]
+            impl #impl_generics #factory_ident #ty_generics #where_clause {
+                #(#factory_items)*
+            }
+
+            #impl_default
+
+            #impl_debug
+        }
+    }
+
+    /// Generates the `Default` implementation of the factory struct, delegating to `new()`.
+    ///
+    /// Factories with `#[factory(no_default)]` fields cannot be built without arguments, so
+    /// no implementation is generated for them.
+    fn generate_factory_impl_default(&self) -> Option<TokenStream> {
+        if self.required_fields().next().is_some() {
+            return None;
+        }
+
+        let factory_ident = Self::generate_factory_ident(&self.input.ident);
+        let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
+
+        Some(quote! {
+            #[automatically_derived]
+            impl #impl_generics Default for #factory_ident #ty_generics #where_clause {
+                fn default() -> Self {
+                    #factory_ident::new()
+                }
             }
+        })
+    }
 
-            impl #factory_ident {
-                #factory_method_new
+    /// Generates the `Debug` implementation of the factory struct.
+    ///
+    /// The field values are printed through `fabrique::debug::Field`, falling back to
+    /// `<opaque>` for types not implementing `Debug`. The callbacks and trait objects of the
+    /// relations cannot be printed, so they are rendered as `<set>` or `<unset>`.
+    fn generate_factory_impl_debug(&self) -> TokenStream {
+        let factory_ident = Self::generate_factory_ident(&self.input.ident);
+        let name = factory_ident.to_string();
+        let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
 
-                #factory_method_create
+        let value_fields = self
+            .analysis
+            .fields
+            .iter()
+            .map(|field| field.field.ident.clone().expect("Named field"))
+            .chain(
+                self.analysis
+                    .context
+                    .as_ref()
+                    .map(|_| Ident::new("context", Span::call_site())),
+            )
+            .map(|ident| {
+                let label = ident.unraw().to_string();
+                quote! {
+                    debug.field(#label, (&&fabrique::debug::Field(&self.#ident)).as_debug());
+                }
+            });
+        let relation_fields = self
+            .analysis
+            .relations()
+            .flat_map(|(_, relation)| {
+                let cfg = Self::generate_relation_cfg(relation);
+                [&relation.factory_field, &relation.on_created_field]
+                    .map(|ident| (cfg.clone(), ident))
+            })
+            .chain(
+                self.analysis
+                    .dynamic_relations()
+                    .map(|(_, relation)| (None, &relation.factory_field)),
+            )
+            .map(|(cfg, ident)| {
+                let label = ident.to_string();
+                quote! {
+                    #cfg
+                    debug.field(#label, &fabrique::debug::Presence(self.#ident.is_some()));
+                }
+            });
 
-                #(#factory_method_fields)*
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics std::fmt::Debug for #factory_ident #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    use fabrique::debug::{DebugField as _, OpaqueField as _};
 
-                #(#factory_methods_for_relation)*
+                    let mut debug = f.debug_struct(#name);
+                    #(#value_fields)*
+                    #(#relation_fields)*
+                    debug.finish()
+                }
             }
         }
     }
 
+    /// Prefixes a generated item with a `/// ---` marker, when `#[factory(pretty)]` is set.
+    ///
+    /// Proc macros cannot run rustfmt, so the markers delimit the generated items in the
+    /// output of `cargo expand` instead.
+    fn generate_pretty_marker(&self, item: TokenStream) -> TokenStream {
+        if !self.analysis.pretty {
+            return item;
+        }
+
+        quote! {
+            #[doc = " ---"]
+            #item
+        }
+    }
+
     /// Generates field definitions for the factory struct.
     ///
     /// Transforms each field into an Option so users can either set specific values
     /// or let the factory generate defaults when building the final struct.
+    /// Fields marked with `#[factory(no_default)]` are always set, so they are not wrapped.
     fn generate_factory_fields(&self) -> impl Iterator<Item = TokenStream> {
         self.analysis.fields.clone().into_iter().map(|field| {
             let name = &field.field.ident;
             let ty = &field.field.ty;
-            quote! {
-                #name: std::option::Option<#ty>
+            match field.no_default {
+                true => quote! { #name: #ty },
+                false => quote! { #name: std::option::Option<#ty> },
             }
         })
     }
 
+    /// Returns the fields marked with `#[factory(no_default)]`.
+    fn required_fields(&self) -> impl Iterator<Item = &FactoryFieldAnalysisOutput> {
+        self.analysis.fields.iter().filter(|field| field.no_default)
+    }
+
+    /// Generates the parameters of the factory constructor, one per field without default.
+    fn generate_required_params(&self) -> Vec<TokenStream> {
+        self.required_fields()
+            .map(|field| {
+                let name = &field.field.ident;
+                let ty = &field.field.ty;
+                quote! { #name: #ty }
+            })
+            .collect()
+    }
+
+    /// Returns the type taken by the `try_[field]` setters of a field, unwrapped from its
+    /// `Option` if any.
+    fn setter_type(field: &FactoryFieldAnalysisOutput) -> &Type {
+        field.option_item.as_ref().unwrap_or(&field.field.ty)
+    }
+
+    /// Generates the type taken by the setters of a field.
+    ///
+    /// Setters of an optional field take either its inner value or `None`, so a default can
+    /// be overridden both ways.
+    fn generate_setter_param_type(field: &FactoryFieldAnalysisOutput) -> TokenStream {
+        match &field.option_item {
+            Some(item) => quote! { impl Into<Option<#item>> },
+            None => {
+                let ty = &field.field.ty;
+                quote! { #ty }
+            }
+        }
+    }
+
+    /// Generates the value stored into the factory field of a setter argument, already of
+    /// the field type.
+    fn generate_stored_value(
+        field: &FactoryFieldAnalysisOutput,
+        value: TokenStream,
+    ) -> TokenStream {
+        match field.no_default {
+            true => value,
+            false => quote! { Some(#value) },
+        }
+    }
+
+    /// Generates the `RELATIONS` constant listing the relation names of the factory.
+    ///
+    /// This lets tooling walk the factory dependencies of a model generically. Relations
+    /// compiled out by their `cfg` predicate are left out of the list.
+    fn generate_factory_const_relations(&self) -> TokenStream {
+        let names = self.analysis.relations().map(|(_, relation)| {
+            let name = &relation.name;
+            let cfg = Self::generate_relation_cfg(relation);
+
+            quote! { #cfg #name }
+        });
+
+        quote! {
+            pub const RELATIONS: &'static [&'static str] = &[#(#names),*];
+        }
+    }
+
     /// Generates factory relation fields for linked factory dependencies.
     fn generate_factory_relation_fields(&self) -> impl Iterator<Item = TokenStream> {
         self.analysis.relations().map(|(_, relation)| {
             let ident = &relation.factory_field;
-            let ty = Self::generate_factory_ident(&relation.referenced_type);
+            let ty = Self::generate_factory_path(&relation.referenced_type);
+            let cfg = Self::generate_relation_cfg(relation);
+
+            let pointer = self.generate_callback_pointer();
+            let callback = self.generate_callback_trait();
+            let auto_traits = self.generate_callback_auto_traits();
+            let bound = self.generate_closure_lifetime_bound();
 
             quote! {
-                #ident: std::option::Option<Box<dyn FnOnce(#ty) -> #ty + Send>>
+                #cfg
+                #ident: std::option::Option<#pointer<dyn #callback(#ty) -> #ty #auto_traits #bound>>
             }
         })
     }
 
-    /// Generates the factory identifier with "Factory" suffix.
-    fn generate_factory_ident(ident: &Ident) -> Ident {
-        let factory_name = format!("{}Factory", ident);
-        Ident::new(&factory_name, ident.span())
+    /// Generates factory fields holding the callbacks run once a related object is created.
+    fn generate_factory_relation_callback_fields(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.relations().map(|(_, relation)| {
+            let ident = &relation.on_created_field;
+            let ty = &relation.referenced_type;
+            let cfg = Self::generate_relation_cfg(relation);
+
+            let pointer = self.generate_callback_pointer();
+            let callback = self.generate_callback_trait();
+            let auto_traits = self.generate_callback_auto_traits();
+            let bound = self.generate_closure_lifetime_bound();
+
+            quote! {
+                #cfg
+                #ident: std::option::Option<#pointer<dyn #callback(&#ty) #auto_traits #bound>>
+            }
+        })
     }
 
-    /// Generates the `create()` method for the factory struct.
+    /// Generates the pointer holding the relation callbacks.
     ///
-    /// This method handles both relation creation and object persistence:
-    /// 1. Creates any related objects first (via factory relations)
-    /// 2. Creates the main object with all field values
-    /// 3. Persists the object using the Persistable trait
-    fn generate_factory_method_create(&self) -> TokenStream {
-        // Generate relation creation code - related objects are created first
-        // to establish the dependency graph before creating the main object
-        let relations_create = self.analysis.relations().map(|(field, relation)| {
-            let field = &field.ident;
-            let ident = &relation.factory_field;
-            let ty = Self::generate_factory_ident(&relation.referenced_type);
-            let referenced_key = &relation.referenced_key;
+    /// Factories with `#[factory(repeat)]` are cloned, so their callbacks are shared between
+    /// the clones instead of boxed.
+    fn generate_callback_pointer(&self) -> TokenStream {
+        match self.analysis.repeat {
+            true => quote! { std::sync::Arc },
+            false => quote! { Box },
+        }
+    }
+
+    /// Generates the closure trait of the relation callbacks, without its signature.
+    ///
+    /// Factories with `#[factory(repeat)]` may run a callback once per created object.
+    fn generate_callback_trait(&self) -> TokenStream {
+        match self.analysis.repeat {
+            true => quote! { Fn },
+            false => quote! { FnOnce },
+        }
+    }
+
+    /// Generates the auto traits the relation callbacks implement, e.g. `+ Send`.
+    ///
+    /// Callbacks shared between the clones of a factory must also be `Sync`.
+    fn generate_callback_auto_traits(&self) -> TokenStream {
+        match self.analysis.repeat {
+            true => quote! { + Send + Sync },
+            false => quote! { + Send },
+        }
+    }
+
+    /// Generates the lifetime the closures and trait objects held by the factory must outlive.
+    ///
+    /// This is the first lifetime parameter of the struct, so that relation callbacks can
+    /// borrow data living as long as the built object, or `'static` when there is none.
+    fn generate_closure_lifetime(&self) -> TokenStream {
+        match self.input.generics.lifetimes().next() {
+            Some(param) => param.lifetime.to_token_stream(),
+            None => quote! { 'static },
+        }
+    }
+
+    /// Generates the lifetime bound of the boxed closures and trait objects, when the struct
+    /// has a lifetime parameter.
+    ///
+    /// Boxed trait objects default to `'static`, which would reject borrowing closures.
+    fn generate_closure_lifetime_bound(&self) -> Option<TokenStream> {
+        let param = self.input.generics.lifetimes().next()?;
+        let lifetime = &param.lifetime;
+
+        Some(quote! { + #lifetime })
+    }
 
+    /// Generates the `#[cfg(...)]` attribute gating the items of a relation, if any.
+    fn generate_relation_cfg(relation: &Relation) -> Option<TokenStream> {
+        relation.cfg.as_ref().map(|predicate| {
             quote! {
-                if let Some(callback) = self.#ident {
-                    let instance = callback(#ty::new()).create(connection).await?;
-                    self.#field = Some(instance.#referenced_key);
-                }
+                #[cfg(#predicate)]
             }
-        });
+        })
+    }
 
-        // Generate struct field initialization - use provided values or defaults
-        let struct_ident = &self.analysis.base_struct_ident;
-        let struct_fields = self.analysis.fields.iter().map(|field| {
-            let name = &field.field.ident;
-            let ty = &field.field.ty;
+    /// Generates factory fields holding the trait objects of dynamic relations.
+    fn generate_factory_dynamic_relation_fields(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.dynamic_relations().map(|(field, relation)| {
+            let ident = &relation.factory_field;
+            let creatable = self.generate_creatable_bound(field, relation);
+            let bound = self.generate_closure_lifetime_bound();
 
             quote! {
-                #name: self.#name.unwrap_or(<#ty as Default>::default())
+                #ident: std::option::Option<Box<dyn #creatable #bound>>
             }
-        });
+        })
+    }
+
+    /// Generates the factory field holding the context, when `#[factory(context)]` is set.
+    fn generate_factory_context_field(&self) -> Option<TokenStream> {
+        let ty = self.analysis.context.as_ref()?;
+
+        Some(quote! {
+            context: std::option::Option<#ty>,
+        })
+    }
+
+    /// Generates the trait bound implemented by the objects of a dynamic relation.
+    fn generate_creatable_bound(&self, field: &Field, relation: &DynamicRelation) -> TokenStream {
+        let struct_ident = self.generate_struct_type();
+        let trait_path = &relation.trait_path;
+        let ty = &field.ty;
 
         quote! {
-            pub async fn create(mut self, connection: &<#struct_ident as fabrique::Persistable>::Connection) -> Result<#struct_ident, <#struct_ident as fabrique::Persistable>::Error>
-            {
-                #(#relations_create)*
+            #trait_path<#ty, <#struct_ident as fabrique::Persistable>::Connection, <#struct_ident as fabrique::Persistable>::Error>
+        }
+    }
 
-                let instance = #struct_ident {
-                    #(#struct_fields,)*
-                };
+    /// Generates the type of the original struct, including its generic parameters.
+    fn generate_struct_type(&self) -> TokenStream {
+        let struct_ident = &self.analysis.base_struct_ident;
+        let (_, ty_generics, _) = self.input.generics.split_for_impl();
 
-                instance.create(connection).await
-            }
+        quote! { #struct_ident #ty_generics }
+    }
+
+    /// Generates the type of the objects built by the factory.
+    ///
+    /// This is the original struct, wrapped into the pointer set by `#[factory(output)]`.
+    fn generate_output_type(&self) -> TokenStream {
+        let struct_type = self.generate_struct_type();
+
+        match &self.analysis.output {
+            Some(Output::Arc) => quote! { std::sync::Arc<#struct_type> },
+            Some(Output::Rc) => quote! { std::rc::Rc<#struct_type> },
+            Some(Output::Box) => quote! { Box<#struct_type> },
+            None => struct_type,
         }
     }
 
-    /// Generates the `new()` method for the factory struct.
-    fn generate_factory_method_new(&self) -> TokenStream {
-        let initialized_fields = self.analysis.fields.clone().into_iter().map(|field| {
-            let name = &field.field.ident;
-            quote! {
-                #name: None
-            }
-        });
+    /// Generates the constructor wrapping the built objects, when `#[factory(output)]` is set.
+    fn generate_output_constructor(&self) -> Option<TokenStream> {
+        self.analysis.output.as_ref().map(|output| match output {
+            Output::Arc => quote! { std::sync::Arc::new },
+            Output::Rc => quote! { std::rc::Rc::new },
+            Output::Box => quote! { Box::new },
+        })
+    }
 
-        let initialized_relation_fields = self.analysis.relations().map(|(_, relation)| {
-            let name = &relation.factory_field;
+    /// Generates the `mock()` constructor of the original struct, when `#[factory(mock)]` is set.
+    ///
+    /// Unlike `Default`, every field gets a recognizable placeholder value.
+    fn generate_method_mock(&self) -> Option<TokenStream> {
+        if !self.analysis.mock {
+            return None;
+        }
+
+        let struct_ident = &self.analysis.base_struct_ident;
+        let fields = self.analysis.fields.iter().map(|field| {
+            let name = field.field.ident.as_ref().expect("Named field");
+            let placeholder =
+                Self::generate_placeholder(&field.field.ty, &name.unraw().to_string());
             quote! {
-                #name: None
+                #name: #placeholder
             }
         });
 
-        quote! {
-            pub fn new() -> Self {
-                Self {
-                    #(#initialized_fields,)*
-                    #(#initialized_relation_fields,)*
+        Some(quote! {
+            pub fn mock() -> Self {
+                #struct_ident {
+                    #(#fields,)*
                 }
             }
+        })
+    }
+
+    /// Generates a deterministic placeholder value for a field type.
+    ///
+    /// Numbers are `1`, booleans `false`, strings the field name and options wrap the
+    /// placeholder of their inner type. Other types fall back to `Default`.
+    fn generate_placeholder(ty: &Type, field_name: &str) -> TokenStream {
+        let segment = match ty {
+            Type::Path(path) => path.path.segments.last(),
+            Type::Reference(reference) => {
+                return match reference.elem.as_ref() {
+                    Type::Path(path) if path.path.is_ident("str") => quote! { #field_name },
+                    _ => quote! { Default::default() },
+                };
+            }
+            _ => None,
+        };
+
+        let Some(segment) = segment else {
+            return quote! { Default::default() };
+        };
+
+        match segment.ident.to_string().as_str() {
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" => quote! { 1 },
+            "f32" | "f64" => quote! { 1.0 },
+            "bool" => quote! { false },
+            "char" => quote! { 'a' },
+            "String" => quote! { #field_name.to_string() },
+            "Option" => match &segment.arguments {
+                PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+                    Some(GenericArgument::Type(inner)) => {
+                        let placeholder = Self::generate_placeholder(inner, field_name);
+                        quote! { Some(#placeholder) }
+                    }
+                    _ => quote! { None },
+                },
+                _ => quote! { None },
+            },
+            _ => quote! { Default::default() },
         }
     }
 
-    fn generate_factory_method_fields(&self) -> impl Iterator<Item = TokenStream> {
-        self.analysis.fields.clone().into_iter().map(|field| {
-            let name = &field.field.ident;
-            let ty = &field.field.ty;
+    /// Generates the factory identifier with "Factory" suffix.
+    fn generate_factory_ident(ident: &Ident) -> Ident {
+        let factory_name = format!("{}Factory", ident);
+        Ident::new(&factory_name, ident.span())
+    }
 
-            quote! {
-                pub fn #name(mut self, #name: #ty) -> Self {
-                    self.#name = Some(#name);
-                    self
+    /// Generates the path to the factory of a related type, e.g. `tools::HammerFactory`.
+    fn generate_factory_path(path: &Path) -> Path {
+        let mut path = path.clone();
+        if let Some(segment) = path.segments.last_mut() {
+            segment.ident = Self::generate_factory_ident(&segment.ident);
+        }
+        path
+    }
+
+    /// Generates the `create()` method for the factory struct.
+    ///
+    /// This method handles both relation creation and object persistence:
+    /// 1. Creates any related objects first (via factory relations)
+    /// 2. Creates the main object with all field values
+    /// 3. Persists the object using the Persistable trait
+    fn generate_factory_method_create(&self) -> TokenStream {
+        self.generate_create_method(CreateMode::Plain)
+    }
+
+    /// Generates the `create_with_stats()` method for the factory struct.
+    ///
+    /// This method creates the object like `create()`, and also counts the rows inserted
+    /// for it and all its relations, e.g. to report the progress of a seed.
+    fn generate_factory_method_create_with_stats(&self) -> TokenStream {
+        self.generate_create_method(CreateMode::WithStats)
+    }
+
+    /// Generates the `create_tracked()` method for the factory struct, when
+    /// `#[factory(tracked)]` is set.
+    ///
+    /// This method creates the object like `create()`, and also appends the table and primary
+    /// key of every created object, relations included, to the given log, e.g. to clean up
+    /// after a test. The related factories must be tracked as well.
+    fn generate_factory_method_create_tracked(&self) -> Option<TokenStream> {
+        self.analysis
+            .tracked
+            .then(|| self.generate_create_method(CreateMode::Tracked))
+    }
+
+    /// Generates the `create_many()` method for the factory struct, when `#[factory(repeat)]`
+    /// is set.
+    ///
+    /// Each object is created from its own clone of the factory, so its relations are
+    /// resolved again and every object gets its own related objects.
+    fn generate_factory_method_create_many(&self) -> Option<TokenStream> {
+        if !self.analysis.repeat {
+            return None;
+        }
+
+        let struct_type = self.generate_struct_type();
+        let output_type = self.generate_output_type();
+
+        Some(quote! {
+            pub async fn create_many(self, count: usize, connection: &<#struct_type as fabrique::Persistable>::Connection) -> Result<Vec<#output_type>, <#struct_type as fabrique::Persistable>::Error>
+            {
+                let mut created = Vec::with_capacity(count);
+                for _ in 0..count {
+                    created.push(self.clone().create(connection).await?);
                 }
+
+                Ok(created)
             }
         })
     }
 
-    /// Generates the `for_[relation]` methods for the factory struct.
+    /// Generates the `create_many_with()` method for the factory struct, when
+    /// `#[factory(repeat)]` is set.
     ///
-    /// These methods allow buffering the creation of related factory instances,
-    /// which are then executed when building the final object.
-    fn generate_factory_methods_for_relation(&self) -> impl Iterator<Item = TokenStream> {
-        self.analysis.relations().map(|(_, relation)| {
-            let ty = Self::generate_factory_ident(&relation.referenced_type);
-            let method_name = Ident::new(&format!("for_{}", &relation.name), ty.span());
-            let field_ident = &relation.factory_field;
-            quote! {
-                pub fn #method_name<F>(mut self, callback: F) -> Self
-                where F: FnOnce(#ty) -> #ty + Send + 'static
-                {
-                    self.#field_ident = Some(Box::new(callback));
-                    self
+    /// Like `create_many()`, each object is created from its own clone of the factory, which
+    /// is first handed to the closure along with the index of the object, e.g. to vary a field.
+    fn generate_factory_method_create_many_with(&self) -> Option<TokenStream> {
+        if !self.analysis.repeat {
+            return None;
+        }
+
+        let struct_type = self.generate_struct_type();
+        let output_type = self.generate_output_type();
+        let (customize, factory) = match self.analysis.mut_builder {
+            true => (
+                quote! { impl Fn(usize, &mut Self) },
+                quote! {
+                    let mut factory = self.clone();
+                    customize(index, &mut factory);
+                },
+            ),
+            false => (
+                quote! { impl Fn(usize, Self) -> Self },
+                quote! { let factory = customize(index, self.clone()); },
+            ),
+        };
+
+        Some(quote! {
+            pub async fn create_many_with(self, count: usize, customize: #customize, connection: &<#struct_type as fabrique::Persistable>::Connection) -> Result<Vec<#output_type>, <#struct_type as fabrique::Persistable>::Error>
+            {
+                let mut created = Vec::with_capacity(count);
+                for index in 0..count {
+                    #factory
+                    created.push(factory.create(connection).await?);
                 }
+
+                Ok(created)
             }
         })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use syn::parse_quote;
+    /// Generates the body shared by `create()`, `create_with_stats()` and `create_tracked()`.
+    ///
+    /// With `#[factory(after_create)]`, the persisted object goes through the hook before
+    /// being returned.
+    fn generate_create_method(&self, mode: CreateMode) -> TokenStream {
+        // Generate relation creation code - related objects are created first
+        // to establish the dependency graph before creating the main object
+        let relations_create = self
+            .analysis
+            .relations()
+            .filter(|(_, relation)| relation.shared.is_none())
+            .map(|(field, relation)| {
+                let key_assignment = self.generate_relation_key_assignment(field, relation, false);
+                let ident = &relation.factory_field;
+                let on_created = &relation.on_created_field;
+                let cfg = Self::generate_relation_cfg(relation);
+                let related_create = Self::generate_related_create(relation, mode);
 
-    #[test]
-    fn test_factory_codegen_from_fails_on_invalid_input() {
-        // Arrange an enum (which is not supported)
-        let result = FactoryCodegen::from(parse_quote! {
-            enum Anvil {}
-        });
+                quote! {
+                    #cfg
+                    if let Some(callback) = self.#ident {
+                        #related_create
+                        if let Some(on_created) = self.#on_created {
+                            on_created(&instance);
+                        }
+                        #key_assignment
+                    }
+                }
+            });
 
-        // Assert that it returns an error
-        assert!(result.is_err());
-    }
+        // Generate shared relation creation code - the related object of a group is created
+        // once, from the only callback set, and its key assigned to every foreign key
+        let shared_relations = self.analysis.shared_relations();
+        let shared_relations_create = shared_relations.iter().map(|(group, relations)| {
+            let first = &relations[0].1.factory_field;
+            let others = relations[1..]
+                .iter()
+                .map(|(_, relation)| &relation.factory_field);
+            let callbacks = relations
+                .iter()
+                .map(|(_, relation)| &relation.factory_field);
+            let message = format!(
+                "only one of {} can be set, as the shared group `{group}` creates a single related object",
+                relations
+                    .iter()
+                    .map(|(_, relation)| format!("`for_{}()`", relation.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let on_created = relations
+                .iter()
+                .map(|(_, relation)| &relation.on_created_field);
+            let key_assignments = relations.iter().map(|(field, relation)| {
+                self.generate_relation_key_assignment(field, relation, false)
+            });
+            let related_create = Self::generate_related_create(relations[0].1, mode);
 
-    #[test]
-    fn test_generate_factory() {
-        // Arrange the codegen
-        let codegen = FactoryCodegen::from(parse_quote! {
-            struct Anvil {
-                #[fabrique(relation = "Hammer", referenced_key = "id")]
-                hammer_id: u32,
-                hardness: u32,
-                weight: u32,
+            quote! {
+                assert!(
+                    [#(self.#callbacks.is_some()),*].into_iter().filter(|set| *set).count() <= 1,
+                    #message
+                );
+                if let Some(callback) = self.#first #(.or(self.#others))* {
+                    #related_create
+                    #(
+                        if let Some(on_created) = self.#on_created {
+                            on_created(&instance);
+                        }
+                    )*
+                    #({ #key_assignments })*
+                }
             }
-        })
-        .unwrap();
+        });
 
-        // Act the call to the factory ident method
-        let generated = codegen.generate_factory();
+        // Generate dynamic relation creation code - the trait object creates the related
+        // object and hands back the key to store
+        let dynamic_relations_create =
+            self.analysis.dynamic_relations().map(|(field, relation)| {
+                let field = &field.ident;
+                let ident = &relation.factory_field;
+                let count = (mode == CreateMode::WithStats).then(|| quote! { stats.rows += 1; });
 
-        // Assert the result
-        assert_eq!(
-            generated.to_string(),
-            quote! {
-                impl Anvil {
-                    pub fn factory() -> AnvilFactory {
-                        AnvilFactory::new()
+                quote! {
+                    if let Some(creatable) = self.#ident {
+                        self.#field = Some(creatable.create(connection).await?);
+                        #count
                     }
                 }
-                pub struct AnvilFactory {
-                    hammer_id: std::option::Option<u32>,
-                    hardness: std::option::Option<u32>,
-                    weight: std::option::Option<u32>,
+            });
 
-                    hammer_factory: std::option::Option<Box<dyn FnOnce(HammerFactory) -> HammerFactory + Send>>,
-                }
+        let struct_type = self.generate_struct_type();
+        let output_type = self.generate_output_type();
+        let instance = self.generate_struct_instance(false);
+        let constructor = self.generate_output_constructor();
+        let output = match constructor {
+            Some(constructor) => quote! { #constructor(instance) },
+            None => quote! { instance },
+        };
+        let after_create = self.analysis.after_create.as_ref().map(|after_create| {
+            quote! { let instance = #after_create(instance, connection).await?; }
+        });
+        let (method_name, log, return_type, stats, persist) = match mode {
+            CreateMode::Plain => (
+                quote! { create },
+                None,
+                output_type,
+                None,
+                self.generate_instance_persist(),
+            ),
+            CreateMode::WithStats => (
+                quote! { create_with_stats },
+                None,
+                quote! { (#output_type, fabrique::CreateStats) },
+                Some(quote! { let mut stats = fabrique::CreateStats::default(); }),
+                quote! {
+                    let instance = instance.create(connection).await?;
+                    #after_create
+                    stats.rows += 1;
+                    Ok((#output, stats))
+                },
+            ),
+            CreateMode::Tracked => (
+                quote! { create_tracked },
+                Some(quote! { , log: &mut fabrique::SeedLog }),
+                output_type,
+                None,
+                quote! {
+                    let instance = instance.create(connection).await?;
+                    log.push((<#struct_type as fabrique::Trackable>::TABLE_NAME, fabrique::Trackable::primary_key(&instance)));
+                    #after_create
+                    Ok(#output)
+                },
+            ),
+        };
+
+        quote! {
+            pub async fn #method_name(mut self, connection: &<#struct_type as fabrique::Persistable>::Connection #log) -> Result<#return_type, <#struct_type as fabrique::Persistable>::Error>
+            {
+                #stats
+
+                #(#relations_create)*
+
+                #(#shared_relations_create)*
+
+                #(#dynamic_relations_create)*
+
+                let instance = #instance;
+
+                #persist
+            }
+        }
+    }
+
+    /// Generates the `create_without_relations()` method for the factory struct.
+    ///
+    /// This method persists the object alone with the foreign keys currently set, ignoring
+    /// the relations configured through `for_<relation>()`, so a factory can be reused
+    /// without creating its related objects again.
+    fn generate_factory_method_create_without_relations(&self) -> TokenStream {
+        let struct_type = self.generate_struct_type();
+        let output_type = self.generate_output_type();
+        let instance = self.generate_struct_instance(false);
+        let persist = self.generate_instance_persist();
+
+        quote! {
+            pub async fn create_without_relations(self, connection: &<#struct_type as fabrique::Persistable>::Connection) -> Result<#output_type, <#struct_type as fabrique::Persistable>::Error>
+            {
+                let instance = #instance;
+
+                #persist
+            }
+        }
+    }
+
+    /// Generates the persistence of `instance`, running the `#[factory(after_create)]` hook
+    /// and wrapping the result into the output type.
+    fn generate_instance_persist(&self) -> TokenStream {
+        let created = match &self.analysis.after_create {
+            Some(after_create) => quote! {
+                let instance = instance.create(connection).await?;
+                #after_create(instance, connection)
+            },
+            None => quote! { instance.create(connection) },
+        };
+
+        match self.generate_output_constructor() {
+            Some(constructor) => quote! { #created.await.map(#constructor) },
+            None => quote! { #created.await },
+        }
+    }
+
+    /// Generates the creation of a related object from the callback of its relation, binding
+    /// it to `instance`.
+    ///
+    /// When counting the inserted rows, the rows of the related object are added to `stats`.
+    /// When tracking the created objects, the related factory logs into the same `log`.
+    fn generate_related_create(relation: &Relation, mode: CreateMode) -> TokenStream {
+        let ty = Self::generate_factory_path(&relation.referenced_type);
+        let method_call = match mode {
+            CreateMode::Plain => quote! { create(connection) },
+            CreateMode::WithStats => quote! { create_with_stats(connection) },
+            CreateMode::Tracked => quote! { create_tracked(connection, log) },
+        };
+
+        // A self-referential relation makes `create()` recursive, which requires boxing
+        let related_create = match relation.self_referential {
+            true => quote! { Box::pin(callback(#ty::new()).#method_call).await? },
+            false => quote! { callback(#ty::new()).#method_call.await? },
+        };
+
+        match mode {
+            CreateMode::WithStats => quote! {
+                let (instance, related_stats) = #related_create;
+                stats += related_stats;
+            },
+            CreateMode::Plain | CreateMode::Tracked => quote! { let instance = #related_create; },
+        }
+    }
+
+    /// Generates the assignment of the key of a created related object to the foreign key.
+    ///
+    /// The key goes through a trait local to the relation, so that a type mismatch between
+    /// the foreign key and the referenced key reports both fields instead of a bare type error.
+    /// The key of a shared related object is cloned, as it is assigned to several fields, and
+    /// so is the key of a borrowed `instance`. An optional foreign key, e.g. the parent of a
+    /// root node, is set to `Some` of the key.
+    fn generate_relation_key_assignment(
+        &self,
+        field: &Field,
+        relation: &Relation,
+        borrowed: bool,
+    ) -> TokenStream {
+        let name = &field.ident;
+        let option_item = option_item(&field.ty);
+        let ty = option_item.as_ref().unwrap_or(&field.ty);
+        let referenced_key = &relation.referenced_key;
+        let key = match borrowed || relation.shared.is_some() {
+            true => quote! { instance.#referenced_key.clone() },
+            false => quote! { instance.#referenced_key },
+        };
+        let foreign_key =
+            quote_spanned! {field.span()=> <_ as RelationKey<#ty>>::into_foreign_key(#key) };
+        let foreign_key = match option_item {
+            Some(_) => quote_spanned! {field.span()=> Some(#foreign_key) },
+            None => foreign_key,
+        };
+        let message = format!(
+            "relation FK type mismatch between `{}.{}` and `{}.{}`",
+            self.analysis.base_struct_ident,
+            name.as_ref().expect("Named field"),
+            relation
+                .referenced_type
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::"),
+            referenced_key
+        );
+
+        quote_spanned! {field.span()=>
+            #[diagnostic::on_unimplemented(
+                message = #message,
+                label = "the referenced key must have the type of the foreign key"
+            )]
+            trait RelationKey<ForeignKey> {
+                fn into_foreign_key(self) -> ForeignKey;
+            }
+
+            #[automatically_derived]
+            impl<T> RelationKey<T> for T {
+                fn into_foreign_key(self) -> T {
+                    self
+                }
+            }
+
+            self.#name = Some(#foreign_key);
+        }
+    }
+
+    /// Generates the `build()` method for the factory struct.
+    ///
+    /// This method creates the object from the field values without persisting it.
+    /// Relations are not resolved, so foreign keys keep their set or default values.
+    fn generate_factory_method_build(&self) -> TokenStream {
+        let output_type = self.generate_output_type();
+        let receiver = self.generate_builder_receiver();
+        let instance = self.generate_struct_instance(self.analysis.mut_builder);
+        let output = match self.generate_output_constructor() {
+            Some(constructor) => quote! { #constructor(#instance) },
+            None => instance,
+        };
+
+        quote! {
+            pub fn build(#receiver) -> #output_type {
+                #output
+            }
+        }
+    }
+
+    /// Generates the receiver of the setters and of `build()`: `&mut self` when
+    /// `#[factory(mut_builder)]` is set, `self` otherwise.
+    fn generate_builder_receiver(&self) -> TokenStream {
+        match self.analysis.mut_builder {
+            true => quote! { &mut self },
+            false => quote! { self },
+        }
+    }
+
+    /// Generates the receiver and the returned type of the setters: `&mut self` and
+    /// `&mut Self` when `#[factory(mut_builder)]` is set, `mut self` and `Self` otherwise.
+    fn generate_setter_signature(&self) -> (TokenStream, TokenStream) {
+        match self.analysis.mut_builder {
+            true => (quote! { &mut self }, quote! { &mut Self }),
+            false => (quote! { mut self }, quote! { Self }),
+        }
+    }
+
+    /// Generates the `finalize()` method for the factory struct.
+    ///
+    /// An alias of `build()`: going through the factory keeps the construction code compiling
+    /// when the struct gains new fields, unlike a struct literal.
+    fn generate_factory_method_finalize(&self) -> TokenStream {
+        let output_type = self.generate_output_type();
+        let receiver = self.generate_builder_receiver();
+
+        quote! {
+            pub fn finalize(#receiver) -> #output_type {
+                self.build()
+            }
+        }
+    }
+
+    /// Generates the `repeat()` method for the factory struct, when `#[factory(repeat)]` is set.
+    ///
+    /// The configured values are cloned for each object, which is only built once the
+    /// iterator reaches it, so sequences keep drawing distinct values.
+    fn generate_factory_method_repeat(&self) -> Option<TokenStream> {
+        if !self.analysis.repeat {
+            return None;
+        }
+
+        let output_type = self.generate_output_type();
+        let instance = self.generate_struct_instance(true);
+        let output = match self.generate_output_constructor() {
+            Some(constructor) => quote! { #constructor(#instance) },
+            None => instance,
+        };
+
+        Some(quote! {
+            pub fn repeat(self, n: usize) -> impl Iterator<Item = #output_type> {
+                (0..n).map(move |_| #output)
+            }
+        })
+    }
+
+    /// Generates the static counters of the owned sequences.
+    ///
+    /// Each counter is named after the struct and the field, so unrelated factories never
+    /// share a counter, and is declared once next to the factory so that every method building
+    /// an object draws from it.
+    fn generate_sequence_counters(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis
+            .fields
+            .iter()
+            .filter_map(|field| match &field.sequence {
+                Some(Sequence::Owned(counter)) => Some(quote! {
+                    static #counter: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+                }),
+                _ => None,
+            })
+    }
+
+    /// Generates the expression drawing the next value of a sequence.
+    fn generate_sequence_next_value(sequence: &Sequence) -> TokenStream {
+        match sequence {
+            Sequence::Owned(counter) => quote! {
+                #counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            },
+            Sequence::Shared(namespace) => quote! {
+                fabrique::sequence::next(#namespace)
+            },
+        }
+    }
+
+    /// Generates the `try_build_validated()` method for the factory struct.
+    ///
+    /// Only generated when `#[factory(validate)]` is set: the object is built without
+    /// touching the database, then checked through its `fabrique::Validate` implementation.
+    fn generate_factory_method_try_build_validated(&self) -> Option<TokenStream> {
+        if !self.analysis.validate {
+            return None;
+        }
+
+        let struct_type = self.generate_struct_type();
+        let output_type = self.generate_output_type();
+        let receiver = self.generate_builder_receiver();
+
+        Some(quote! {
+            pub fn try_build_validated(#receiver) -> Result<#output_type, <#struct_type as fabrique::Validate>::Error> {
+                let instance = self.build();
+                <#struct_type as fabrique::Validate>::validate(&instance)?;
+                Ok(instance)
+            }
+        })
+    }
+
+    /// Generates the expression building the object from the factory fields.
+    ///
+    /// With `#[factory(build_with_defaults_from)]`, the template is built first, once per
+    /// object, and the fields left unset are moved out of it.
+    fn generate_struct_instance(&self, cloned: bool) -> TokenStream {
+        let struct_ident = &self.analysis.base_struct_ident;
+        let struct_fields = self.generate_struct_fields(cloned);
+        let instance = quote! {
+            #struct_ident {
+                #(#struct_fields,)*
+            }
+        };
+
+        match &self.analysis.build_with_defaults_from {
+            Some(template) => quote! {
+                {
+                    let template = #template();
+                    #instance
+                }
+            },
+            None => instance,
+        }
+    }
+
+    /// Generates the struct field initialization of the built object.
+    ///
+    /// Uses the provided values, falling back to the field's default expression, function,
+    /// factory or sequence if any, or to the `Default` implementation of the field type through
+    /// `fabrique::FactoryDefault`, or to the field of the template when the factory has one.
+    /// Optional fields left unset are `None`. Formatted sequences render
+    /// their value through `format!` instead of converting it to the field type. The values are cloned out
+    /// of the factory when it is only borrowed.
+    fn generate_struct_fields(&self, cloned: bool) -> impl Iterator<Item = TokenStream> {
+        self.analysis.fields.iter().map(move |field| {
+            let name = &field.field.ident;
+            let ty = &field.field.ty;
+            let value = match cloned {
+                true => quote! { self.#name.clone() },
+                false => quote! { self.#name },
+            };
+
+            if field.no_default {
+                return quote! {
+                    #name: #value
+                };
+            }
+
+            if let Some(sequence) = &field.sequence {
+                let next_value = Self::generate_sequence_next_value(sequence);
+                if let Some(format) = &field.sequence_format {
+                    return quote! {
+                        #name: #value.unwrap_or_else(|| format!(#format, n = #next_value))
+                    };
+                }
+                return quote! {
+                    #name: #value.unwrap_or_else(|| {
+                        <#ty as std::convert::TryFrom<u64>>::try_from(#next_value)
+                            .expect("sequence value out of range")
+                    })
+                };
+            }
+
+            if let Some(default) = &field.default {
+                return quote! {
+                    #name: #value.unwrap_or_else(|| #default)
+                };
+            }
+
+            if let Some(default_fn) = &field.default_fn {
+                return quote! {
+                    #name: #value.unwrap_or_else(#default_fn)
+                };
+            }
+
+            if self.analysis.build_with_defaults_from.is_some() && field.default_factory.is_none() {
+                return quote! {
+                    #name: #value.unwrap_or(template.#name)
+                };
+            }
+
+            match (&field.default_factory, &field.option_item) {
+                (Some(factory), _) => quote! {
+                    #name: #value.unwrap_or_else(|| #factory::new().build())
+                },
+                (None, Some(_)) => quote! {
+                    #name: #value.unwrap_or(None)
+                },
+                (None, None) => quote! {
+                    #name: #value.unwrap_or(<#ty as fabrique::FactoryDefault>::factory_default())
+                },
+            }
+        })
+    }
+
+    /// Generates the `new()` method for the factory struct.
+    fn generate_factory_method_new(&self) -> TokenStream {
+        let initialized_fields = self.analysis.fields.clone().into_iter().map(|field| {
+            let name = &field.field.ident;
+            match field.no_default {
+                true => quote! { #name },
+                false => quote! { #name: None },
+            }
+        });
+        let required_params = self.generate_required_params();
+
+        let initialized_relation_fields = self.analysis.relations().map(|(_, relation)| {
+            let name = &relation.factory_field;
+            let cfg = Self::generate_relation_cfg(relation);
+            let value = match Self::generate_default_child(relation) {
+                Some(default_child) => {
+                    let pointer = self.generate_callback_pointer();
+                    quote! { Some(#pointer::new(#default_child)) }
+                }
+                None => quote! { None },
+            };
+            quote! {
+                #cfg
+                #name: #value
+            }
+        });
+
+        let initialized_relation_callback_fields =
+            self.analysis.relations().map(|(_, relation)| {
+                let name = &relation.on_created_field;
+                let cfg = Self::generate_relation_cfg(relation);
+                quote! {
+                    #cfg
+                    #name: None
+                }
+            });
+
+        let initialized_dynamic_relation_fields =
+            self.analysis.dynamic_relations().map(|(_, relation)| {
+                let name = &relation.factory_field;
+                quote! {
+                    #name: None
+                }
+            });
+
+        let initialized_context_field = self.analysis.context.as_ref().map(|_| {
+            quote! {
+                context: None,
+            }
+        });
+
+        quote! {
+            pub fn new(#(#required_params),*) -> Self {
+                Self {
+                    #(#initialized_fields,)*
+                    #(#initialized_relation_fields,)*
+                    #(#initialized_relation_callback_fields,)*
+                    #(#initialized_dynamic_relation_fields,)*
+                    #initialized_context_field
+                }
+            }
+        }
+    }
+
+    /// Generates the `with_context()` method, when `#[factory(context)]` is set.
+    ///
+    /// The context is readable as `self.context` from the `#[factory(default)]` expressions,
+    /// so several fields can derive their defaults from a shared value.
+    fn generate_factory_method_with_context(&self) -> Option<TokenStream> {
+        let ty = self.analysis.context.as_ref()?;
+        let (receiver, builder_type) = self.generate_setter_signature();
+
+        Some(quote! {
+            pub fn with_context(#receiver, context: #ty) -> #builder_type {
+                self.context = Some(context);
+                self
+            }
+        })
+    }
+
+    fn generate_factory_method_fields(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.fields.iter().map(|field| {
+            let name = field.field.ident.as_ref().expect("Named field");
+            let setter = &field.setter;
+            let ty = Self::generate_setter_param_type(field);
+            let value = match field.option_item {
+                Some(_) => quote! { #name.into() },
+                None => quote! { #name },
+            };
+            let value = Self::generate_stored_value(field, value);
+            let (receiver, builder_type) = self.generate_setter_signature();
+
+            quote! {
+                pub fn #setter(#receiver, #name: #ty) -> #builder_type {
+                    self.#name = #value;
+                    self
+                }
+            }
+        })
+    }
+
+    /// Generates the `try_[field]` setters of the fields having a validator.
+    ///
+    /// The validator runs before the value is stored, so an invalid value is rejected when
+    /// it is set rather than when the object is built. Its error is returned as is when typed
+    /// with `validate_field_error`, and converted into the one of `fabrique::Validate`
+    /// otherwise.
+    fn generate_factory_methods_try_fields(&self) -> impl Iterator<Item = TokenStream> {
+        let struct_type = self.generate_struct_type();
+
+        self.analysis.fields.iter().filter_map(move |field| {
+            let validator = field.validate_field.as_ref()?;
+            let name = field.field.ident.as_ref()?;
+            let method_name = Ident::new(&format!("try_{}", field.setter.unraw()), name.span());
+            let ty = Self::setter_type(field);
+            let value = match field.option_item {
+                Some(_) => quote! { Some(#name) },
+                None => quote! { #name },
+            };
+            let value = Self::generate_stored_value(field, value);
+            let (receiver, builder_type) = self.generate_setter_signature();
+            let error_type = match &field.validate_field_error {
+                Some(error_type) => quote! { #error_type },
+                None => quote! { <#struct_type as fabrique::Validate>::Error },
+            };
+
+            Some(quote! {
+                pub fn #method_name(#receiver, #name: #ty) -> Result<#builder_type, #error_type> {
+                    #validator(&#name)?;
+                    self.#name = #value;
+                    Ok(self)
+                }
+            })
+        })
+    }
+
+    /// Generates the `extend_[field]` setters of the `Vec` fields.
+    ///
+    /// The items are appended to the values set so far, starting from an empty vector.
+    fn generate_factory_methods_extend_fields(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.fields.iter().filter_map(move |field| {
+            let item = field.vec_item.as_ref()?;
+            let name = field.field.ident.as_ref()?;
+            let method_name = Ident::new(&format!("extend_{}", field.setter.unraw()), name.span());
+            let (receiver, builder_type) = self.generate_setter_signature();
+            let target = match field.no_default {
+                true => quote! { self.#name },
+                false => quote! { self.#name.get_or_insert_with(Vec::new) },
+            };
+
+            Some(quote! {
+                pub fn #method_name(#receiver, items: impl IntoIterator<Item = #item>) -> #builder_type {
+                    #target.extend(items);
+                    self
+                }
+            })
+        })
+    }
+
+    /// Generates the default configuration of the related factory, when the relation sets
+    /// `default_child`.
+    ///
+    /// The closure is coerced to a function pointer so its parameter type is inferred.
+    fn generate_default_child(relation: &Relation) -> Option<TokenStream> {
+        let default_child = relation.default_child.as_ref()?;
+        let ty = Self::generate_factory_path(&relation.referenced_type);
+
+        Some(quote! {
+            {
+                let default_child: fn(#ty) -> #ty = #default_child;
+                default_child
+            }
+        })
+    }
+
+    /// Generates the callback stored for a relation, composed after the default
+    /// configuration of the related factory, if any.
+    fn generate_relation_callback(&self, relation: &Relation) -> TokenStream {
+        let pointer = self.generate_callback_pointer();
+        match Self::generate_default_child(relation) {
+            Some(default_child) => quote! {
+                #pointer::new(move |factory| callback((#default_child)(factory)))
+            },
+            None => quote! { #pointer::new(callback) },
+        }
+    }
+
+    /// Generates the `for_[relation]` methods for the factory struct.
+    ///
+    /// These methods allow buffering the creation of related factory instances,
+    /// which are then executed when building the final object.
+    fn generate_factory_methods_for_relation(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.relations().map(|(_, relation)| {
+            let ty = Self::generate_factory_path(&relation.referenced_type);
+            let method_name = Ident::new(&format!("for_{}", &relation.name), ty.span());
+            let field_ident = &relation.factory_field;
+            let cfg = Self::generate_relation_cfg(relation);
+            let lifetime = self.generate_closure_lifetime();
+            let callback = self.generate_relation_callback(relation);
+            let callback_trait = self.generate_callback_trait();
+            let auto_traits = self.generate_callback_auto_traits();
+            let (receiver, builder_type) = self.generate_setter_signature();
+            quote! {
+                #cfg
+                pub fn #method_name<F>(#receiver, callback: F) -> #builder_type
+                where F: #callback_trait(#ty) -> #ty #auto_traits + #lifetime
+                {
+                    self.#field_ident = Some(#callback);
+                    self
+                }
+            }
+        })
+    }
+
+    /// Generates the `for_[relation]_opt` methods for the factory struct.
+    ///
+    /// These methods only buffer the callback when one is given, so a relation can be
+    /// configured conditionally without breaking the method chain.
+    fn generate_factory_methods_for_relation_opt(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.relations().map(|(_, relation)| {
+            let ty = Self::generate_factory_path(&relation.referenced_type);
+            let method_name = Ident::new(&format!("for_{}_opt", &relation.name), ty.span());
+            let field_ident = &relation.factory_field;
+            let cfg = Self::generate_relation_cfg(relation);
+            let lifetime = self.generate_closure_lifetime();
+            let callback = self.generate_relation_callback(relation);
+            let callback_trait = self.generate_callback_trait();
+            let auto_traits = self.generate_callback_auto_traits();
+            let (receiver, builder_type) = self.generate_setter_signature();
+            quote! {
+                #cfg
+                pub fn #method_name<F>(#receiver, callback: Option<F>) -> #builder_type
+                where F: #callback_trait(#ty) -> #ty #auto_traits + #lifetime
+                {
+                    if let Some(callback) = callback {
+                        self.#field_ident = Some(#callback);
+                    }
+                    self
+                }
+            }
+        })
+    }
+
+    /// Generates the `with_[relation]` methods for the factory struct.
+    ///
+    /// These methods link the relation to an already persisted object: its key is stored
+    /// into the foreign key and the relation callback is dropped, so `create()` inserts no
+    /// related object. Linking a shared relation links every relation of its group.
+    fn generate_factory_methods_with_relation(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.relations().map(|(_, relation)| {
+            let ty = &relation.referenced_type;
+            let method_name = Ident::new(&format!("with_{}", &relation.name), ty.span());
+            let cfg = Self::generate_relation_cfg(relation);
+            let links = self
+                .analysis
+                .relations()
+                .filter(|(_, linked)| {
+                    linked.name == relation.name
+                        || (linked.shared.is_some() && linked.shared == relation.shared)
+                })
+                .map(|(field, linked)| {
+                    let key_assignment = self.generate_relation_key_assignment(field, linked, true);
+                    let factory_field = &linked.factory_field;
+                    quote! {
+                        { #key_assignment }
+                        self.#factory_field = None;
+                    }
+                });
+            let (receiver, builder_type) = self.generate_setter_signature();
+
+            quote! {
+                #cfg
+                pub fn #method_name(#receiver, instance: &#ty) -> #builder_type {
+                    #(#links)*
+                    self
+                }
+            }
+        })
+    }
+
+    /// Generates the `on_[relation]_created` methods for the factory struct.
+    ///
+    /// These methods register a callback receiving the related object right after it has
+    /// been persisted, e.g. to capture its key into an external structure.
+    fn generate_factory_methods_on_relation_created(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.relations().map(|(field, relation)| {
+            let ty = &relation.referenced_type;
+            let method_name = Ident::new(&format!("on_{}_created", &relation.name), field.span());
+            let field_ident = &relation.on_created_field;
+            let cfg = Self::generate_relation_cfg(relation);
+            let lifetime = self.generate_closure_lifetime();
+            let pointer = self.generate_callback_pointer();
+            let callback_trait = self.generate_callback_trait();
+            let auto_traits = self.generate_callback_auto_traits();
+            let (receiver, builder_type) = self.generate_setter_signature();
+            quote! {
+                #cfg
+                pub fn #method_name<F>(#receiver, callback: F) -> #builder_type
+                where F: #callback_trait(&#ty) #auto_traits + #lifetime
+                {
+                    self.#field_ident = Some(#pointer::new(callback));
+                    self
+                }
+            }
+        })
+    }
+
+    /// Generates the `for_[relation]` methods of dynamic relations.
+    ///
+    /// These methods accept any implementor of the relation trait, allowing the concrete
+    /// related type to be chosen at runtime.
+    fn generate_factory_methods_for_dynamic_relation(&self) -> impl Iterator<Item = TokenStream> {
+        self.analysis.dynamic_relations().map(|(field, relation)| {
+            let method_name = Ident::new(&format!("for_{}", &relation.name), field.span());
+            let field_ident = &relation.factory_field;
+            let creatable = self.generate_creatable_bound(field, relation);
+            let lifetime = self.generate_closure_lifetime();
+            let (receiver, builder_type) = self.generate_setter_signature();
+
+            quote! {
+                pub fn #method_name<C>(#receiver, creatable: C) -> #builder_type
+                where C: #creatable + #lifetime
+                {
+                    self.#field_ident = Some(Box::new(creatable));
+                    self
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_factory_codegen_from_fails_on_invalid_input() {
+        // Arrange an enum (which is not supported)
+        let result = FactoryCodegen::from(parse_quote! {
+            enum Anvil {}
+        });
+
+        // Assert that it returns an error
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_generate_factory() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+                hardness: u32,
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the factory ident method
+        let generated = codegen.generate_factory();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl Anvil {
+                    pub fn factory() -> AnvilFactory {
+                        AnvilFactory::new()
+                    }
+                }
+                pub struct AnvilFactory {
+                    hammer_id: std::option::Option<u32>,
+                    hardness: std::option::Option<u32>,
+                    weight: std::option::Option<u32>,
+
+                    hammer_factory: std::option::Option<Box<dyn FnOnce(HammerFactory) -> HammerFactory + Send>>,
+                    hammer_on_created: std::option::Option<Box<dyn FnOnce(&Hammer) + Send>>,
+                }
+
+                #[automatically_derived]
+                impl AnvilFactory {
+                    pub const RELATIONS: &'static [&'static str] = &["hammer"];
+
+                    pub fn new() -> Self {
+                        Self {
+                            hammer_id: None,
+                            hardness: None,
+                            weight: None,
+                            hammer_factory: None,
+                            hammer_on_created: None,
+                        }
+                    }
+
+                    pub fn build(self) -> Anvil {
+                        Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        }
+                    }
+
+                    pub fn finalize(self) -> Anvil {
+                        self.build()
+                    }
+
+                    pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        if let Some(callback) = self.hammer_factory {
+                            let instance = callback(HammerFactory::new()).create(connection).await?;
+                            if let Some(on_created) = self.hammer_on_created {
+                                on_created(&instance);
+                            }
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                        }
+
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub async fn create_with_stats(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<(Anvil, fabrique::CreateStats), <Anvil as fabrique::Persistable>::Error> {
+                        let mut stats = fabrique::CreateStats::default();
+
+                        if let Some(callback) = self.hammer_factory {
+                            let (instance, related_stats) = callback(HammerFactory::new()).create_with_stats(connection).await?;
+                            stats += related_stats;
+                            if let Some(on_created) = self.hammer_on_created {
+                                on_created(&instance);
+                            }
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                        }
+
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        let instance = instance.create(connection).await?;
+                        stats.rows += 1;
+                        Ok((instance, stats))
+                    }
+
+                    pub async fn create_without_relations(self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub fn hammer_id(mut self, hammer_id: u32) -> Self {
+                        self.hammer_id = Some(hammer_id);
+                        self
+                    }
+
+                    pub fn hardness(mut self, hardness: u32) -> Self {
+                        self.hardness = Some(hardness);
+                        self
+                    }
+
+                    pub fn weight(mut self, weight: u32) -> Self {
+                        self.weight = Some(weight);
+                        self
+                    }
+
+                    pub fn for_hammer<F>(mut self, callback: F) -> Self
+                    where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                    {
+                        self.hammer_factory = Some(Box::new(callback));
+                        self
+                    }
+
+                    pub fn for_hammer_opt<F>(mut self, callback: Option<F>) -> Self
+                    where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                    {
+                        if let Some(callback) = callback {
+                            self.hammer_factory = Some(Box::new(callback));
+                        }
+                        self
+                    }
+
+                    pub fn with_hammer(mut self, instance: &Hammer) -> Self {
+                        {
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
+                        }
+                        self.hammer_factory = None;
+                        self
+                    }
+
+                    pub fn on_hammer_created<F>(mut self, callback: F) -> Self
+                    where F: FnOnce(&Hammer) + Send + 'static
+                    {
+                        self.hammer_on_created = Some(Box::new(callback));
+                        self
+                    }
+                }
+
+                #[automatically_derived]
+                impl Default for AnvilFactory {
+                    fn default() -> Self {
+                        AnvilFactory::new()
+                    }
+                }
+
+                #[automatically_derived]
+                impl std::fmt::Debug for AnvilFactory {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        use fabrique::debug::{DebugField as _, OpaqueField as _};
+
+                        let mut debug = f.debug_struct("AnvilFactory");
+                        debug.field("hammer_id", (&&fabrique::debug::Field(&self.hammer_id)).as_debug());
+                        debug.field("hardness", (&&fabrique::debug::Field(&self.hardness)).as_debug());
+                        debug.field("weight", (&&fabrique::debug::Field(&self.weight)).as_debug());
+                        debug.field("hammer_factory", &fabrique::debug::Presence(self.hammer_factory.is_some()));
+                        debug.field("hammer_on_created", &fabrique::debug::Presence(self.hammer_on_created.is_some()));
+                        debug.finish()
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_with_const_generics() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            #[repr(C)]
+            struct Anvil<const N: usize> {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory method
+        let generated = codegen.generate_factory();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl<const N: usize> Anvil<N> {
+                    pub fn factory() -> AnvilFactory<N> {
+                        AnvilFactory::new()
+                    }
+                }
+
+                pub struct AnvilFactory<const N: usize> {
+                    weight: std::option::Option<u32>,
+                }
+
+                #[automatically_derived]
+                impl<const N: usize> AnvilFactory<N> {
+                    pub const RELATIONS: &'static [&'static str] = &[];
+
+                    pub fn new() -> Self {
+                        Self {
+                            weight: None,
+                        }
+                    }
+
+                    pub fn build(self) -> Anvil<N> {
+                        Anvil {
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        }
+                    }
+
+                    pub fn finalize(self) -> Anvil<N> {
+                        self.build()
+                    }
+
+                    pub async fn create(mut self, connection: &<Anvil<N> as fabrique::Persistable>::Connection) -> Result<Anvil<N>, <Anvil<N> as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub async fn create_with_stats(mut self, connection: &<Anvil<N> as fabrique::Persistable>::Connection) -> Result<(Anvil<N>, fabrique::CreateStats), <Anvil<N> as fabrique::Persistable>::Error> {
+                        let mut stats = fabrique::CreateStats::default();
+
+                        let instance = Anvil {
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        let instance = instance.create(connection).await?;
+                        stats.rows += 1;
+                        Ok((instance, stats))
+                    }
+
+                    pub async fn create_without_relations(self, connection: &<Anvil<N> as fabrique::Persistable>::Connection) -> Result<Anvil<N>, <Anvil<N> as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub fn weight(mut self, weight: u32) -> Self {
+                        self.weight = Some(weight);
+                        self
+                    }
+                }
+
+                #[automatically_derived]
+                impl<const N: usize> Default for AnvilFactory<N> {
+                    fn default() -> Self {
+                        AnvilFactory::new()
+                    }
+                }
+
+                #[automatically_derived]
+                impl<const N: usize> std::fmt::Debug for AnvilFactory<N> {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        use fabrique::debug::{DebugField as _, OpaqueField as _};
+
+                        let mut debug = f.debug_struct("AnvilFactory");
+                        debug.field("weight", (&&fabrique::debug::Field(&self.weight)).as_debug());
+                        debug.finish()
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_with_lifetimes() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Label<'a> {
+                name: &'a str,
+                tags: Vec<&'a str>,
+
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the relation generation methods
+        let fields = codegen.generate_factory_relation_fields();
+        let callback_fields = codegen.generate_factory_relation_callback_fields();
+        let methods = codegen.generate_factory_methods_for_relation();
+
+        // Assert the closures are bound by the lifetime of the struct
+        assert_eq!(
+            quote! { #(#fields)* #(#callback_fields)* #(#methods)* }.to_string(),
+            quote! {
+                hammer_factory: std::option::Option<Box<dyn FnOnce(HammerFactory) -> HammerFactory + Send + 'a>>
+                hammer_on_created: std::option::Option<Box<dyn FnOnce(&Hammer) + Send + 'a>>
+                pub fn for_hammer<F>(mut self, callback: F) -> Self
+                where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'a
+                {
+                    self.hammer_factory = Some(Box::new(callback));
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_with_type_generics() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Crate<T>
+            where
+                T: Default,
+            {
+                content: T,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory method
+        let generated = codegen.generate_factory().to_string();
+
+        // Assert the parameters and where clause are threaded onto every item
+        for item in [
+            quote! {
+                impl<T> Crate<T> where T: Default, {
+                    pub fn factory() -> CrateFactory<T> {
+                        CrateFactory::new()
+                    }
+                }
+            },
+            quote! {
+                pub struct CrateFactory<T> where T: Default, {
+                    content: std::option::Option<T>,
+                }
+            },
+            quote! { impl<T> CrateFactory<T> where T: Default, },
+            quote! { impl<T> Default for CrateFactory<T> where T: Default, },
+            quote! { impl<T> std::fmt::Debug for CrateFactory<T> where T: Default, },
+            quote! { pub fn build(self) -> Crate<T> },
+        ] {
+            assert!(generated.contains(&item.to_string()), "missing {item}");
+        }
+    }
+
+    #[test]
+    fn test_generate_factory_with_pretty_markers() {
+        // Arrange the codegens
+        let pretty = FactoryCodegen::from(parse_quote! {
+            #[factory(pretty)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+        let plain = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the calls to the generate_factory method
+        let pretty = pretty.generate_factory().to_string();
+        let plain = plain.generate_factory().to_string();
+
+        // Assert a marker precedes each of the 8 factory items
+        let marker = quote! { #[doc = " ---"] }.to_string();
+        assert_eq!(pretty.matches(&marker).count(), 8);
+        assert!(
+            pretty.contains(
+                &quote! {
+                    #[doc = " ---"]
+                    pub fn weight(mut self, weight: u32) -> Self
+                }
+                .to_string()
+            )
+        );
+        assert!(!plain.contains(&marker));
+    }
+
+    #[test]
+    fn test_generate_factory_fields() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the codegen fields method
+        let generated: Vec<TokenStream> = codegen.generate_factory_fields().collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! { weight: std::option::Option<u32> }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_with_aliased_field_types() {
+        // Arrange the codegen of a struct whose fields are type aliases
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: units::Kilograms,
+                tags: Tags<'static>,
+            }
+        })
+        .unwrap();
+
+        // Act the calls to the fields, setters and build methods
+        let fields: Vec<TokenStream> = codegen.generate_factory_fields().collect();
+        let setters: Vec<TokenStream> = codegen.generate_factory_method_fields().collect();
+        let build = codegen.generate_factory_method_build();
+
+        // Assert the aliases are passed through as-is
+        assert_eq!(
+            fields[0].to_string(),
+            quote! { weight: std::option::Option<units::Kilograms> }.to_string()
+        );
+        assert_eq!(
+            setters[1].to_string(),
+            quote! {
+                pub fn tags(mut self, tags: Tags<'static>) -> Self {
+                    self.tags = Some(tags);
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            build.to_string(),
+            quote! {
+                pub fn build(self) -> Anvil {
+                    Anvil {
+                        weight: self.weight.unwrap_or(<units::Kilograms as fabrique::FactoryDefault>::factory_default()),
+                        tags: self.tags.unwrap_or(<Tags<'static> as fabrique::FactoryDefault>::factory_default()),
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_const_relations() {
+        // Arrange the codegens
+        let with_relation = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+        let without_relation = FactoryCodegen::from(parse_quote! {
+            struct Hammer {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the calls to the generate_factory_const_relations method
+        let with_relation = with_relation.generate_factory_const_relations();
+        let without_relation = without_relation.generate_factory_const_relations();
+
+        // Assert the results
+        assert_eq!(
+            with_relation.to_string(),
+            quote! {
+                pub const RELATIONS: &'static [&'static str] = &["hammer"];
+            }
+            .to_string()
+        );
+        assert_eq!(
+            without_relation.to_string(),
+            quote! {
+                pub const RELATIONS: &'static [&'static str] = &[];
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_relation_fields() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(relation = "Explosive", referenced_key = "id")]
+                explosive_id: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the codegen fields method
+        let generated: Vec<TokenStream> = codegen.generate_factory_relation_fields().collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                explosive_factory: std::option::Option<Box<dyn FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send>>
+            }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_ident() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {}
+        })
+        .unwrap();
+
+        // Act the call to the factory ident method
+        let generated = FactoryCodegen::generate_factory_ident(&factory.input.ident);
+
+        // Assert the result
+        assert_eq!(&generated, "AnvilFactory");
+    }
+
+    #[test]
+    fn test_generate_factory_path() {
+        // Arrange the related type path
+        let path: Path = parse_quote!(tools::Hammer);
+
+        // Act the call to the factory path method
+        let generated = FactoryCodegen::generate_factory_path(&path);
+
+        // Assert the result
+        let expected: Path = parse_quote!(tools::HammerFactory);
+        assert_eq!(generated, expected);
+    }
+
+    #[test]
+    fn test_generate_factory_method_create() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+                hardness: u32,
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the factory ident method
+        let generated = factory.generate_factory_method_create();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                    if let Some(callback) = self.hammer_factory {
+                        let instance = callback(HammerFactory::new()).create(connection).await?;
+                        if let Some(on_created) = self.hammer_on_created {
+                            on_created(&instance);
+                        }
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
+                        }
+
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                    }
+
+                    let instance = Anvil {
+                        hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+                    instance.create(connection).await
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_reads_the_referenced_key() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "uuid")]
+                hammer_uuid: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create method
+        let generated = factory.generate_factory_method_create().to_string();
+
+        // Assert the foreign key is read from the referenced key rather than `id`
+        assert!(generated.contains(
+            &quote! {
+                self.hammer_uuid = Some(<_ as RelationKey<String>>::into_foreign_key(instance.uuid));
+            }
+            .to_string()
+        ));
+        assert!(!generated.contains("instance . id"));
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_with_optional_foreign_key() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Node {
+                #[fabrique(relation = "Self", referenced_key = "id")]
+                parent_id: Option<u32>,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create method
+        let generated = factory.generate_factory_method_create().to_string();
+
+        // Assert the key is checked against the inner type and wrapped into `Some`
+        assert!(generated.contains(
+            &quote! {
+                self.parent_id = Some(Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id)));
+            }
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_with_after_create() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(after_create = "register_anvil")]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create method
+        let generated = factory.generate_factory_method_create();
+
+        // Assert the hook receives the persisted object
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                    let instance = Anvil {
+                        weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+                    let instance = instance.create(connection).await?;
+                    register_anvil(instance, connection).await
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_with_shared_relations() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Shipment {
+                #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+                pallet_id: u32,
+                #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+                return_pallet_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create method
+        let generated = factory.generate_factory_method_create();
+
+        // Assert the pallet is created once for both foreign keys
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub async fn create(mut self, connection: &<Shipment as fabrique::Persistable>::Connection) -> Result<Shipment, <Shipment as fabrique::Persistable>::Error> {
+                    assert!(
+                        [self.pallet_factory.is_some(), self.return_pallet_factory.is_some()].into_iter().filter(|set| *set).count() <= 1,
+                        "only one of `for_pallet()`, `for_return_pallet()` can be set, as the shared group `pallet` creates a single related object"
+                    );
+                    if let Some(callback) = self.pallet_factory.or(self.return_pallet_factory) {
+                        let instance = callback(PalletFactory::new()).create(connection).await?;
+                        if let Some(on_created) = self.pallet_on_created {
+                            on_created(&instance);
+                        }
+                        if let Some(on_created) = self.return_pallet_on_created {
+                            on_created(&instance);
+                        }
+                        {
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Shipment.pallet_id` and `Pallet.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.pallet_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
+                        }
+                        {
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Shipment.return_pallet_id` and `Pallet.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.return_pallet_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
+                        }
+                    }
+
+                    let instance = Shipment {
+                        pallet_id: self.pallet_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        return_pallet_id: self.return_pallet_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+                    instance.create(connection).await
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_build() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(default_factory = "HammerFactory")]
+                hammer: Hammer,
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_build method
+        let generated = factory.generate_factory_method_build();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub fn build(self) -> Anvil {
+                    Anvil {
+                        hammer: self.hammer.unwrap_or_else(|| HammerFactory::new().build()),
+                        weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_struct_fields_with_sequences() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence)]
+                serial_number: u32,
+                #[factory(sequence = "inventory")]
+                inventory_number: u64,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_struct_fields method
+        let generated: Vec<TokenStream> = factory.generate_struct_fields(false).collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                serial_number: self.serial_number.unwrap_or_else(|| {
+                    <u32 as std::convert::TryFrom<u64>>::try_from(ANVIL_SERIAL_NUMBER_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+                        .expect("sequence value out of range")
+                })
+            }
+            .to_string()
+        );
+        assert_eq!(
+            generated[1].to_string(),
+            quote! {
+                inventory_number: self.inventory_number.unwrap_or_else(|| {
+                    <u64 as std::convert::TryFrom<u64>>::try_from(fabrique::sequence::next("inventory"))
+                        .expect("sequence value out of range")
+                })
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_sequence_counters() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence)]
+                serial_number: u32,
+                #[factory(sequence = "inventory")]
+                inventory_number: u64,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_sequence_counters method
+        let generated: Vec<TokenStream> = factory.generate_sequence_counters().collect();
+
+        // Assert only the owned sequence gets a counter
+        assert_eq!(generated.len(), 1);
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                static ANVIL_SERIAL_NUMBER_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_struct_fields_with_literal_defaults() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(default = "5 * 100")]
+                weight: u32,
+                #[factory(default = "\"anvil\".to_string()")]
+                name: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_struct_fields method
+        let generated: Vec<TokenStream> = factory.generate_struct_fields(false).collect();
+
+        // Assert the defaults replace the `Default` fallback
+        assert_eq!(
+            generated[0].to_string(),
+            quote! { weight: self.weight.unwrap_or_else(|| 5 * 100) }.to_string()
+        );
+        assert_eq!(
+            generated[1].to_string(),
+            quote! { name: self.name.unwrap_or_else(|| "anvil".to_string()) }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_struct_fields_with_default_functions() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(default_fn = "chrono::Utc::now")]
+                forged_at: DateTime<Utc>,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_struct_fields method
+        let generated: Vec<TokenStream> = factory.generate_struct_fields(false).collect();
+
+        // Assert the function is only called when the field is unset
+        assert_eq!(
+            generated[0].to_string(),
+            quote! { forged_at: self.forged_at.unwrap_or_else(chrono::Utc::now) }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_struct_fields_with_formatted_sequence() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(sequence, sequence_format = "anvil-{n}")]
+                name: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_struct_fields method
+        let generated: Vec<TokenStream> = factory.generate_struct_fields(false).collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                name: self.name.unwrap_or_else(|| format!("anvil-{n}", n = ANVIL_NAME_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed)))
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_with_context() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(context = "Workshop")]
+            struct Anvil {
+                #[factory(default = "self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)")]
+                workshop_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory method
+        let generated = factory.generate_factory();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl Anvil {
+                    pub fn factory() -> AnvilFactory {
+                        AnvilFactory::new()
+                    }
+                }
+
+                pub struct AnvilFactory {
+                    workshop_id: std::option::Option<u32>,
+                    context: std::option::Option<Workshop>,
+                }
+
+                #[automatically_derived]
+                impl AnvilFactory {
+                    pub const RELATIONS: &'static [&'static str] = &[];
 
-                impl AnvilFactory {
                     pub fn new() -> Self {
                         Self {
-                            hammer_id: None,
-                            hardness: None,
-                            weight: None,
-                            hammer_factory: None,
+                            workshop_id: None,
+                            context: None,
+                        }
+                    }
+
+                    pub fn build(self) -> Anvil {
+                        Anvil {
+                            workshop_id: self.workshop_id.unwrap_or_else(|| self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)),
+                        }
+                    }
+
+                    pub fn finalize(self) -> Anvil {
+                        self.build()
+                    }
+
+                    pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            workshop_id: self.workshop_id.unwrap_or_else(|| self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub async fn create_with_stats(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<(Anvil, fabrique::CreateStats), <Anvil as fabrique::Persistable>::Error> {
+                        let mut stats = fabrique::CreateStats::default();
+
+                        let instance = Anvil {
+                            workshop_id: self.workshop_id.unwrap_or_else(|| self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)),
+                        };
+                        let instance = instance.create(connection).await?;
+                        stats.rows += 1;
+                        Ok((instance, stats))
+                    }
+
+                    pub async fn create_without_relations(self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            workshop_id: self.workshop_id.unwrap_or_else(|| self.context.as_ref().map(|workshop| workshop.id).unwrap_or(0)),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub fn workshop_id(mut self, workshop_id: u32) -> Self {
+                        self.workshop_id = Some(workshop_id);
+                        self
+                    }
+
+                    pub fn with_context(mut self, context: Workshop) -> Self {
+                        self.context = Some(context);
+                        self
+                    }
+                }
+
+                #[automatically_derived]
+                impl Default for AnvilFactory {
+                    fn default() -> Self {
+                        AnvilFactory::new()
+                    }
+                }
+
+                #[automatically_derived]
+                impl std::fmt::Debug for AnvilFactory {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        use fabrique::debug::{DebugField as _, OpaqueField as _};
+
+                        let mut debug = f.debug_struct("AnvilFactory");
+                        debug.field("workshop_id", (&&fabrique::debug::Field(&self.workshop_id)).as_debug());
+                        debug.field("context", (&&fabrique::debug::Field(&self.context)).as_debug());
+                        debug.finish()
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_with_stats() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(output = "Arc")]
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_with_stats method
+        let generated = factory.generate_factory_method_create_with_stats();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub async fn create_with_stats(mut self, connection: &<Toolbox as fabrique::Persistable>::Connection) -> Result<(std::sync::Arc<Toolbox>, fabrique::CreateStats), <Toolbox as fabrique::Persistable>::Error>
+                {
+                    let mut stats = fabrique::CreateStats::default();
+
+                    if let Some(creatable) = self.tool_creatable {
+                        self.tool_id = Some(creatable.create(connection).await?);
+                        stats.rows += 1;
+                    }
+
+                    let instance = Toolbox {
+                        tool_id: self.tool_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+
+                    let instance = instance.create(connection).await?;
+                    stats.rows += 1;
+                    Ok((std::sync::Arc::new(instance), stats))
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_tracked() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(tracked)]
+            struct Anvil {
+                #[fabrique(relation = "Self", referenced_key = "id")]
+                parent_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_tracked method
+        let generated = factory.generate_factory_method_create_tracked();
+
+        // Assert the related factory logs into the same log before the object itself
+        assert_eq!(
+            quote! { #generated }.to_string(),
+            quote! {
+                pub async fn create_tracked(mut self, connection: &<Anvil as fabrique::Persistable>::Connection, log: &mut fabrique::SeedLog) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error>
+                {
+                    if let Some(callback) = self.parent_factory {
+                        let instance = Box::pin(callback(AnvilFactory::new()).create_tracked(connection, log)).await?;
+                        if let Some(on_created) = self.parent_on_created {
+                            on_created(&instance);
+                        }
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Anvil.parent_id` and `Anvil.id`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
+                        }
+
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.parent_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                    }
+
+                    let instance = Anvil {
+                        parent_id: self.parent_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+
+                    let instance = instance.create(connection).await?;
+                    log.push((<Anvil as fabrique::Trackable>::TABLE_NAME, fabrique::Trackable::primary_key(&instance)));
+                    Ok(instance)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_tracked_requires_the_attribute() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_tracked method
+        let generated = factory.generate_factory_method_create_tracked();
+
+        // Assert nothing is generated
+        assert!(generated.is_none());
+    }
+
+    #[test]
+    fn test_generate_factory_with_output() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(output = "Arc")]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the calls to the build and create generators
+        let build = factory.generate_factory_method_build();
+        let create = factory.generate_factory_method_create();
+
+        // Assert the results
+        assert_eq!(
+            build.to_string(),
+            quote! {
+                pub fn build(self) -> std::sync::Arc<Anvil> {
+                    std::sync::Arc::new(Anvil {
+                        weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    })
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            create.to_string(),
+            quote! {
+                pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<std::sync::Arc<Anvil>, <Anvil as fabrique::Persistable>::Error>
+                {
+                    let instance = Anvil {
+                        weight: self.weight.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+
+                    instance.create(connection).await.map(std::sync::Arc::new)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_method_mock() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(mock)]
+            struct Anvil {
+                id: u32,
+                weight: f64,
+                name: String,
+                is_heavy: bool,
+                brand: Option<String>,
+                r#type: &'static str,
+                hammer: Hammer,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_method_mock method
+        let generated = factory.generate_method_mock();
+
+        // Assert the result
+        assert_eq!(
+            generated.unwrap().to_string(),
+            quote! {
+                pub fn mock() -> Self {
+                    Anvil {
+                        id: 1,
+                        weight: 1.0,
+                        name: "name".to_string(),
+                        is_heavy: false,
+                        brand: Some("brand".to_string()),
+                        r#type: "type",
+                        hammer: Default::default(),
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_method_mock_requires_the_attribute() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_method_mock method
+        let generated = factory.generate_method_mock();
+
+        // Assert the result
+        assert!(generated.is_none());
+    }
+
+    #[test]
+    fn test_generate_factory_method_finalize() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_finalize method
+        let generated = factory.generate_factory_method_finalize();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub fn finalize(self) -> Anvil {
+                    self.build()
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_try_build_validated() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(validate)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_try_build_validated method
+        let generated = factory.generate_factory_method_try_build_validated();
+
+        // Assert the result
+        assert_eq!(
+            generated.unwrap().to_string(),
+            quote! {
+                pub fn try_build_validated(self) -> Result<Anvil, <Anvil as fabrique::Validate>::Error> {
+                    let instance = self.build();
+                    <Anvil as fabrique::Validate>::validate(&instance)?;
+                    Ok(instance)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_try_build_validated_requires_the_attribute() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_try_build_validated method
+        let generated = factory.generate_factory_method_try_build_validated();
+
+        // Assert the result
+        assert!(generated.is_none());
+    }
+
+    #[test]
+    fn test_generate_factory_method_new() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                hardness: u32,
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the factory ident method
+        let generated = factory.generate_factory_method_new();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub fn new() -> Self {
+                    Self {
+                        hardness: None,
+                        weight: None,
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_fields() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                hardness: u32,
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_fields method
+        let generated: Vec<TokenStream> = factory.generate_factory_method_fields().collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn hardness(mut self, hardness: u32) -> Self {
+                    self.hardness = Some(hardness);
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_fields_with_rename() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(rename = "kind")]
+                r#type: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_fields method
+        let generated: Vec<TokenStream> = factory.generate_factory_method_fields().collect();
+
+        // Assert the setter is renamed but still assigns the raw field
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn kind(mut self, r#type: String) -> Self {
+                    self.r#type = Some(r#type);
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_fields_with_option() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                brand: Option<String>,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_fields and build methods
+        let generated: Vec<TokenStream> = factory.generate_factory_method_fields().collect();
+        let build = factory.generate_factory_method_build();
+
+        // Assert the setter takes the inner value or None and the field defaults to None
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn brand(mut self, brand: impl Into<Option<String>>) -> Self {
+                    self.brand = Some(brand.into());
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            build.to_string(),
+            quote! {
+                pub fn build(self) -> Anvil {
+                    Anvil {
+                        brand: self.brand.unwrap_or(None),
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_fields_with_mut_builder() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(mut_builder, context = "Workshop")]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_fields and build methods
+        let generated: Vec<TokenStream> = factory.generate_factory_method_fields().collect();
+        let build = factory.generate_factory_method_build();
+        let with_context = factory.generate_factory_method_with_context();
+
+        // Assert the factory is borrowed rather than consumed
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn weight(&mut self, weight: u32) -> &mut Self {
+                    self.weight = Some(weight);
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            build.to_string(),
+            quote! {
+                pub fn build(&mut self) -> Anvil {
+                    Anvil {
+                        weight: self.weight.clone().unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    }
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            with_context.unwrap().to_string(),
+            quote! {
+                pub fn with_context(&mut self, context: Workshop) -> &mut Self {
+                    self.context = Some(context);
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_relation_with_mut_builder() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(mut_builder)]
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the relation methods
+        let for_relation: Vec<TokenStream> =
+            factory.generate_factory_methods_for_relation().collect();
+        let for_relation_opt: Vec<TokenStream> = factory
+            .generate_factory_methods_for_relation_opt()
+            .collect();
+        let with_relation: Vec<TokenStream> =
+            factory.generate_factory_methods_with_relation().collect();
+        let on_relation_created: Vec<TokenStream> = factory
+            .generate_factory_methods_on_relation_created()
+            .collect();
+
+        // Assert the factory is borrowed rather than consumed
+        assert_eq!(
+            for_relation[0].to_string(),
+            quote! {
+                pub fn for_hammer<F>(&mut self, callback: F) -> &mut Self
+                where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                {
+                    self.hammer_factory = Some(Box::new(callback));
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            for_relation_opt[0].to_string(),
+            quote! {
+                pub fn for_hammer_opt<F>(&mut self, callback: Option<F>) -> &mut Self
+                where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                {
+                    if let Some(callback) = callback {
+                        self.hammer_factory = Some(Box::new(callback));
+                    }
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert!(with_relation[0].to_string().starts_with(
+            &quote! { pub fn with_hammer(&mut self, instance: &Hammer) -> &mut Self }.to_string()
+        ));
+        assert_eq!(
+            on_relation_created[0].to_string(),
+            quote! {
+                pub fn on_hammer_created<F>(&mut self, callback: F) -> &mut Self
+                where F: FnOnce(&Hammer) + Send + 'static
+                {
+                    self.hammer_on_created = Some(Box::new(callback));
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_extend_fields() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+                serial_numbers: Vec<u32>,
+                #[factory(no_default)]
+                markings: Vec<String>,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_extend_fields method
+        let generated: Vec<TokenStream> =
+            factory.generate_factory_methods_extend_fields().collect();
+
+        // Assert only the Vec fields get an extend_ setter
+        assert_eq!(generated.len(), 2);
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn extend_serial_numbers(mut self, items: impl IntoIterator<Item = u32>) -> Self {
+                    self.serial_numbers.get_or_insert_with(Vec::new).extend(items);
+                    self
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            generated[1].to_string(),
+            quote! {
+                pub fn extend_markings(mut self, items: impl IntoIterator<Item = String>) -> Self {
+                    self.markings.extend(items);
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_build_with_defaults_from() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(build_with_defaults_from = "anvil_template")]
+            struct Anvil {
+                #[factory(sequence)]
+                id: u32,
+                weight: u32,
+                hardness: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_build method
+        let generated = factory.generate_factory_method_build();
+
+        // Assert the template is used as the base and the set fields override it
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub fn build(self) -> Anvil {
+                    {
+                        let template = anvil_template();
+                        Anvil {
+                            id: self.id.unwrap_or_else(|| {
+                                <u32 as std::convert::TryFrom<u64>>::try_from(ANVIL_ID_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+                                    .expect("sequence value out of range")
+                            }),
+                            weight: self.weight.unwrap_or(template.weight),
+                            hardness: self.hardness.unwrap_or(template.hardness),
                         }
                     }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_repeat() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_repeat method
+        let result = factory.generate_factory_method_repeat();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub fn repeat(self, n: usize) -> impl Iterator<Item = Anvil> {
+                    (0..n).map(move |_| Anvil {
+                        weight: self.weight.clone().unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    })
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_repeat_requires_the_attribute() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! { struct Anvil { weight: u32 } }).unwrap();
+
+        // Act the call to the generate_factory_method_repeat method
+        let result = factory.generate_factory_method_repeat();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_factory_codegen_from_fails_on_repeat_with_dynamic_relations() {
+        // Act the call to the from method
+        let result = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat)]
+            struct Anvil {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        });
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::RepeatWithDynamicRelations)));
+    }
+
+    #[test]
+    fn test_factory_codegen_from_fails_on_tracked_with_dynamic_relations() {
+        // Act the call to the from method
+        let result = FactoryCodegen::from(parse_quote! {
+            #[factory(tracked)]
+            struct Anvil {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        });
+
+        // Assert the result
+        assert!(matches!(result, Err(Error::TrackedWithDynamicRelations)));
+    }
+
+    #[test]
+    fn test_generate_factory_relation_fields_with_repeat() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat)]
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_relation_fields method
+        let result = factory
+            .generate_factory_relation_fields()
+            .collect::<Vec<_>>();
+
+        // Assert the callbacks are shared and can run several times
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].to_string(),
+            quote! {
+                hammer_factory: std::option::Option<std::sync::Arc<dyn Fn(HammerFactory) -> HammerFactory + Send + Sync>>
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_many() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_many method
+        let result = factory.generate_factory_method_create_many();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn create_many(self, count: usize, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Vec<Anvil>, <Anvil as fabrique::Persistable>::Error>
+                {
+                    let mut created = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        created.push(self.clone().create(connection).await?);
+                    }
+
+                    Ok(created)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_many_with() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_many_with method
+        let result = factory.generate_factory_method_create_many_with();
+
+        // Assert each clone of the factory goes through the closure with its index
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn create_many_with(self, count: usize, customize: impl Fn(usize, Self) -> Self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Vec<Anvil>, <Anvil as fabrique::Persistable>::Error>
+                {
+                    let mut created = Vec::with_capacity(count);
+                    for index in 0..count {
+                        let factory = customize(index, self.clone());
+                        created.push(factory.create(connection).await?);
+                    }
 
-                    pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
-                        if let Some(callback) = self.hammer_factory {
-                            let instance = callback(HammerFactory::new()).create(connection).await?;
-                            self.hammer_id = Some(instance.id);
+                    Ok(created)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_many_with_mut_builder() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            #[factory(repeat, mut_builder)]
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_many_with method
+        let result = factory.generate_factory_method_create_many_with().unwrap();
+
+        // Assert the closure customizes the clone in place
+        let expected = quote! {
+            customize: impl Fn(usize, &mut Self),
+        };
+        assert!(result.to_string().contains(&expected.to_string()));
+        let expected = quote! {
+            let mut factory = self.clone();
+            customize(index, &mut factory);
+        };
+        assert!(result.to_string().contains(&expected.to_string()));
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_many_requires_the_attribute() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! { struct Anvil { weight: u32 } }).unwrap();
+
+        // Act the call to the generate_factory_method_create_many method
+        let result = factory.generate_factory_method_create_many();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_factory_methods_try_fields() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                hardness: u32,
+                #[factory(validate_field = "check_weight")]
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_try_fields method
+        let generated: Vec<TokenStream> = factory.generate_factory_methods_try_fields().collect();
+
+        // Assert only the validated field gets a fallible setter
+        assert_eq!(generated.len(), 1);
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn try_weight(mut self, weight: u32) -> Result<Self, <Anvil as fabrique::Validate>::Error> {
+                    check_weight(&weight)?;
+                    self.weight = Some(weight);
+                    Ok(self)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_try_fields_with_validator_error() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[factory(validate_field = "check_weight", validate_field_error = "WeightError")]
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_try_fields method
+        let generated: Vec<TokenStream> = factory.generate_factory_methods_try_fields().collect();
+
+        // Assert the setter returns the error of the validator
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn try_weight(mut self, weight: u32) -> Result<Self, WeightError> {
+                    check_weight(&weight)?;
+                    self.weight = Some(weight);
+                    Ok(self)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_for_relation() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(relation = "Explosive", referenced_key = "id")]
+                explosive_id: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_fields method
+        let generated: Vec<TokenStream> = factory.generate_factory_methods_for_relation().collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn for_explosive<F>(mut self, callback: F) -> Self
+                where F: FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send + 'static
+                {
+                    self.explosive_factory = Some(Box::new(callback));
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_for_relation_with_rename() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(relation = "Explosive", referenced_key = "id")]
+                #[factory(rename = "charge")]
+                explosive_id: String,
+            }
+        })
+        .unwrap();
+
+        // Act the calls to the relation fields and methods generation
+        let fields: Vec<TokenStream> = factory.generate_factory_relation_fields().collect();
+        let methods: Vec<TokenStream> = factory.generate_factory_methods_for_relation().collect();
+
+        // Assert both the field and the method use the renamed value
+        assert_eq!(
+            fields[0].to_string(),
+            quote! {
+                charge_factory: std::option::Option<Box<dyn FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send>>
+            }
+            .to_string()
+        );
+        assert_eq!(
+            methods[0].to_string(),
+            quote! {
+                pub fn for_charge<F>(mut self, callback: F) -> Self
+                where F: FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send + 'static
+                {
+                    self.charge_factory = Some(Box::new(callback));
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_for_relation_with_default_child() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(
+                    relation = "Explosive",
+                    referenced_key = "id",
+                    default_child = "|factory| factory.yield_kg(5)"
+                )]
+                explosive_id: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_for_relation and new methods
+        let generated: Vec<TokenStream> = factory.generate_factory_methods_for_relation().collect();
+        let new = factory.generate_factory_method_new();
+
+        // Assert the user callback is composed after the default
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn for_explosive<F>(mut self, callback: F) -> Self
+                where F: FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send + 'static
+                {
+                    self.explosive_factory = Some(Box::new(move |factory| callback(({
+                        let default_child: fn(ExplosiveFactory) -> ExplosiveFactory = |factory| factory.yield_kg(5);
+                        default_child
+                    })(factory))));
+                    self
+                }
+            }
+            .to_string()
+        );
+        // Assert the default applies when the relation is not set
+        assert!(new.to_string().contains(
+            &quote! {
+                explosive_factory: Some(Box::new({
+                    let default_child: fn(ExplosiveFactory) -> ExplosiveFactory = |factory| factory.yield_kg(5);
+                    default_child
+                }))
+            }
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn test_generate_factory_methods_for_relation_opt() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(relation = "Explosive", referenced_key = "id")]
+                explosive_id: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_for_relation_opt method
+        let generated: Vec<TokenStream> = factory
+            .generate_factory_methods_for_relation_opt()
+            .collect();
+
+        // Assert the result
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn for_explosive_opt<F>(mut self, callback: Option<F>) -> Self
+                where F: FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send + 'static
+                {
+                    if let Some(callback) = callback {
+                        self.explosive_factory = Some(Box::new(callback));
+                    }
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_with_relation() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Dynamite {
+                #[fabrique(relation = "Explosive", referenced_key = "uuid")]
+                explosive_uuid: String,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_with_relation method
+        let generated: Vec<TokenStream> =
+            factory.generate_factory_methods_with_relation().collect();
+
+        // Assert the key is read from the instance and the callback dropped
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn with_explosive(mut self, instance: &Explosive) -> Self {
+                    {
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Dynamite.explosive_uuid` and `Explosive.uuid`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
                         }
 
-                        let instance = Anvil {
-                            hammer_id: self.hammer_id.unwrap_or(<u32 as Default>::default()),
-                            hardness: self.hardness.unwrap_or(<u32 as Default>::default()),
-                            weight: self.weight.unwrap_or(<u32 as Default>::default()),
-                        };
-                        instance.create(connection).await
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.explosive_uuid = Some(<_ as RelationKey<String>>::into_foreign_key(instance.uuid.clone()));
                     }
+                    self.explosive_factory = None;
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
 
-                    pub fn hammer_id(mut self, hammer_id: u32) -> Self {
-                        self.hammer_id = Some(hammer_id);
-                        self
+    #[test]
+    fn test_generate_factory_methods_with_shared_relation() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Delivery {
+                #[fabrique(relation = "Warehouse", referenced_key = "id", shared = "site")]
+                origin_id: u32,
+                #[fabrique(relation = "Warehouse", referenced_key = "id", shared = "site")]
+                destination_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_methods_with_relation method
+        let generated: Vec<TokenStream> =
+            factory.generate_factory_methods_with_relation().collect();
+
+        // Assert linking one relation links the whole group
+        assert_eq!(
+            generated[0].to_string(),
+            quote! {
+                pub fn with_origin(mut self, instance: &Warehouse) -> Self {
+                    {
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Delivery.origin_id` and `Warehouse.id`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
+                        }
+
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.origin_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
                     }
+                    self.origin_factory = None;
+                    {
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Delivery.destination_id` and `Warehouse.id`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
+                        }
 
-                    pub fn hardness(mut self, hardness: u32) -> Self {
-                        self.hardness = Some(hardness);
-                        self
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.destination_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
                     }
+                    self.destination_factory = None;
+                    self
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_without_relations() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            #[factory(output = "Arc")]
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_method_create_without_relations method
+        let generated = codegen.generate_factory_method_create_without_relations();
+
+        // Assert the relation callbacks are not called
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                pub async fn create_without_relations(self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<std::sync::Arc<Anvil>, <Anvil as fabrique::Persistable>::Error>
+                {
+                    let instance = Anvil {
+                        hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+
+                    instance.create(connection).await.map(std::sync::Arc::new)
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_impl_default() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Anvil<const N: usize> {
+                weight: u32,
+            }
+        })
+        .unwrap();
 
-                    pub fn weight(mut self, weight: u32) -> Self {
-                        self.weight = Some(weight);
-                        self
-                    }
+        // Act the call to the generate_factory_impl_default method
+        let generated = codegen.generate_factory_impl_default();
 
-                    pub fn for_hammer<F>(mut self, callback: F) -> Self
-                    where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
-                    {
-                        self.hammer_factory = Some(Box::new(callback));
-                        self
+        // Assert the implementation delegates to new()
+        assert_eq!(
+            quote! { #generated }.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl<const N: usize> Default for AnvilFactory<N> {
+                    fn default() -> Self {
+                        AnvilFactory::new()
                     }
                 }
             }
@@ -299,94 +3783,210 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_factory_fields() {
+    fn test_generate_factory_impl_default_skips_factories_with_required_fields() {
         // Arrange the codegen
         let codegen = FactoryCodegen::from(parse_quote! {
             struct Anvil {
+                #[factory(no_default)]
                 weight: u32,
             }
         })
         .unwrap();
 
-        // Act the call to the codegen fields method
-        let generated: Vec<TokenStream> = codegen.generate_factory_fields().collect();
+        // Act the call to the generate_factory_impl_default method
+        let generated = codegen.generate_factory_impl_default();
 
-        // Assert the result
+        // Assert no implementation is generated
+        assert!(generated.is_none());
+    }
+
+    #[test]
+    fn test_generate_factory_impl_debug() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Toolbox {
+                r#type: String,
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory_impl_debug method
+        let generated = codegen.generate_factory_impl_debug();
+
+        // Assert the trait object is only rendered as set or unset
         assert_eq!(
-            generated[0].to_string(),
-            quote! { weight: std::option::Option<u32> }.to_string()
+            generated.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl std::fmt::Debug for ToolboxFactory {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        use fabrique::debug::{DebugField as _, OpaqueField as _};
+
+                        let mut debug = f.debug_struct("ToolboxFactory");
+                        debug.field("type", (&&fabrique::debug::Field(&self.r#type)).as_debug());
+                        debug.field("tool_id", (&&fabrique::debug::Field(&self.tool_id)).as_debug());
+                        debug.field("tool_creatable", &fabrique::debug::Presence(self.tool_creatable.is_some()));
+                        debug.finish()
+                    }
+                }
+            }
+            .to_string()
         );
     }
 
     #[test]
-    fn test_generate_factory_relation_fields() {
+    fn test_generate_factory_dynamic_relation_fields() {
         // Arrange the codegen
         let codegen = FactoryCodegen::from(parse_quote! {
-            struct Dynamite {
-                #[fabrique(relation = "Explosive", referenced_key = "id")]
-                explosive_id: String,
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
             }
         })
         .unwrap();
 
         // Act the call to the codegen fields method
-        let generated: Vec<TokenStream> = codegen.generate_factory_relation_fields().collect();
+        let generated: Vec<TokenStream> =
+            codegen.generate_factory_dynamic_relation_fields().collect();
 
         // Assert the result
         assert_eq!(
             generated[0].to_string(),
             quote! {
-                explosive_factory: std::option::Option<Box<dyn FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send>>
-            }.to_string()
+                tool_creatable: std::option::Option<Box<dyn Creatable<u32, <Toolbox as fabrique::Persistable>::Connection, <Toolbox as fabrique::Persistable>::Error> >>
+            }
+            .to_string()
         );
     }
 
     #[test]
-    fn test_generate_factory_ident() {
-        // Arrange the codegen
+    fn test_generate_factory_only_calls_the_new_of_factories() {
+        // Arrange the codegen of a model which may have an inherent `new()`
         let factory = FactoryCodegen::from(parse_quote! {
-            struct Anvil {}
+            struct Node {
+                id: u32,
+                #[fabrique(relation = "Self", referenced_key = "id")]
+                parent_id: u32,
+                #[factory(default_factory = "HammerFactory")]
+                hammer: Hammer,
+            }
         })
         .unwrap();
 
-        // Act the call to the factory ident method
-        let generated = FactoryCodegen::generate_factory_ident(&factory.input.ident);
+        // Act the call to the generate_factory method
+        let generated = factory.generate_factory().to_string();
 
-        // Assert the result
-        assert_eq!(&generated, "AnvilFactory");
+        // Assert every `new()` call targets a factory
+        assert!(generated.contains(&quote! { NodeFactory::new() }.to_string()));
+        assert!(generated.contains(&quote! { HammerFactory::new() }.to_string()));
+        assert!(!generated.contains(&quote! { Node::new }.to_string()));
+        assert!(!generated.contains(&quote! { Self::new }.to_string()));
     }
 
     #[test]
-    fn test_generate_factory_method_create() {
+    fn test_generate_factory_with_a_field_without_default() {
         // Arrange the codegen
         let factory = FactoryCodegen::from(parse_quote! {
             struct Anvil {
-                #[fabrique(relation = "Hammer", referenced_key = "id")]
-                hammer_id: u32,
+                #[factory(no_default)]
+                weight: u32,
                 hardness: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the field and new generation methods
+        let fields = factory.generate_factory_fields();
+        let new = factory.generate_factory_method_new();
+        let setters = factory.generate_factory_method_fields();
+        let build = factory.generate_factory_method_build();
+
+        // Assert the field is required by new() and not wrapped into an Option
+        assert_eq!(
+            quote! { #(#fields,)* #new #(#setters)* #build }.to_string(),
+            quote! {
                 weight: u32,
+                hardness: std::option::Option<u32>,
+
+                pub fn new(weight: u32) -> Self {
+                    Self {
+                        weight,
+                        hardness: None,
+                    }
+                }
+
+                pub fn weight(mut self, weight: u32) -> Self {
+                    self.weight = weight;
+                    self
+                }
+
+                pub fn hardness(mut self, hardness: u32) -> Self {
+                    self.hardness = Some(hardness);
+                    self
+                }
+
+                pub fn build(self) -> Anvil {
+                    Anvil {
+                        weight: self.weight,
+                        hardness: self.hardness.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_method_create_with_self_relation() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Node {
+                id: u32,
+                #[fabrique(relation = "Self", referenced_key = "id")]
+                parent_id: u32,
             }
         })
         .unwrap();
 
-        // Act the call to the factory ident method
+        // Act the call to the generate_factory_method_create method
         let generated = factory.generate_factory_method_create();
 
         // Assert the result
         assert_eq!(
             generated.to_string(),
             quote! {
-                pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
-                    if let Some(callback) = self.hammer_factory {
-                        let instance = callback(HammerFactory::new()).create(connection).await?;
-                        self.hammer_id = Some(instance.id);
+                pub async fn create(mut self, connection: &<Node as fabrique::Persistable>::Connection) -> Result<Node, <Node as fabrique::Persistable>::Error>
+                {
+                    if let Some(callback) = self.parent_factory {
+                        let instance = Box::pin(callback(NodeFactory::new()).create(connection)).await?;
+                        if let Some(on_created) = self.parent_on_created {
+                            on_created(&instance);
+                        }
+                        #[diagnostic::on_unimplemented(
+                            message = "relation FK type mismatch between `Node.parent_id` and `Node.id`",
+                            label = "the referenced key must have the type of the foreign key"
+                        )]
+                        trait RelationKey<ForeignKey> {
+                            fn into_foreign_key(self) -> ForeignKey;
+                        }
+
+                        #[automatically_derived]
+                        impl<T> RelationKey<T> for T {
+                            fn into_foreign_key(self) -> T {
+                                self
+                            }
+                        }
+
+                        self.parent_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
                     }
 
-                    let instance = Anvil {
-                        hammer_id: self.hammer_id.unwrap_or(<u32 as Default>::default()),
-                        hardness: self.hardness.unwrap_or(<u32 as Default>::default()),
-                        weight: self.weight.unwrap_or(<u32 as Default>::default()),
+                    let instance = Node {
+                        id: self.id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        parent_id: self.parent_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
                     };
+
                     instance.create(connection).await
                 }
             }
@@ -395,28 +3995,32 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_factory_method_new() {
+    fn test_generate_factory_method_create_with_dynamic_relation() {
         // Arrange the codegen
         let factory = FactoryCodegen::from(parse_quote! {
-            struct Anvil {
-                hardness: u32,
-                weight: u32,
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
             }
         })
         .unwrap();
 
-        // Act the call to the factory ident method
-        let generated = factory.generate_factory_method_new();
+        // Act the call to the generate_factory_method_create method
+        let generated = factory.generate_factory_method_create();
 
         // Assert the result
         assert_eq!(
             generated.to_string(),
             quote! {
-                pub fn new() -> Self {
-                    Self {
-                        hardness: None,
-                        weight: None,
+                pub async fn create(mut self, connection: &<Toolbox as fabrique::Persistable>::Connection) -> Result<Toolbox, <Toolbox as fabrique::Persistable>::Error> {
+                    if let Some(creatable) = self.tool_creatable {
+                        self.tool_id = Some(creatable.create(connection).await?);
                     }
+
+                    let instance = Toolbox {
+                        tool_id: self.tool_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                    };
+                    instance.create(connection).await
                 }
             }
             .to_string()
@@ -424,25 +4028,29 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_factory_method_fields() {
+    fn test_generate_factory_methods_for_dynamic_relation() {
         // Arrange the codegen
         let factory = FactoryCodegen::from(parse_quote! {
-            struct Anvil {
-                hardness: u32,
-                weight: u32,
+            struct Toolbox {
+                #[fabrique(relation_trait = "dyn Creatable")]
+                tool_id: u32,
             }
         })
         .unwrap();
 
-        // Act the call to the generate_factory_method_fields method
-        let generated: Vec<TokenStream> = factory.generate_factory_method_fields().collect();
+        // Act the call to the generate_factory_methods_for_dynamic_relation method
+        let generated: Vec<TokenStream> = factory
+            .generate_factory_methods_for_dynamic_relation()
+            .collect();
 
         // Assert the result
         assert_eq!(
             generated[0].to_string(),
             quote! {
-                pub fn hardness(mut self, hardness: u32) -> Self {
-                    self.hardness = Some(hardness);
+                pub fn for_tool<C>(mut self, creatable: C) -> Self
+                where C: Creatable<u32, <Toolbox as fabrique::Persistable>::Connection, <Toolbox as fabrique::Persistable>::Error> + 'static
+                {
+                    self.tool_creatable = Some(Box::new(creatable));
                     self
                 }
             }
@@ -451,7 +4059,220 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_factory_methods_for_relation() {
+    fn test_generate_factory_with_cfg_relation() {
+        // Arrange the codegen
+        let factory = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                #[fabrique(relation = "Hammer", referenced_key = "id", cfg = "feature = \"hammers\"")]
+                hammer_id: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory method
+        let generated = factory.generate_factory();
+
+        // Assert the result
+        assert_eq!(
+            generated.to_string(),
+            quote! {
+                #[automatically_derived]
+                impl Anvil {
+                    pub fn factory() -> AnvilFactory {
+                        AnvilFactory::new()
+                    }
+                }
+
+                pub struct AnvilFactory {
+                    hammer_id: std::option::Option<u32>,
+                    #[cfg(feature = "hammers")]
+                    hammer_factory: std::option::Option<Box<dyn FnOnce(HammerFactory) -> HammerFactory + Send>>,
+                    #[cfg(feature = "hammers")]
+                    hammer_on_created: std::option::Option<Box<dyn FnOnce(&Hammer) + Send>>,
+                }
+
+                #[automatically_derived]
+                impl AnvilFactory {
+                    pub const RELATIONS: &'static [&'static str] = &[#[cfg(feature = "hammers")] "hammer"];
+
+                    pub fn new() -> Self {
+                        Self {
+                            hammer_id: None,
+                            #[cfg(feature = "hammers")]
+                            hammer_factory: None,
+                            #[cfg(feature = "hammers")]
+                            hammer_on_created: None,
+                        }
+                    }
+
+                    pub fn build(self) -> Anvil {
+                        Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        }
+                    }
+
+                    pub fn finalize(self) -> Anvil {
+                        self.build()
+                    }
+
+                    pub async fn create(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        #[cfg(feature = "hammers")]
+                        if let Some(callback) = self.hammer_factory {
+                            let instance = callback(HammerFactory::new()).create(connection).await?;
+                            if let Some(on_created) = self.hammer_on_created {
+                                on_created(&instance);
+                            }
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                        }
+
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub async fn create_with_stats(mut self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<(Anvil, fabrique::CreateStats), <Anvil as fabrique::Persistable>::Error> {
+                        let mut stats = fabrique::CreateStats::default();
+
+                        #[cfg(feature = "hammers")]
+                        if let Some(callback) = self.hammer_factory {
+                            let (instance, related_stats) = callback(HammerFactory::new()).create_with_stats(connection).await?;
+                            stats += related_stats;
+                            if let Some(on_created) = self.hammer_on_created {
+                                on_created(&instance);
+                            }
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id));
+                        }
+
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        let instance = instance.create(connection).await?;
+                        stats.rows += 1;
+                        Ok((instance, stats))
+                    }
+
+                    pub async fn create_without_relations(self, connection: &<Anvil as fabrique::Persistable>::Connection) -> Result<Anvil, <Anvil as fabrique::Persistable>::Error> {
+                        let instance = Anvil {
+                            hammer_id: self.hammer_id.unwrap_or(<u32 as fabrique::FactoryDefault>::factory_default()),
+                        };
+                        instance.create(connection).await
+                    }
+
+                    pub fn hammer_id(mut self, hammer_id: u32) -> Self {
+                        self.hammer_id = Some(hammer_id);
+                        self
+                    }
+
+                    #[cfg(feature = "hammers")]
+                    pub fn for_hammer<F>(mut self, callback: F) -> Self
+                    where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                    {
+                        self.hammer_factory = Some(Box::new(callback));
+                        self
+                    }
+
+                    #[cfg(feature = "hammers")]
+                    pub fn for_hammer_opt<F>(mut self, callback: Option<F>) -> Self
+                    where F: FnOnce(HammerFactory) -> HammerFactory + Send + 'static
+                    {
+                        if let Some(callback) = callback {
+                            self.hammer_factory = Some(Box::new(callback));
+                        }
+                        self
+                    }
+
+                    #[cfg(feature = "hammers")]
+                    pub fn with_hammer(mut self, instance: &Hammer) -> Self {
+                        {
+                            #[diagnostic::on_unimplemented(
+                                message = "relation FK type mismatch between `Anvil.hammer_id` and `Hammer.id`",
+                                label = "the referenced key must have the type of the foreign key"
+                            )]
+                            trait RelationKey<ForeignKey> {
+                                fn into_foreign_key(self) -> ForeignKey;
+                            }
+
+                            #[automatically_derived]
+                            impl<T> RelationKey<T> for T {
+                                fn into_foreign_key(self) -> T {
+                                    self
+                                }
+                            }
+
+                            self.hammer_id = Some(<_ as RelationKey<u32>>::into_foreign_key(instance.id.clone()));
+                        }
+                        self.hammer_factory = None;
+                        self
+                    }
+
+                    #[cfg(feature = "hammers")]
+                    pub fn on_hammer_created<F>(mut self, callback: F) -> Self
+                    where F: FnOnce(&Hammer) + Send + 'static
+                    {
+                        self.hammer_on_created = Some(Box::new(callback));
+                        self
+                    }
+                }
+
+                #[automatically_derived]
+                impl Default for AnvilFactory {
+                    fn default() -> Self {
+                        AnvilFactory::new()
+                    }
+                }
+
+                #[automatically_derived]
+                impl std::fmt::Debug for AnvilFactory {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        use fabrique::debug::{DebugField as _, OpaqueField as _};
+
+                        let mut debug = f.debug_struct("AnvilFactory");
+                        debug.field("hammer_id", (&&fabrique::debug::Field(&self.hammer_id)).as_debug());
+                        #[cfg(feature = "hammers")]
+                        debug.field("hammer_factory", &fabrique::debug::Presence(self.hammer_factory.is_some()));
+                        #[cfg(feature = "hammers")]
+                        debug.field("hammer_on_created", &fabrique::debug::Presence(self.hammer_on_created.is_some()));
+                        debug.finish()
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_factory_methods_on_relation_created() {
         // Arrange the codegen
         let factory = FactoryCodegen::from(parse_quote! {
             struct Dynamite {
@@ -461,21 +4282,43 @@ mod tests {
         })
         .unwrap();
 
-        // Act the call to the generate_factory_method_fields method
-        let generated: Vec<TokenStream> = factory.generate_factory_methods_for_relation().collect();
+        // Act the call to the generate_factory_methods_on_relation_created method
+        let generated: Vec<TokenStream> = factory
+            .generate_factory_methods_on_relation_created()
+            .collect();
 
         // Assert the result
         assert_eq!(
             generated[0].to_string(),
             quote! {
-                pub fn for_explosive<F>(mut self, callback: F) -> Self
-                where F: FnOnce(ExplosiveFactory) -> ExplosiveFactory + Send + 'static
+                pub fn on_explosive_created<F>(mut self, callback: F) -> Self
+                where F: FnOnce(&Explosive) + Send + 'static
                 {
-                    self.explosive_factory = Some(Box::new(callback));
+                    self.explosive_on_created = Some(Box::new(callback));
                     self
                 }
             }
             .to_string()
         );
     }
+
+    #[test]
+    fn test_generate_factory_marks_impls_as_automatically_derived() {
+        // Arrange the codegen
+        let codegen = FactoryCodegen::from(parse_quote! {
+            struct Anvil {
+                weight: u32,
+            }
+        })
+        .unwrap();
+
+        // Act the call to the generate_factory method
+        let generated = codegen.generate_factory().to_string();
+
+        // Assert the result
+        assert!(generated.contains(&quote! { #[automatically_derived] impl Anvil }.to_string()));
+        assert!(
+            generated.contains(&quote! { #[automatically_derived] impl AnvilFactory }.to_string())
+        );
+    }
 }
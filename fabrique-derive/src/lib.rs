@@ -20,7 +20,7 @@ pub fn derive_persistable(input: TokenStream) -> TokenStream {
     let span = input.span();
     crate::persistable::PersistableCodegen::from(&input)
         .and_then(|codegen| codegen.generate())
-        .unwrap_or_else(|e| Error::new(span, e).into_compile_error())
+        .unwrap_or_else(|e| Error::new(e.span().unwrap_or(span), e).into_compile_error())
         .into()
 }
 
@@ -31,6 +31,6 @@ pub fn derive_factory(input: TokenStream) -> TokenStream {
     let span = input.span();
     FactoryCodegen::from(input)
         .map(|codegen| codegen.generate_factory())
-        .unwrap_or_else(|e| Error::new(span, e).into_compile_error())
+        .unwrap_or_else(|e| Error::new(e.span().unwrap_or(span), e).into_compile_error())
         .into()
 }
@@ -1,7 +1,13 @@
-use crate::{analysis::Analysis, error::Error};
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::DeriveInput;
+use crate::{
+    analysis::{Analysis, Backend, JoinedRelation, Placeholder},
+    error::Error,
+};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Field, Ident, ext::IdentExt};
+
+/// The SQL expression the timestamp columns are set to, supported by every backend.
+const CURRENT_TIMESTAMP: &str = "CURRENT_TIMESTAMP";
 
 /// Code generator for persistable trait implementation.
 pub struct PersistableCodegen<'a> {
@@ -9,93 +15,3667 @@ pub struct PersistableCodegen<'a> {
     analysis: Analysis<'a>,
 }
 
-impl<'a> PersistableCodegen<'a> {
-    /// Creates a code generator from the given derive input.
-    pub fn from(input: &'a DeriveInput) -> Result<Self, Error> {
-        let analysis = Analysis::from(input)?;
+impl<'a> PersistableCodegen<'a> {
+    /// Creates a code generator from the given derive input.
+    pub fn from(input: &'a DeriveInput) -> Result<Self, Error> {
+        let analysis = Analysis::from(input)?;
+
+        Ok(Self { analysis })
+    }
+
+    pub fn generate(self) -> Result<TokenStream, Error> {
+        let base_struct_ident = &self.analysis.ident;
+        let fn_all = self.generate_fn_all();
+        let fn_create = self.generate_fn_create();
+        let fn_update = self.generate_fn_update()?;
+        let fn_delete = self.generate_fn_delete()?;
+        let fn_count = self.generate_fn_count();
+        let fns_count_distinct = self.generate_fns_count_distinct();
+        let fn_all_sorted_by = self.generate_fn_all_sorted_by()?;
+        let fn_paginate = self.generate_fn_paginate();
+        let fn_copy_in = self.generate_fn_copy_in();
+        let fns_soft_delete = self.generate_fns_soft_delete()?;
+        let fn_create_batch = self.generate_fn_create_batch();
+        let fn_all_by_id = self.generate_fn_all_by_id();
+        let fn_last_created = self.generate_fn_last_created();
+        let fn_modified_since = self.generate_fn_modified_since();
+        let fn_reload_all = self.generate_fn_reload_all();
+        let fn_which_exist = self.generate_fn_which_exist();
+        let fn_create_sql = self.generate_fn_create_sql();
+        let fn_create_tx = self.generate_fn_create_tx();
+        let fn_create_with_event = self.generate_fn_create_with_event();
+        let fn_get_or_create = self.generate_fn_get_or_create();
+        let fn_find_by_id = self.generate_fn_find_by_id()?;
+        let fns_find_with = self.generate_fns_find_with()?;
+        let fn_all_for_update = self.generate_fn_all_for_update();
+        let all_cache = self.generate_all_cache();
+        let fn_all_cached = self.generate_fn_all_cached();
+        let sort_column = self.generate_sort_column()?;
+        let from_row = self.generate_from_row();
+        let impl_trackable = self.generate_impl_trackable()?;
+
+        // Every entry is listed from the generated method itself, so the list cannot drift
+        // from what the attributes and backend enabled
+        let base_fns = [
+            ("create", Some(&fn_create)),
+            ("all", Some(&fn_all)),
+            ("update", Some(&fn_update)),
+            ("delete", Some(&fn_delete)),
+            ("count", Some(&fn_count)),
+            ("create_tx", Some(&fn_create_tx)),
+            ("find_by_id", Some(&fn_find_by_id)),
+            ("all_for_update", fn_all_for_update.as_ref()),
+            ("paginate", fn_paginate.as_ref()),
+        ];
+        let optional_fns = [
+            ("all_sorted_by", fn_all_sorted_by.as_ref()),
+            ("create_with_event", fn_create_with_event.as_ref()),
+            ("get_or_create", fn_get_or_create.as_ref()),
+            ("copy_in", fn_copy_in.as_ref()),
+            ("create_batch", fn_create_batch.as_ref()),
+            ("all_by_id", fn_all_by_id.as_ref()),
+            ("last_created", fn_last_created.as_ref()),
+            ("modified_since", fn_modified_since.as_ref()),
+            ("reload_all", fn_reload_all.as_ref()),
+            ("which_exist", fn_which_exist.as_ref()),
+            ("create_sql", fn_create_sql.as_ref()),
+            ("all_cached", fn_all_cached.as_ref()),
+        ];
+        let generated_names = |fns: &[(&str, Option<&TokenStream>)]| {
+            fns.iter()
+                .filter(|(_, generated)| generated.is_some())
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let capabilities = generated_names(&base_fns)
+            .into_iter()
+            .chain(
+                self.analysis
+                    .joined_relations
+                    .iter()
+                    .zip(&fns_find_with)
+                    .map(|(relation, _)| format!("find_with_{}", relation.name)),
+            )
+            .chain(
+                self.analysis
+                    .distinct_countable_fields
+                    .iter()
+                    .zip(&fns_count_distinct)
+                    .filter_map(|(field, _)| field.ident.as_ref())
+                    .map(|ident| format!("count_distinct_{}", ident.unraw())),
+            )
+            .chain(generated_names(&optional_fns))
+            .chain(
+                fns_soft_delete
+                    .iter()
+                    .flat_map(|_| ["soft_destroy", "restore", "all_with_deleted"])
+                    .map(str::to_owned),
+            )
+            .collect::<Vec<_>>();
+        let const_capabilities = Self::generate_const_capabilities(&capabilities);
+        let const_table_columns = self.generate_const_table_columns();
+
+        let database = self.database_type();
+
+        let generated = quote! {
+            #[automatically_derived]
+            impl ::fabrique::Persistable for #base_struct_ident {
+                type Connection = sqlx::Pool<#database>;
+                type Error = sqlx::Error;
+
+                #fn_create
+                #fn_all
+                #fn_count
+            }
+
+            #[automatically_derived]
+            impl ::fabrique::Updatable for #base_struct_ident {
+                #fn_update
+            }
+
+            #[automatically_derived]
+            impl ::fabrique::Deletable for #base_struct_ident {
+                #fn_delete
+            }
+
+            #[automatically_derived]
+            impl #base_struct_ident {
+                #const_capabilities
+
+                #const_table_columns
+
+                #fn_create_tx
+
+                #fn_create_with_event
+
+                #fn_get_or_create
+
+                #fn_find_by_id
+
+                #(#fns_find_with)*
+
+                #(#fns_count_distinct)*
+
+                #fn_all_for_update
+
+                #fn_all_sorted_by
+
+                #fn_paginate
+
+                #fn_copy_in
+
+                #fn_create_batch
+
+                #fns_soft_delete
+
+                #fn_all_by_id
+
+                #fn_last_created
+
+                #fn_modified_since
+
+                #fn_reload_all
+
+                #fn_which_exist
+
+                #fn_create_sql
+
+                #fn_all_cached
+            }
+
+            #all_cache
+
+            #sort_column
+
+            #from_row
+
+            #impl_trackable
+        };
+
+        Ok(generated)
+    }
+
+    /// Generates the `fabrique::Trackable` implementation, naming the table and rendering the
+    /// primary key through `Display`, so factories can log the created records.
+    fn generate_impl_trackable(&self) -> Result<Option<TokenStream>, Error> {
+        if !self.analysis.trackable {
+            return Ok(None);
+        }
+
+        let ident = &self.analysis.ident;
+        let table_name = &self.analysis.table_name;
+        let primary_key = &self.primary_key()?.ident;
+
+        Ok(Some(quote! {
+            #[automatically_derived]
+            impl ::fabrique::Trackable for #ident {
+                const TABLE_NAME: &'static str = #table_name;
+
+                fn primary_key(&self) -> String {
+                    self.#primary_key.to_string()
+                }
+            }
+        }))
+    }
+
+    /// Generates the `sqlx` database type of the targeted backend (e.g. `sqlx::Postgres`).
+    fn database_type(&self) -> TokenStream {
+        match self.analysis.backend {
+            Backend::Postgres => quote! { sqlx::Postgres },
+            Backend::MySql => quote! { sqlx::MySql },
+            Backend::Sqlite => quote! { sqlx::Sqlite },
+        }
+    }
+
+    /// Generates the `sqlx` row type of the targeted backend (e.g. `sqlx::postgres::PgRow`).
+    fn row_type(&self) -> TokenStream {
+        match self.analysis.backend {
+            Backend::Postgres => quote! { sqlx::postgres::PgRow },
+            Backend::MySql => quote! { sqlx::mysql::MySqlRow },
+            Backend::Sqlite => quote! { sqlx::sqlite::SqliteRow },
+        }
+    }
+
+    /// Computes the sql column names of the model, i.e. of every field but the computed ones.
+    fn column_names(&self) -> Vec<String> {
+        self.stored_fields()
+            .map(|field| self.analysis.column_name(field))
+            .collect()
+    }
+
+    /// Returns the fields stored in a column, i.e. the persisted fields but the ones computed
+    /// by `#[fabrique(returning_expr)]`.
+    fn stored_fields(&self) -> impl Iterator<Item = &'a Field> {
+        self.analysis
+            .persisted_fields()
+            .filter(|field| !self.analysis.is_computed(field))
+    }
+
+    /// Computes the list of columns read into the model, by `SELECT` and `RETURNING` clauses.
+    ///
+    /// Renamed columns and computed expressions are aliased to their field name (e.g.
+    /// `forgedAt AS forged_at`), as `sqlx::query_as!` binds the columns to the fields of the
+    /// same name.
+    fn selected_columns(&self) -> String {
+        self.analysis
+            .persisted_fields()
+            .map(|field| {
+                let column = self.analysis.column_expr(field);
+                match field.ident.as_ref().map(|ident| ident.unraw().to_string()) {
+                    Some(name) if name != column => format!("{column} AS {name}"),
+                    _ => column,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Computes the `WHERE` condition applied to every generated `SELECT`, if any.
+    ///
+    /// It combines the default scope of the model with the exclusion of the soft deleted
+    /// records.
+    fn scope(&self) -> Option<String> {
+        let soft_delete = self
+            .analysis
+            .soft_delete
+            .map(|field| format!("{} IS NULL", self.analysis.column_name(field)));
+
+        match (&self.analysis.default_scope, soft_delete) {
+            (Some(scope), Some(soft_delete)) => Some(format!("({scope}) AND {soft_delete}")),
+            (scope, soft_delete) => scope.clone().or(soft_delete),
+        }
+    }
+
+    /// Computes the `SELECT` query fetching every column of the model.
+    ///
+    /// The scope of the model, if any, restricts the selected rows.
+    fn select_query(&self) -> String {
+        let query = format!(
+            "SELECT {} FROM {}",
+            self.selected_columns(),
+            self.analysis.table_name
+        );
+
+        match &self.scope() {
+            Some(scope) => format!("{query} WHERE {scope}"),
+            None => query,
+        }
+    }
+
+    /// Computes the `SELECT` query fetching the rows matching the given condition.
+    ///
+    /// The condition is combined with the default scope of the model, if any.
+    fn select_query_where(&self, condition: &str) -> String {
+        let query = format!(
+            "SELECT {} FROM {}",
+            self.selected_columns(),
+            self.analysis.table_name
+        );
+
+        match &self.scope() {
+            Some(scope) => format!("{query} WHERE ({scope}) AND {condition}"),
+            None => format!("{query} WHERE {condition}"),
+        }
+    }
+
+    /// Binds the parameter of the default scope, if the query is scoped by one.
+    ///
+    /// The named parameter (e.g. `$TENANT`) is numbered after the other parameters of the
+    /// query, and bound to the value returned by the `scope_param` function each time the
+    /// query runs.
+    fn bind_scope(&self, query: &str, params: &[TokenStream]) -> (String, Vec<TokenStream>) {
+        let mut params = params.to_vec();
+        let Some(scope_param) = &self.analysis.scope_param else {
+            return (query.to_owned(), params);
+        };
+        let name = format!("${}", scope_param.name);
+        if !query.contains(&name) {
+            return (query.to_owned(), params);
+        }
+
+        let provider = &scope_param.provider;
+        params.push(quote! { #provider() });
+        (
+            query.replace(&name, &self.placeholder(params.len())),
+            params,
+        )
+    }
+
+    /// Computes the query placeholder of the parameter at the given position (starting at 1).
+    fn placeholder(&self, index: usize) -> String {
+        match self.analysis.placeholder {
+            Placeholder::Numbered => format!("${index}"),
+            Placeholder::Positional => "?".to_string(),
+        }
+    }
+
+    /// Returns the fields inserted by the `INSERT` query, i.e. every stored field but the ones
+    /// generated by the database.
+    fn inserted_fields(&self) -> Vec<&'a Field> {
+        self.stored_fields()
+            .filter(|field| !self.analysis.generated_fields.contains(field))
+            .collect()
+    }
+
+    /// Computes the `INSERT` query persisting every column of the model.
+    ///
+    /// Placeholders follow the syntax of the backend (`$1` or `?`) and are numbered in column
+    /// order, so the statement text only depends on the model and every instance shares the
+    /// same prepared statement. Generated columns are left to the database and only read back.
+    /// MySQL has no `RETURNING` clause, so the inserted row is only returned by the other
+    /// backends.
+    fn insert_query(&self) -> String {
+        let query = self.insert_values_query();
+
+        match self.analysis.backend {
+            Backend::MySql => query,
+            Backend::Postgres | Backend::Sqlite => {
+                format!("{query} RETURNING {}", self.selected_columns())
+            }
+        }
+    }
+
+    /// Computes the `INSERT ... VALUES` statement of the `INSERT` query, without the clauses
+    /// following the inserted values.
+    ///
+    /// With `#[fabrique(timestamps)]`, the timestamp columns are set to `CURRENT_TIMESTAMP`
+    /// rather than bound, so the other placeholders are numbered without them.
+    fn insert_values_query(&self) -> String {
+        let inserted_fields = self.inserted_fields();
+        let inserted_columns = inserted_fields
+            .iter()
+            .map(|field| self.analysis.column_name(field))
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        let placeholders = inserted_fields
+            .iter()
+            .map(|field| match self.analysis.is_timestamp(field) {
+                true => CURRENT_TIMESTAMP.to_owned(),
+                false => {
+                    index += 1;
+                    self.placeholder(index)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match (inserted_columns.is_empty(), self.analysis.backend) {
+            (true, Backend::MySql) => {
+                format!("INSERT INTO {} () VALUES ()", self.analysis.table_name)
+            }
+            (true, Backend::Postgres | Backend::Sqlite) => {
+                format!("INSERT INTO {} DEFAULT VALUES", self.analysis.table_name)
+            }
+            (false, _) => format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                self.analysis.table_name,
+                inserted_columns.join(", "),
+                placeholders.join(", "),
+            ),
+        }
+    }
+
+    /// Computes the `INSERT` query of `get_or_create()`, returning the existing row when the
+    /// unique index of the model conflicts.
+    ///
+    /// `DO NOTHING` would return no row on conflict, so the first unique column is updated
+    /// to its own value instead.
+    fn get_or_create_query(&self) -> String {
+        let unique_columns = &self.analysis.unique_columns;
+        let first = &unique_columns[0];
+
+        format!(
+            "{} ON CONFLICT ({}) DO UPDATE SET {first} = EXCLUDED.{first} RETURNING {}",
+            self.insert_values_query(),
+            unique_columns.join(", "),
+            self.selected_columns()
+        )
+    }
+
+    /// Generates the parameters bound to the `INSERT` query, in column order.
+    fn generate_insert_params(&self) -> Vec<TokenStream> {
+        self.bound_fields()
+            .into_iter()
+            .map(|field| self.generate_param(field))
+            .collect()
+    }
+
+    /// Returns the fields bound to the `INSERT` query, i.e. the inserted fields but the
+    /// timestamps set by the database.
+    fn bound_fields(&self) -> Vec<&'a Field> {
+        self.inserted_fields()
+            .into_iter()
+            .filter(|field| !self.analysis.is_timestamp(field))
+            .collect()
+    }
+
+    /// Generates the parameter binding the given field to a query.
+    ///
+    /// Array columns are bound as slices, as expected by `sqlx::query_as!`.
+    fn generate_param(&self, field: &Field) -> TokenStream {
+        let ident = &field.ident;
+        match self.analysis.array_fields.contains(&field) {
+            true => quote! { self.#ident.as_slice() },
+            false => quote! { self.#ident },
+        }
+    }
+
+    /// Returns the primary key field of the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no primary key.
+    fn primary_key(&self) -> Result<&'a Field, Error> {
+        self.analysis
+            .primary_key
+            .ok_or_else(|| Error::MissingPrimaryKey(self.analysis.ident.to_string()))
+    }
+
+    /// Returns the fields set from the object by the `UPDATE` query, i.e. every field but the
+    /// primary key, the version column, the timestamps, the computed fields and the fields
+    /// generated by the database.
+    fn updated_fields(&self, primary_key: &Field) -> Vec<&'a Field> {
+        self.stored_fields()
+            .filter(|field| {
+                *field != primary_key
+                    && Some(*field) != self.analysis.version
+                    && !self.analysis.is_timestamp(field)
+                    && !self.analysis.generated_fields.contains(field)
+            })
+            .collect()
+    }
+
+    /// Computes the `UPDATE` query persisting every column of the model but the primary key.
+    ///
+    /// The primary key is bound after the updated columns, in the `WHERE` clause. With
+    /// `#[fabrique(version)]`, the version column is incremented and the previous version is
+    /// bound last, so a stale object matches no row. With `#[fabrique(timestamps)]`, the update
+    /// time is set to `CURRENT_TIMESTAMP` and the creation time is left untouched. A model made
+    /// of its primary key alone has nothing to update, so the key is set to itself to keep the
+    /// statement valid.
+    fn update_query(&self, primary_key: &Field) -> String {
+        let updated_fields = self.updated_fields(primary_key);
+        let mut assignments = updated_fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                format!(
+                    "{} = {}",
+                    self.analysis.column_name(field),
+                    self.placeholder(index + 1)
+                )
+            })
+            .collect::<Vec<_>>();
+        let primary_key_column = self.analysis.column_name(primary_key);
+        let mut conditions = vec![format!(
+            "{primary_key_column} = {}",
+            self.placeholder(updated_fields.len() + 1)
+        )];
+
+        if let Some(timestamps) = &self.analysis.timestamps {
+            let updated_at_column = self.analysis.column_name(timestamps.updated_at);
+            assignments.push(format!("{updated_at_column} = {CURRENT_TIMESTAMP}"));
+        }
+
+        if let Some(version) = self.analysis.version {
+            let version_column = self.analysis.column_name(version);
+            assignments.push(format!("{version_column} = {version_column} + 1"));
+            conditions.push(format!(
+                "{version_column} = {}",
+                self.placeholder(updated_fields.len() + 2)
+            ));
+        }
+
+        let assignments = match assignments.is_empty() {
+            true => format!("{primary_key_column} = {primary_key_column}"),
+            false => assignments.join(", "),
+        };
+
+        let query = format!(
+            "UPDATE {} SET {} WHERE {}",
+            self.analysis.table_name,
+            assignments,
+            conditions.join(" AND "),
+        );
+
+        match self.analysis.backend {
+            Backend::MySql => query,
+            Backend::Postgres | Backend::Sqlite => {
+                format!("{query} RETURNING {}", self.selected_columns())
+            }
+        }
+    }
+
+    /// Computes the identifier of the generated sort column enum (e.g. `AnvilSortColumn`).
+    fn sort_column_ident(&self) -> Ident {
+        format_ident!("{}SortColumn", self.analysis.ident)
+    }
+
+    /// Generates the `CAPABILITIES` constant listing the operations generated for the model.
+    ///
+    /// This lets tooling and tests check which optional methods the attributes enabled.
+    fn generate_const_capabilities(capabilities: &[String]) -> TokenStream {
+        quote! {
+            pub const CAPABILITIES: &'static [&'static str] = &[#(#capabilities),*];
+        }
+    }
+
+    /// Generates the `TABLE` and `COLUMNS` constants, naming the table and the columns of the
+    /// model for hand-written queries.
+    ///
+    /// The columns are the ones `INSERT` and `COPY` write, i.e. their renamed names, without
+    /// the skipped and computed fields.
+    fn generate_const_table_columns(&self) -> TokenStream {
+        let table_name = &self.analysis.table_name;
+        let column_names = self.column_names();
+
+        quote! {
+            pub const TABLE: &'static str = #table_name;
+
+            pub const COLUMNS: &'static [&'static str] = &[#(#column_names),*];
+        }
+    }
+
+    /// Generates the `all()` associated function.
+    fn generate_fn_all(&self) -> TokenStream {
+        let query = self
+            .analysis
+            .all_query
+            .clone()
+            .unwrap_or_else(|| self.select_query());
+        let query_as = self.generate_query_as(&query, &[]);
+
+        quote! {
+            async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                #query_as.fetch_all(connection).await
+            }
+        }
+    }
+
+    /// Generates the `paginate()` associated function, when `#[fabrique(paginate)]` is set on a
+    /// model with a primary key.
+    ///
+    /// Fetches the window of the rows starting at `offset`, at most `limit` of them. The rows
+    /// are ordered by primary key, so that the pages neither overlap nor skip rows.
+    fn generate_fn_paginate(&self) -> Option<TokenStream> {
+        if !self.analysis.paginate {
+            return None;
+        }
+
+        let primary_key = self.analysis.primary_key?;
+        let query = format!(
+            "{} ORDER BY {} LIMIT {} OFFSET {}",
+            self.select_query(),
+            self.analysis.column_name(primary_key),
+            self.placeholder(1),
+            self.placeholder(2)
+        );
+        let query_as = self.generate_query_as(&query, &[quote! { limit }, quote! { offset }]);
+
+        Some(quote! {
+            pub async fn paginate(limit: i64, offset: i64, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #query_as.fetch_all(connection).await
+            }
+        })
+    }
+
+    /// Generates the `count()` associated function.
+    ///
+    /// The rows are counted by the database, within the default scope of the model if any.
+    fn generate_fn_count(&self) -> TokenStream {
+        let query = format!(
+            "SELECT COUNT(*) AS \"count!\" FROM {}",
+            self.analysis.table_name
+        );
+        let query = match &self.scope() {
+            Some(scope) => format!("{query} WHERE {scope}"),
+            None => query,
+        };
+        let (query, params) = self.bind_scope(&query, &[]);
+
+        quote! {
+            async fn count(connection: &Self::Connection) -> Result<i64, Self::Error> {
+                sqlx::query_scalar!(#query #(, #params)*).fetch_one(connection).await
+            }
+        }
+    }
+
+    /// Generates a `count_distinct_<field>()` associated function for every field marked with
+    /// `#[fabrique(distinct_countable)]`.
+    ///
+    /// Like `count()`, the values are counted within the default scope of the model if any.
+    fn generate_fns_count_distinct(&self) -> Vec<TokenStream> {
+        self.analysis
+            .distinct_countable_fields
+            .iter()
+            .map(|field| {
+                let ident = field.ident.as_ref().expect("Named field");
+                let method_name = format_ident!("count_distinct_{}", ident.unraw());
+                let query = format!(
+                    "SELECT COUNT(DISTINCT {}) AS \"count!\" FROM {}",
+                    self.analysis.column_expr(field),
+                    self.analysis.table_name
+                );
+                let query = match &self.scope() {
+                    Some(scope) => format!("{query} WHERE {scope}"),
+                    None => query,
+                };
+                let (query, params) = self.bind_scope(&query, &[]);
+
+                quote! {
+                    pub async fn #method_name(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<i64, <Self as ::fabrique::Persistable>::Error> {
+                        sqlx::query_scalar!(#query #(, #params)*).fetch_one(connection).await
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the `all_by_id()` associated function, when `#[fabrique(all_by_id)]` is set on
+    /// a model with a primary key.
+    ///
+    /// Fetches every record through `all()` and indexes them by primary key.
+    fn generate_fn_all_by_id(&self) -> Option<TokenStream> {
+        if !self.analysis.all_by_id {
+            return None;
+        }
+
+        let primary_key = self.analysis.primary_key?;
+        let ident = &primary_key.ident;
+        let ty = &primary_key.ty;
+
+        Some(quote! {
+            pub async fn all_by_id(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<std::collections::HashMap<#ty, Self>, <Self as ::fabrique::Persistable>::Error> {
+                let items = <Self as ::fabrique::Persistable>::all(connection).await?;
+                Ok(items.into_iter().map(|item| (item.#ident.clone(), item)).collect())
+            }
+        })
+    }
+
+    /// Computes the identifier of the static holding the `all_cached()` results
+    /// (e.g. `ANVIL_ALL_CACHE`).
+    fn all_cache_ident(&self) -> Ident {
+        format_ident!(
+            "{}_ALL_CACHE",
+            self.analysis.ident.to_string().to_uppercase()
+        )
+    }
+
+    /// Generates the static holding the last `all()` results of every pool, when
+    /// `#[fabrique(cache_all = "...")]` is set.
+    fn generate_all_cache(&self) -> Option<TokenStream> {
+        self.analysis.cache_ttl?;
+        let ident = self.all_cache_ident();
+        let base_struct_ident = &self.analysis.ident;
+
+        Some(quote! {
+            static #ident: ::fabrique::cache::AllCache<#base_struct_ident> = ::fabrique::cache::AllCache::new();
+        })
+    }
+
+    /// Generates the `all_cached()` and `invalidate_all_cached()` associated functions, when
+    /// `#[fabrique(cache_all = "...")]` is set.
+    ///
+    /// The records are cached per pool, keyed by its connection URL: they are refetched
+    /// through `all()` once the time to live has elapsed, or after an explicit invalidation.
+    fn generate_fn_all_cached(&self) -> Option<TokenStream> {
+        let ttl = self.analysis.cache_ttl?;
+        let ttl = Literal::u128_unsuffixed(ttl.as_millis());
+        let ident = self.all_cache_ident();
+
+        Some(quote! {
+            pub async fn all_cached(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                let key = sqlx::ConnectOptions::to_url_lossy(&*connection.connect_options()).to_string();
+                #ident.get_or_fetch(key, std::time::Duration::from_millis(#ttl), <Self as ::fabrique::Persistable>::all(connection)).await
+            }
+
+            pub fn invalidate_all_cached() {
+                #ident.invalidate();
+            }
+        })
+    }
+
+    /// Generates the `modified_since()` associated function, when `#[fabrique(modified_since)]`
+    /// is set along with `#[fabrique(timestamps)]`.
+    ///
+    /// Fetches the records updated after the given timestamp, oldest change first, e.g. to sync
+    /// the changes made since the previous run.
+    fn generate_fn_modified_since(&self) -> Option<TokenStream> {
+        if !self.analysis.modified_since {
+            return None;
+        }
+
+        let updated_at = self.analysis.timestamps.as_ref()?.updated_at;
+        let ty = &updated_at.ty;
+        let column = self.analysis.column_name(updated_at);
+        let query = format!(
+            "{} ORDER BY {column}",
+            self.select_query_where(&format!("{column} > {}", self.placeholder(1)))
+        );
+        let query_as = self.generate_query_as(&query, &[quote! { since }]);
+
+        Some(quote! {
+            pub async fn modified_since(since: #ty, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #query_as.fetch_all(connection).await
+            }
+        })
+    }
+
+    /// Generates the `find_by_id()` associated function.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no primary key.
+    fn generate_fn_find_by_id(&self) -> Result<TokenStream, Error> {
+        let primary_key = self.primary_key()?;
+        let ty = &primary_key.ty;
+        let column = self.analysis.column_name(primary_key);
+        let query = self.select_query_where(&format!("{column} = {}", self.placeholder(1)));
+        let query_as = self.generate_query_as(&query, &[quote! { id }]);
+
+        Ok(quote! {
+            pub async fn find_by_id(id: #ty, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #query_as.fetch_optional(connection).await
+            }
+        })
+    }
+
+    /// Generates a `find_with_<relation>()` associated function for every joined relation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has joined relations but no primary key.
+    fn generate_fns_find_with(&self) -> Result<Vec<TokenStream>, Error> {
+        self.analysis
+            .joined_relations
+            .iter()
+            .map(|relation| self.generate_fn_find_with(relation))
+            .collect()
+    }
+
+    /// Generates the `find_with_<relation>()` associated function, fetching a record along
+    /// with its related record in a single join query.
+    ///
+    /// The columns of both tables are aliased with a `self_` or `related_` prefix so they do
+    /// not clash, and mapped back to the fields of the same name.
+    fn generate_fn_find_with(&self, relation: &JoinedRelation) -> Result<TokenStream, Error> {
+        let primary_key = self.primary_key()?;
+        let ty = &primary_key.ty;
+        let table = &self.analysis.table_name;
+
+        let self_columns = self.analysis.persisted_fields().map(|field| {
+            let name = field.ident.as_ref().expect("Named field").unraw();
+            match self.analysis.is_computed(field) {
+                true => format!("{} AS self_{name}", self.analysis.column_expr(field)),
+                false => format!(
+                    "{table}.{} AS self_{name}",
+                    self.analysis.column_name(field)
+                ),
+            }
+        });
+        let related_columns = relation.columns.iter().map(|related| {
+            format!(
+                "related.{} AS related_{}",
+                related.column,
+                related.field.unraw()
+            )
+        });
+        let condition = format!(
+            "{table}.{} = {}",
+            self.analysis.column_name(primary_key),
+            self.placeholder(1)
+        );
+        let query = format!(
+            "SELECT {} FROM {table} JOIN {} AS related ON related.{} = {table}.{} WHERE {}",
+            self_columns
+                .chain(related_columns)
+                .collect::<Vec<_>>()
+                .join(", "),
+            relation.table,
+            relation.referenced_key,
+            self.analysis.column_name(relation.field),
+            match &self.scope() {
+                Some(scope) => format!("({scope}) AND {condition}"),
+                None => condition,
+            }
+        );
+
+        let fields = self.analysis.fields.iter().map(|field| {
+            let ident = &field.ident;
+            match self.analysis.skipped_fields.contains(&field) {
+                true => quote! { #ident: Default::default() },
+                false => {
+                    let alias =
+                        format_ident!("self_{}", ident.as_ref().expect("Named field").unraw());
+                    quote! { #ident: row.#alias }
+                }
+            }
+        });
+        let related_fields = relation.columns.iter().map(|related| {
+            let ident = &related.field;
+            let alias = format_ident!("related_{}", related.field.unraw());
+            quote! { #ident: row.#alias }
+        });
+
+        let related_type = &relation.related_type;
+        let method_name = format_ident!("find_with_{}", relation.name);
+        let (query, params) = self.bind_scope(&query, &[quote! { id }]);
+
+        Ok(quote! {
+            pub async fn #method_name(id: #ty, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<(Self, #related_type)>, <Self as ::fabrique::Persistable>::Error> {
+                sqlx::query!(#query #(, #params)*)
+                    .map(|row| (Self { #(#fields,)* }, #related_type { #(#related_fields,)* }))
+                    .fetch_optional(connection)
+                    .await
+            }
+        })
+    }
+
+    /// Generates the `reload_all()` associated function, when `#[fabrique(reload_all)]` is set
+    /// on a model with a primary key targeting Postgres.
+    ///
+    /// Refreshes the given records in a single query, keeping their order. Records that no
+    /// longer exist are dropped from the result rather than reported as an error.
+    fn generate_fn_reload_all(&self) -> Option<TokenStream> {
+        if !self.analysis.reload_all || self.analysis.backend != Backend::Postgres {
+            return None;
+        }
+
+        let primary_key = self.analysis.primary_key?;
+        let ident = &primary_key.ident;
+        let column = self.analysis.column_name(primary_key);
+        let query = self.select_query_where(&format!("{column} = ANY({})", self.placeholder(1)));
+        let query_as = self.generate_query_as(&query, &[quote! { &keys[..] }]);
+
+        Some(quote! {
+            pub async fn reload_all(items: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                let keys = items.into_iter().map(|item| item.#ident).collect::<Vec<_>>();
+                let mut reloaded = #query_as
+                    .fetch_all(connection)
+                    .await?
+                    .into_iter()
+                    .map(|item| (item.#ident.clone(), item))
+                    .collect::<std::collections::HashMap<_, _>>();
+                Ok(keys.iter().filter_map(|key| reloaded.remove(key)).collect())
+            }
+        })
+    }
+
+    /// Generates the `which_exist()` associated function, when `#[fabrique(which_exist)]` is set
+    /// on a model with a primary key targeting Postgres.
+    ///
+    /// Returns the given keys that are already stored, e.g. to skip duplicates before a bulk
+    /// insert. The default scope is not applied, as scoped out rows still hold their key.
+    fn generate_fn_which_exist(&self) -> Option<TokenStream> {
+        if !self.analysis.which_exist || self.analysis.backend != Backend::Postgres {
+            return None;
+        }
+
+        let primary_key = self.analysis.primary_key?;
+        let ty = &primary_key.ty;
+        let column = self.analysis.column_name(primary_key);
+        let query = format!(
+            "SELECT {column} FROM {} WHERE {column} = ANY({})",
+            self.analysis.table_name,
+            self.placeholder(1)
+        );
+
+        Some(quote! {
+            pub async fn which_exist(ids: &[#ty], connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<#ty>, <Self as ::fabrique::Persistable>::Error> {
+                sqlx::query_scalar!(#query, ids).fetch_all(connection).await
+            }
+        })
+    }
+
+    /// Generates the `last_created()` associated function, when `#[fabrique(timestamps)]` is
+    /// set.
+    ///
+    /// The most recently created record is the one with the latest `created_at` timestamp. The
+    /// primary key is not a fallback, as most keys, e.g. UUIDs, do not increase with each insert.
+    fn generate_fn_last_created(&self) -> Option<TokenStream> {
+        let order_field = self.analysis.timestamps.as_ref()?.created_at;
+        let order_column = self.analysis.column_name(order_field);
+        let query = format!(
+            "{} ORDER BY {} DESC LIMIT 1",
+            self.select_query(),
+            order_column
+        );
+        let query_as = self.generate_query_as(&query, &[]);
+
+        Some(quote! {
+            pub async fn last_created(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #query_as.fetch_optional(connection).await
+            }
+        })
+    }
+
+    /// Generates the enum of the columns `all_sorted_by()` can order by, when fields are marked
+    /// with `#[fabrique(sortable)]`.
+    ///
+    /// Sorting on a variant of this enum rather than on a raw string prevents arbitrary SQL
+    /// from being injected into the `ORDER BY` clause.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two sortable fields map to the same variant.
+    fn generate_sort_column(&self) -> Result<Option<TokenStream>, Error> {
+        if self.analysis.sortable_fields.is_empty() {
+            return Ok(None);
+        }
+
+        let ident = self.sort_column_ident();
+        let (variants, column_names): (Vec<Ident>, Vec<String>) =
+            self.sort_columns()?.into_iter().unzip();
+
+        Ok(Some(quote! {
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            pub enum #ident {
+                #(#variants,)*
+            }
+
+            #[automatically_derived]
+            impl #ident {
+                pub fn as_sql(&self) -> &'static str {
+                    match self {
+                        #(Self::#variants => #column_names,)*
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Computes the variants of the sort column enum, named after the sortable fields, along
+    /// with the column each of them orders by.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two sortable fields map to the same variant (e.g. `hammer_id` and
+    /// `hammer__id`).
+    fn sort_columns(&self) -> Result<Vec<(Ident, String)>, Error> {
+        let mut columns: Vec<(Ident, String, &Ident)> = Vec::new();
+
+        for field in &self.analysis.sortable_fields {
+            let Some(field_ident) = field.ident.as_ref() else {
+                continue;
+            };
+            let variant = format_ident!("{}", to_pascal_case(&field_ident.unraw().to_string()));
+            if let Some((_, _, first)) = columns.iter().find(|(other, _, _)| *other == variant) {
+                return Err(Error::ConflictingSortColumns {
+                    first: first.to_string(),
+                    second: field_ident.to_string(),
+                    variant: variant.to_string(),
+                    span: field_ident.span(),
+                });
+            }
+            columns.push((variant, self.analysis.column_expr(field), field_ident));
+        }
+
+        Ok(columns
+            .into_iter()
+            .map(|(variant, column, _)| (variant, column))
+            .collect())
+    }
+
+    /// Generates the `all_sorted_by()` associated function, when fields are marked with
+    /// `#[fabrique(sortable)]`.
+    ///
+    /// Each column and direction pair maps to its own checked query, so the `ORDER BY` clause
+    /// never contains user input. Only the marked fields are sortable, as some column types
+    /// (e.g. `json`) have no ordering and would fail the query checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two sortable fields map to the same variant.
+    fn generate_fn_all_sorted_by(&self) -> Result<Option<TokenStream>, Error> {
+        if self.analysis.sortable_fields.is_empty() {
+            return Ok(None);
+        }
+
+        let ident = self.sort_column_ident();
+        let select_query = self.select_query();
+        let mut arms = Vec::new();
+        for (variant, column_name) in self.sort_columns()? {
+            for direction in [format_ident!("Asc"), format_ident!("Desc")] {
+                let query = format!(
+                    "{} ORDER BY {} {}",
+                    select_query,
+                    column_name,
+                    direction.to_string().to_uppercase()
+                );
+                let query_as = self.generate_query_as(&query, &[]);
+
+                arms.push(quote! {
+                    (#ident::#variant, ::fabrique::SortDir::#direction) => {
+                        #query_as.fetch_all(connection).await
+                    }
+                });
+            }
+        }
+
+        Ok(Some(quote! {
+            pub async fn all_sorted_by(column: #ident, direction: ::fabrique::SortDir, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                match (column, direction) {
+                    #(#arms)*
+                }
+            }
+        }))
+    }
+
+    /// Generates the `create_tx()` method.
+    ///
+    /// Runs the `INSERT` within a transaction opened by the caller, who stays in charge of
+    /// committing or rolling it back.
+    fn generate_fn_create_tx(&self) -> TokenStream {
+        let database = self.database_type();
+        let insert = self.generate_insert(quote! { &mut **tx });
+        let not_null_checks = self.generate_not_null_checks();
+
+        quote! {
+            pub async fn create_tx(self, tx: &mut sqlx::Transaction<'_, #database>) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                #(#not_null_checks)*
+                #insert
+            }
+        }
+    }
+
+    /// Generates the `all_for_update()` associated function, when `#[fabrique(all_for_update)]`
+    /// is set and the backend supports `SKIP LOCKED` (i.e. not SQLite).
+    ///
+    /// Locks the fetched rows and skips the ones already locked, so concurrent workers each
+    /// claim distinct rows. The locks are held until the end of the transaction opened by the
+    /// caller.
+    fn generate_fn_all_for_update(&self) -> Option<TokenStream> {
+        if !self.analysis.all_for_update || self.analysis.backend == Backend::Sqlite {
+            return None;
+        }
+
+        let database = self.database_type();
+        let query = format!("{} FOR UPDATE SKIP LOCKED", self.select_query());
+        let query_as = self.generate_query_as(&query, &[]);
+
+        Some(quote! {
+            pub async fn all_for_update(tx: &mut sqlx::Transaction<'_, #database>) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #query_as.fetch_all(&mut **tx).await
+            }
+        })
+    }
+
+    /// Generates the `create_with_event()` method, when `#[fabrique(emit_event)]` is set.
+    ///
+    /// The event is built from the created record through the `From<&Model>` implementation
+    /// the event type must provide.
+    fn generate_fn_create_with_event(&self) -> Option<TokenStream> {
+        let event = self.analysis.event.as_ref()?;
+
+        Some(quote! {
+            pub async fn create_with_event(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<(Self, #event), <Self as ::fabrique::Persistable>::Error> {
+                let created = <Self as ::fabrique::Persistable>::create(self, connection).await?;
+                let event = <#event as From<&Self>>::from(&created);
+                Ok((created, event))
+            }
+        })
+    }
+
+    /// Generates the `get_or_create()` method, when `#[fabrique(unique = [...])]` is set and
+    /// the backend supports `ON CONFLICT` (i.e. not MySQL).
+    ///
+    /// Inserts the record, or returns the stored one holding the same unique values, in a
+    /// single round-trip.
+    fn generate_fn_get_or_create(&self) -> Option<TokenStream> {
+        if self.analysis.unique_columns.is_empty() || self.analysis.backend == Backend::MySql {
+            return None;
+        }
+
+        let query = self.get_or_create_query();
+        let params = self.generate_insert_params();
+        let query_as = self.generate_query_as(&query, &params);
+        let not_null_checks = self.generate_not_null_checks();
+
+        Some(quote! {
+            pub async fn get_or_create(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                #(#not_null_checks)*
+                #query_as.fetch_one(connection).await
+            }
+        })
+    }
+
+    /// Generates the checks rejecting empty non-nullable fields before they are inserted,
+    /// when `#[fabrique(validate_not_null)]` is set.
+    ///
+    /// The error names the field, unlike the generic error of the database constraint.
+    fn generate_not_null_checks(&self) -> Vec<TokenStream> {
+        let table = &self.analysis.table_name;
+
+        self.analysis
+            .not_null_fields
+            .iter()
+            .filter_map(|field| Some((field.ident.as_ref()?, self.analysis.column_name(field))))
+            .map(|(ident, column)| {
+                quote! {
+                    if self.#ident.is_none() {
+                        return Err(sqlx::Error::Database(Box::new(::fabrique::NotNullViolation { table: #table, column: #column })));
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the `copy_in()` associated function, when `#[fabrique(copy_in)]` is set and the
+    /// model targets Postgres.
+    ///
+    /// Bulk loads the given items through Postgres `COPY ... FROM STDIN`, which is far faster
+    /// than inserting them one by one. The rows are sent in the binary format, each field
+    /// encoded through its `sqlx::Encode` implementation as for a bound parameter, so the
+    /// field types must match the column types exactly (e.g. `i32` for an `INTEGER`).
+    fn generate_fn_copy_in(&self) -> Option<TokenStream> {
+        if !self.analysis.copy_in || self.analysis.backend != Backend::Postgres {
+            return None;
+        }
+
+        let statement = format!(
+            "COPY {} ({}) FROM STDIN WITH (FORMAT binary)",
+            self.analysis.table_name,
+            self.column_names().join(", ")
+        );
+        let field_count = Literal::i16_suffixed(self.stored_fields().count() as i16);
+        let idents = self.stored_fields().map(|field| &field.ident);
+
+        Some(quote! {
+            pub async fn copy_in(items: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<u64, <Self as ::fabrique::Persistable>::Error> {
+                fn encode<T: for<'q> sqlx::Encode<'q, sqlx::Postgres>>(value: &T, data: &mut Vec<u8>) -> Result<(), sqlx::Error> {
+                    let mut buffer = sqlx::postgres::PgArgumentBuffer::default();
+                    match sqlx::Encode::<sqlx::Postgres>::encode_by_ref(value, &mut buffer).map_err(sqlx::Error::Encode)? {
+                        sqlx::encode::IsNull::Yes => data.extend_from_slice(&(-1i32).to_be_bytes()),
+                        sqlx::encode::IsNull::No => {
+                            data.extend_from_slice(&(buffer.len() as i32).to_be_bytes());
+                            data.extend_from_slice(&buffer);
+                        }
+                    }
+                    Ok(())
+                }
+
+                // Signature, then empty flags and header extension
+                let mut data = b"PGCOPY\n\xff\r\n\0".to_vec();
+                data.extend_from_slice(&[0; 8]);
+                for item in &items {
+                    data.extend_from_slice(&#field_count.to_be_bytes());
+                    #(encode(&item.#idents, &mut data)?;)*
+                }
+                data.extend_from_slice(&(-1i16).to_be_bytes());
+
+                let mut copy = sqlx::postgres::PgPoolCopyExt::copy_in_raw(connection, #statement).await?;
+                copy.send(data).await?;
+                copy.finish().await
+            }
+        })
+    }
+
+    /// Generates the `create_batch()` associated function, when `#[fabrique(batch_insert)]` is
+    /// set and the backend supports `RETURNING`.
+    ///
+    /// The records are inserted through multi-row `INSERT` statements built at runtime, each
+    /// binding at most `#[fabrique(batch_max_params)]` parameters, so large seeds take a few
+    /// round-trips instead of one per record. The created records are read back from the
+    /// returned rows by column name.
+    fn generate_fn_create_batch(&self) -> Option<TokenStream> {
+        let max_params = self.analysis.batch_max_params?;
+        if self.analysis.backend == Backend::MySql {
+            return None;
+        }
+
+        let inserted_fields = self.inserted_fields();
+        let rows_per_statement = (max_params / self.bound_fields().len().max(1)).max(1);
+        let database = self.database_type();
+        let insert = format!(
+            "INSERT INTO {} ({}) ",
+            self.analysis.table_name,
+            inserted_fields
+                .iter()
+                .map(|field| self.analysis.column_name(field))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let returning = format!(" RETURNING {}", self.selected_columns());
+        let binds = inserted_fields.iter().map(|field| {
+            let ident = &field.ident;
+            match self.analysis.is_timestamp(field) {
+                true => quote! { values.push(#CURRENT_TIMESTAMP); },
+                false => quote! { values.push_bind(row.#ident); },
+            }
+        });
+        let fields = self.analysis.fields.iter().map(|field| {
+            let ident = &field.ident;
+            match self.analysis.skipped_fields.contains(&field) {
+                true => quote! { #ident: Default::default() },
+                false => {
+                    let name = ident.as_ref().map(|ident| ident.unraw().to_string());
+                    quote! { #ident: sqlx::Row::try_get(&row, #name)? }
+                }
+            }
+        });
+
+        Some(quote! {
+            pub async fn create_batch(rows: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                let mut created = Vec::with_capacity(rows.len());
+                let mut rows = rows.into_iter().peekable();
+                while rows.peek().is_some() {
+                    let mut builder = sqlx::QueryBuilder::<#database>::new(#insert);
+                    builder.push_values(rows.by_ref().take(#rows_per_statement), |mut values, row| {
+                        #(#binds)*
+                    });
+                    builder.push(#returning);
+
+                    for row in builder.build().fetch_all(connection).await? {
+                        created.push(Self {
+                            #(#fields,)*
+                        });
+                    }
+                }
+
+                Ok(created)
+            }
+        })
+    }
+
+    /// Generates the `create_sql()` method, when `#[fabrique(dry_run_api)]` is set.
+    ///
+    /// Returns the `INSERT` query run by `create()` along with its parameters, in field order
+    /// and formatted through `Debug`, without touching the database.
+    fn generate_fn_create_sql(&self) -> Option<TokenStream> {
+        if !self.analysis.dry_run_api {
+            return None;
+        }
+
+        let query = self.insert_query();
+        let bound_fields = self.bound_fields();
+        let fields = bound_fields.iter().filter_map(|field| field.ident.as_ref());
+
+        Some(quote! {
+            pub fn create_sql(&self) -> (String, Vec<String>) {
+                (
+                    #query.to_string(),
+                    vec![#(format!("{:?}", self.#fields),)*],
+                )
+            }
+        })
+    }
+
+    /// Generates an explicit `sqlx::FromRow` implementation, when `#[fabrique(from_row)]` is set.
+    ///
+    /// Each field is read from the column of the same name, so the struct can be used with
+    /// hand-written runtime queries such as `sqlx::query_as::<_, Anvil>(...)`. Skipped fields
+    /// get their default value.
+    fn generate_from_row(&self) -> Option<TokenStream> {
+        if !self.analysis.from_row {
+            return None;
+        }
+
+        let ident = &self.analysis.ident;
+        let row = self.row_type();
+        let fields = self.analysis.fields.iter().map(|field| {
+            let name = &field.ident;
+            match self.analysis.skipped_fields.contains(&field) {
+                true => quote! { #name: Default::default() },
+                false => {
+                    let column_name = self.analysis.column_name(field);
+                    quote! { #name: sqlx::Row::try_get(row, #column_name)? }
+                }
+            }
+        });
+
+        Some(quote! {
+            #[automatically_derived]
+            impl<'r> sqlx::FromRow<'r, #row> for #ident {
+                fn from_row(row: &'r #row) -> Result<Self, sqlx::Error> {
+                    Ok(Self {
+                        #(#fields,)*
+                    })
+                }
+            }
+        })
+    }
+
+    /// Generates the `create()` method.
+    ///
+    /// The object is rebuilt from the row returned by the `INSERT`, so columns computed by the
+    /// database (defaults, triggers) are reflected in the created object. MySQL cannot return
+    /// the inserted row, so the object is returned as given there.
+    fn generate_fn_create(&self) -> TokenStream {
+        let insert = self.generate_insert(quote! { connection });
+        let not_null_checks = self.generate_not_null_checks();
+
+        quote! {
+            async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                #(#not_null_checks)*
+                #insert
+            }
+        }
+    }
+
+    /// Generates the `update()` method of the `Persistable` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no primary key.
+    fn generate_fn_update(&self) -> Result<TokenStream, Error> {
+        let primary_key = self.primary_key()?;
+        let query = self.update_query(primary_key);
+        let params = self
+            .updated_fields(primary_key)
+            .into_iter()
+            .chain(std::iter::once(primary_key))
+            .chain(self.analysis.version)
+            .map(|field| self.generate_param(field))
+            .collect::<Vec<_>>();
+        let query_as = self.generate_query_as(&query, &params);
+        let not_null_checks = self.generate_not_null_checks();
+        let table = &self.analysis.table_name;
+        let stale_write = quote! {
+            sqlx::Error::Database(Box::new(::fabrique::StaleWrite { table: #table }))
+        };
+
+        let update = match (self.analysis.backend, self.analysis.version) {
+            (Backend::MySql, None) => quote! {
+                sqlx::query!(#query, #(#params),*).execute(connection).await?;
+                Ok(self)
+            },
+            (Backend::MySql, Some(version)) => {
+                let version = &version.ident;
+                quote! {
+                    let result = sqlx::query!(#query, #(#params),*).execute(connection).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#stale_write);
+                    }
+                    let mut updated = self;
+                    updated.#version += 1;
+                    Ok(updated)
+                }
+            }
+            (Backend::Postgres | Backend::Sqlite, None) => quote! {
+                #query_as.fetch_one(connection).await
+            },
+            (Backend::Postgres | Backend::Sqlite, Some(_)) => quote! {
+                #query_as
+                    .fetch_optional(connection)
+                    .await?
+                    .ok_or_else(|| #stale_write)
+            },
+        };
+
+        Ok(quote! {
+            async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                #(#not_null_checks)*
+                #update
+            }
+        })
+    }
+
+    /// Generates the `delete()` method of the `Persistable` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no primary key.
+    fn generate_fn_delete(&self) -> Result<TokenStream, Error> {
+        let primary_key = self.primary_key()?;
+        let column = self.analysis.column_name(primary_key);
+        let query = format!(
+            "DELETE FROM {} WHERE {column} = {}",
+            self.analysis.table_name,
+            self.placeholder(1),
+        );
+        let param = self.generate_param(primary_key);
+
+        Ok(quote! {
+            async fn delete(self, connection: &Self::Connection) -> Result<(), Self::Error> {
+                sqlx::query!(#query, #param).execute(connection).await?;
+                Ok(())
+            }
+        })
+    }
+
+    /// Generates the `soft_destroy()` and `restore()` methods and the `all_with_deleted()`
+    /// associated function, when `#[fabrique(soft_delete)]` is set.
+    ///
+    /// Soft deleted records are kept in the table with their deletion time set, and left out
+    /// of every generated `SELECT` but the one of `all_with_deleted()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no primary key.
+    fn generate_fns_soft_delete(&self) -> Result<Option<TokenStream>, Error> {
+        let Some(soft_delete) = self.analysis.soft_delete else {
+            return Ok(None);
+        };
+
+        let primary_key = self.primary_key()?;
+        let param = self.generate_param(primary_key);
+        let soft_delete_ident = &soft_delete.ident;
+        let soft_delete_column = self.analysis.column_name(soft_delete);
+        let update_query = |value: &str| {
+            format!(
+                "UPDATE {} SET {soft_delete_column} = {value} WHERE {} = {}",
+                self.analysis.table_name,
+                self.analysis.column_name(primary_key),
+                self.placeholder(1),
+            )
+        };
+        let soft_destroy_query = update_query(CURRENT_TIMESTAMP);
+        let restore_query = update_query("NULL");
+
+        let all_query = format!(
+            "SELECT {} FROM {}",
+            self.selected_columns(),
+            self.analysis.table_name
+        );
+        let all_query = match &self.analysis.default_scope {
+            Some(scope) => format!("{all_query} WHERE {scope}"),
+            None => all_query,
+        };
+        let all_query_as = self.generate_query_as(&all_query, &[]);
+
+        Ok(Some(quote! {
+            pub async fn soft_destroy(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<(), <Self as ::fabrique::Persistable>::Error> {
+                sqlx::query!(#soft_destroy_query, #param).execute(connection).await?;
+                Ok(())
+            }
+
+            pub async fn restore(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                sqlx::query!(#restore_query, #param).execute(connection).await?;
+                let mut restored = self;
+                restored.#soft_delete_ident = None;
+                Ok(restored)
+            }
+
+            pub async fn all_with_deleted(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                #all_query_as.fetch_all(connection).await
+            }
+        }))
+    }
+
+    /// Computes the `SELECT` query reading back the row inserted by MySQL, which has no
+    /// `RETURNING` clause.
+    ///
+    /// A generated primary key is matched against `LAST_INSERT_ID()`, any other against its
+    /// bound value. The default scope is ignored, the inserted row being read back as is.
+    fn mysql_reselect_query(&self, primary_key: &Field) -> String {
+        let column = self.analysis.column_name(primary_key);
+        let key = match self.analysis.generated_fields.contains(&primary_key) {
+            true => "LAST_INSERT_ID()".to_string(),
+            false => self.placeholder(1),
+        };
+
+        format!(
+            "SELECT {} FROM {} WHERE {column} = {key}",
+            self.selected_columns(),
+            self.analysis.table_name
+        )
+    }
+
+    /// Generates the execution of the `INSERT` query on the given executor.
+    ///
+    /// On MySQL, models with generated, computed or timestamp columns read the inserted row
+    /// back with a second query, an extra round-trip the other backends avoid through
+    /// `RETURNING`. Both queries run on the same connection, as `LAST_INSERT_ID()` is tracked
+    /// per connection. The errors of the database are wrapped into a `StatementError` naming
+    /// the table and the statement of the failed insert.
+    fn generate_insert(&self, executor: TokenStream) -> TokenStream {
+        let query = self.insert_query();
+        let params = self.generate_insert_params();
+        let table = &self.analysis.table_name;
+        let param_count = Literal::usize_unsuffixed(params.len());
+        let context = quote! {
+            |error| ::fabrique::StatementError::wrap(error, #table, "INSERT", #query, #param_count)
+        };
+
+        match (self.analysis.backend, self.analysis.primary_key) {
+            (Backend::MySql, Some(primary_key))
+                if !self.analysis.generated_fields.is_empty()
+                    || !self.analysis.computed_fields.is_empty()
+                    || self.analysis.timestamps.is_some() =>
+            {
+                let reselect_query = self.mysql_reselect_query(primary_key);
+                let reselect_params = match self.analysis.generated_fields.contains(&primary_key) {
+                    true => vec![],
+                    false => vec![self.generate_param(primary_key)],
+                };
+                let reselect = self.generate_query_as(&reselect_query, &reselect_params);
+                quote! {
+                    let mut executor = sqlx::Acquire::acquire(#executor).await?;
+                    sqlx::query!(#query, #(#params),*).execute(&mut *executor).await.map_err(#context)?;
+                    #reselect.fetch_one(&mut *executor).await
+                }
+            }
+            (Backend::MySql, _) => quote! {
+                sqlx::query!(#query, #(#params),*).execute(#executor).await.map_err(#context)?;
+                Ok(self)
+            },
+            (Backend::Postgres | Backend::Sqlite, _) => {
+                let query_as = self.generate_query_as(&query, &params);
+                quote! {
+                    #query_as.fetch_one(#executor).await.map_err(#context)
+                }
+            }
+        }
+    }
+
+    /// Generates the query reading rows into the model.
+    ///
+    /// `sqlx::query_as!` expects a column for every field, so models with skipped fields go
+    /// through `sqlx::query!` instead and fill the skipped fields with their default value.
+    fn generate_query_as(&self, query: &str, params: &[TokenStream]) -> TokenStream {
+        let (query, params) = self.bind_scope(query, params);
+        if self.analysis.skipped_fields.is_empty() {
+            return quote! { sqlx::query_as!(Self, #query #(, #params)*) };
+        }
+
+        let fields = self.analysis.fields.iter().map(|field| {
+            let ident = &field.ident;
+            match self.analysis.skipped_fields.contains(&field) {
+                true => quote! { #ident: Default::default() },
+                false => quote! { #ident: row.#ident },
+            }
+        });
+
+        quote! {
+            sqlx::query!(#query #(, #params)*).map(|row| Self { #(#fields,)* })
+        }
+    }
+}
+
+/// Converts a snake case column name into a pascal case identifier (e.g. `hammer_id` into
+/// `HammerId`).
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut characters = part.chars();
+            characters
+                .next()
+                .map(|first| first.to_uppercase().chain(characters).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_generate() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: String } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let result = codegen.generate();
+
+        // Assert the result
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                #[automatically_derived]
+                impl ::fabrique::Persistable for Anvil {
+                    type Connection = sqlx::Pool<sqlx::Postgres>;
+                    type Error = sqlx::Error;
+
+                    async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                        sqlx::query_as!(Self, "INSERT INTO anvils (id) VALUES ($1) RETURNING id", self.id).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id) VALUES ($1) RETURNING id", 1))
+                    }
+
+                    async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                        sqlx::query_as!(Self, "SELECT id FROM anvils").fetch_all(connection).await
+                    }
+
+                    async fn count(connection: &Self::Connection) -> Result<i64, Self::Error> {
+                        sqlx::query_scalar!("SELECT COUNT(*) AS \"count!\" FROM anvils").fetch_one(connection).await
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::fabrique::Updatable for Anvil {
+                    async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                        sqlx::query_as!(Self, "UPDATE anvils SET id = id WHERE id = $1 RETURNING id", self.id).fetch_one(connection).await
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::fabrique::Deletable for Anvil {
+                    async fn delete(self, connection: &Self::Connection) -> Result<(), Self::Error> {
+                        sqlx::query!("DELETE FROM anvils WHERE id = $1", self.id).execute(connection).await?;
+                        Ok(())
+                    }
+                }
+
+                #[automatically_derived]
+                impl Anvil {
+                    pub const CAPABILITIES: &'static [&'static str] = &[
+                        "create", "all", "update", "delete", "count", "create_tx", "find_by_id"
+                    ];
+
+                    pub const TABLE: &'static str = "anvils";
+
+                    pub const COLUMNS: &'static [&'static str] = &["id"];
+
+                    pub async fn create_tx(self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                        sqlx::query_as!(Self, "INSERT INTO anvils (id) VALUES ($1) RETURNING id", self.id).fetch_one(&mut **tx).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id) VALUES ($1) RETURNING id", 1))
+                    }
+
+                    pub async fn find_by_id(id: String, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                        sqlx::query_as!(Self, "SELECT id FROM anvils WHERE id = $1", id).fetch_optional(connection).await
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_impl_trackable() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(table = "forged_anvils", trackable)]
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial: Uuid,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_impl_trackable method
+        let result = codegen.generate_impl_trackable();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().unwrap().to_string(),
+            quote! {
+                #[automatically_derived]
+                impl ::fabrique::Trackable for Anvil {
+                    const TABLE_NAME: &'static str = "forged_anvils";
+
+                    fn primary_key(&self) -> String {
+                        self.serial.to_string()
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_impl_trackable_requires_trackable() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                id: Vec<u8>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_impl_trackable method
+        let result = codegen.generate_impl_trackable();
+
+        // Assert the result
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: String } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let result = codegen.generate_fn_all();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id FROM anvils").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_with_default_scope() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false")]
+            struct Anvil {
+                #[fabrique(sortable)]
+                id: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all and generate_fn_all_sorted_by methods
+        let all = codegen.generate_fn_all();
+        let all_sorted_by = codegen.generate_fn_all_sorted_by().unwrap().unwrap();
+
+        // Assert the scope restricts both queries
+        assert_eq!(
+            all.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id FROM anvils WHERE discontinued = false").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert!(
+            all_sorted_by
+                .to_string()
+                .contains("\"SELECT id FROM anvils WHERE discontinued = false ORDER BY id ASC\"")
+        );
+    }
+
+    #[test]
+    fn test_generate_with_parameterized_default_scope() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "tenant_id = $TENANT", scope_param = "current_tenant")]
+            struct Anvil {
+                id: Uuid,
+                tenant_id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all, generate_fn_count and generate_fn_find_by_id
+        // methods
+        let all = codegen.generate_fn_all();
+        let count = codegen.generate_fn_count();
+        let find_by_id = codegen.generate_fn_find_by_id().unwrap();
+
+        // Assert the scope parameter is numbered after the others and bound on each call
+        assert_eq!(
+            all.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id, tenant_id FROM anvils WHERE tenant_id = $1", current_tenant()).fetch_all(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            count.to_string(),
+            quote! {
+                async fn count(connection: &Self::Connection) -> Result<i64, Self::Error> {
+                    sqlx::query_scalar!("SELECT COUNT(*) AS \"count!\" FROM anvils WHERE tenant_id = $1", current_tenant()).fetch_one(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            find_by_id.to_string(),
+            quote! {
+                pub async fn find_by_id(id: Uuid, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT id, tenant_id FROM anvils WHERE (tenant_id = $2) AND id = $1", id, current_tenant()).fetch_optional(connection).await
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_all_with_custom_query() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(all_query = "SELECT id FROM anvils ORDER BY id")]
+            struct Anvil { id: String }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let result = codegen.generate_fn_all();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id FROM anvils ORDER BY id").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_sort_column() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(sortable)]
+                id: String,
+                #[fabrique(sortable)]
+                hammer_id: String,
+                specs: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_sort_column method
+        let result = codegen.generate_sort_column();
+
+        // Assert only the sortable fields are listed
+        assert_eq!(
+            result.unwrap().unwrap().to_string(),
+            quote! {
+                #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+                pub enum AnvilSortColumn {
+                    Id,
+                    HammerId,
+                }
+
+                #[automatically_derived]
+                impl AnvilSortColumn {
+                    pub fn as_sql(&self) -> &'static str {
+                        match self {
+                            Self::Id => "id",
+                            Self::HammerId => "hammer_id",
+                        }
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_sorted_by() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(sortable)]
+                id: String,
+                #[fabrique(sortable)]
+                weight: u32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_sorted_by method
+        let result = codegen.generate_fn_all_sorted_by();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().unwrap().to_string(),
+            quote! {
+                pub async fn all_sorted_by(column: AnvilSortColumn, direction: ::fabrique::SortDir, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    match (column, direction) {
+                        (AnvilSortColumn::Id, ::fabrique::SortDir::Asc) => {
+                            sqlx::query_as!(Self, "SELECT id, weight FROM anvils ORDER BY id ASC").fetch_all(connection).await
+                        }
+                        (AnvilSortColumn::Id, ::fabrique::SortDir::Desc) => {
+                            sqlx::query_as!(Self, "SELECT id, weight FROM anvils ORDER BY id DESC").fetch_all(connection).await
+                        }
+                        (AnvilSortColumn::Weight, ::fabrique::SortDir::Asc) => {
+                            sqlx::query_as!(Self, "SELECT id, weight FROM anvils ORDER BY weight ASC").fetch_all(connection).await
+                        }
+                        (AnvilSortColumn::Weight, ::fabrique::SortDir::Desc) => {
+                            sqlx::query_as!(Self, "SELECT id, weight FROM anvils ORDER BY weight DESC").fetch_all(connection).await
+                        }
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_sorted_by_requires_sortable_fields() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: String, weight: u32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_sorted_by and generate_sort_column methods
+        let all_sorted_by = codegen.generate_fn_all_sorted_by();
+        let sort_column = codegen.generate_sort_column();
+
+        // Assert neither the method nor the enum is generated
+        assert!(all_sorted_by.unwrap().is_none());
+        assert!(sort_column.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_sorted_by_fails_explicitly_on_conflicting_variants() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(sortable)]
+                hammer_id: String,
+                #[fabrique(sortable)]
+                hammer__id: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_sorted_by method
+        let result = codegen.generate_fn_all_sorted_by();
+
+        // Assert both fields are named in the error
+        assert!(matches!(
+            result,
+            Err(Error::ConflictingSortColumns { first, second, variant, .. })
+                if first == "hammer_id" && second == "hammer__id" && variant == "HammerId"
+        ));
+    }
+
+    #[test]
+    fn test_generate_fn_create_tx() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid, weight: i32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_tx method
+        let result = codegen.generate_fn_create_tx();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                pub async fn create_tx(self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", self.id, self.weight).fetch_one(&mut **tx).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", 2))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_copy_in() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(copy_in)]
+            struct Anvil { id: Uuid, weight: i32 }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_copy_in method
+        let result = codegen.generate_fn_copy_in();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn copy_in(items: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<u64, <Self as ::fabrique::Persistable>::Error> {
+                    fn encode<T: for<'q> sqlx::Encode<'q, sqlx::Postgres>>(value: &T, data: &mut Vec<u8>) -> Result<(), sqlx::Error> {
+                        let mut buffer = sqlx::postgres::PgArgumentBuffer::default();
+                        match sqlx::Encode::<sqlx::Postgres>::encode_by_ref(value, &mut buffer).map_err(sqlx::Error::Encode)? {
+                            sqlx::encode::IsNull::Yes => data.extend_from_slice(&(-1i32).to_be_bytes()),
+                            sqlx::encode::IsNull::No => {
+                                data.extend_from_slice(&(buffer.len() as i32).to_be_bytes());
+                                data.extend_from_slice(&buffer);
+                            }
+                        }
+                        Ok(())
+                    }
+
+                    let mut data = b"PGCOPY\n\xff\r\n\0".to_vec();
+                    data.extend_from_slice(&[0; 8]);
+                    for item in &items {
+                        data.extend_from_slice(&2i16.to_be_bytes());
+                        encode(&item.id, &mut data)?;
+                        encode(&item.weight, &mut data)?;
+                    }
+                    data.extend_from_slice(&(-1i16).to_be_bytes());
+
+                    let mut copy = sqlx::postgres::PgPoolCopyExt::copy_in_raw(connection, "COPY anvils (id, weight) FROM STDIN WITH (FORMAT binary)").await?;
+                    copy.send(data).await?;
+                    copy.finish().await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_copy_in_requires_the_attribute() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid, weight: i32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_copy_in method
+        let result = codegen.generate_fn_copy_in();
+
+        // Assert no method is generated
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_by_id() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(all_by_id)]
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: Uuid,
+                weight: u32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_by_id method
+        let result = codegen.generate_fn_all_by_id();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn all_by_id(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<std::collections::HashMap<Uuid, Self>, <Self as ::fabrique::Persistable>::Error> {
+                    let items = <Self as ::fabrique::Persistable>::all(connection).await?;
+                    Ok(items.into_iter().map(|item| (item.serial_number.clone(), item)).collect())
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_last_created() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(timestamps)]
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: i64,
+                created_at: String,
+                updated_at: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_last_created method
+        let result = codegen.generate_fn_last_created();
+
+        // Assert the records are ordered by creation time rather than primary key
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn last_created(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT serial_number, created_at, updated_at FROM anvils ORDER BY created_at DESC LIMIT 1").fetch_optional(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_last_created_requires_timestamps() {
+        // Arrange the codegen, with a primary key but without timestamps
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_last_created method
+        let result = codegen.generate_fn_last_created();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_last_created_with_timestamps() {
+        // Arrange the codegen, without primary key
+        let input = parse_quote! {
+            #[fabrique(timestamps, created_at = "forged_at")]
+            struct Anvil {
+                weight: i32,
+                forged_at: String,
+                updated_at: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_last_created method
+        let result = codegen.generate_fn_last_created();
+
+        // Assert the records are ordered by creation time
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn last_created(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT weight, forged_at, updated_at FROM anvils ORDER BY forged_at DESC LIMIT 1").fetch_optional(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_modified_since() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(timestamps, updated_at = "reworked_at", modified_since)]
+            struct Anvil {
+                id: Uuid,
+                created_at: String,
+                reworked_at: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_modified_since method
+        let result = codegen.generate_fn_modified_since();
+
+        // Assert the records are filtered and ordered on the update time
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn modified_since(since: String, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT id, created_at, reworked_at FROM anvils WHERE reworked_at > $1 ORDER BY reworked_at", since).fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_modified_since_requires_modified_since() {
+        // Arrange the codegen, with timestamps only
+        let input = parse_quote! {
+            #[fabrique(timestamps)]
+            struct Anvil {
+                id: Uuid,
+                created_at: String,
+                updated_at: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_modified_since method
+        let result = codegen.generate_fn_modified_since();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_find_by_id() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_find_by_id method
+        let result = codegen.generate_fn_find_by_id();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn find_by_id(id: i64, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT serial_number, weight FROM anvils WHERE serial_number = $1", id).fetch_optional(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_find_by_id_with_overridden_placeholder() {
+        // Arrange the codegens, overriding the placeholder style of their backend
+        let numbered = parse_quote! {
+            #[fabrique(backend = "mysql", placeholder = "$")]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let positional = parse_quote! {
+            #[fabrique(placeholder = "?")]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let numbered = PersistableCodegen::from(&numbered).unwrap();
+        let positional = PersistableCodegen::from(&positional).unwrap();
+
+        // Act the calls to the generate_fn_find_by_id method
+        let numbered = numbered.generate_fn_find_by_id();
+        let positional = positional.generate_fn_find_by_id();
+
+        // Assert the overridden placeholders are used
+        assert!(
+            numbered
+                .unwrap()
+                .to_string()
+                .contains("\"SELECT id FROM anvils WHERE id = $1\"")
+        );
+        assert!(
+            positional
+                .unwrap()
+                .to_string()
+                .contains("\"SELECT id FROM anvils WHERE id = ?\"")
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_find_by_id_fails_explicitly_without_primary_key() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { weight: i32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_find_by_id method
+        let result = codegen.generate_fn_find_by_id();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::MissingPrimaryKey(model) if model == "Anvil"
+        ));
+    }
+
+    #[test]
+    fn test_generate_fns_find_with() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Stand {
+                id: Uuid,
+                #[fabrique(
+                    relation = "Hammer",
+                    referenced_key = "id",
+                    related_table = "hammers",
+                    related_columns = "id, name"
+                )]
+                hammer_id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fns_find_with method
+        let result = codegen.generate_fns_find_with();
+
+        // Assert the result
+        let result = result.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].to_string(),
+            quote! {
+                pub async fn find_with_hammer(id: Uuid, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<(Self, Hammer)>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query!("SELECT stands.id AS self_id, stands.hammer_id AS self_hammer_id, related.id AS related_id, related.name AS related_name FROM stands JOIN hammers AS related ON related.id = stands.hammer_id WHERE stands.id = $1", id)
+                        .map(|row| (Self { id: row.self_id, hammer_id: row.self_hammer_id, }, Hammer { id: row.related_id, name: row.related_name, }))
+                        .fetch_optional(connection)
+                        .await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fns_find_with_mapped_columns() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Stand {
+                id: Uuid,
+                #[fabrique(
+                    relation = "Hammer",
+                    referenced_key = "id",
+                    related_table = "hammers",
+                    related_columns = "id, forged_on AS made_at, type"
+                )]
+                hammer_id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fns_find_with method
+        let result = codegen.generate_fns_find_with();
+
+        // Assert the result
+        let result = result.unwrap();
+        assert_eq!(
+            result[0].to_string(),
+            quote! {
+                pub async fn find_with_hammer(id: Uuid, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Option<(Self, Hammer)>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query!("SELECT stands.id AS self_id, stands.hammer_id AS self_hammer_id, related.id AS related_id, related.forged_on AS related_made_at, related.type AS related_type FROM stands JOIN hammers AS related ON related.id = stands.hammer_id WHERE stands.id = $1", id)
+                        .map(|row| (Self { id: row.self_id, hammer_id: row.self_hammer_id, }, Hammer { id: row.related_id, made_at: row.related_made_at, r#type: row.related_type, }))
+                        .fetch_optional(connection)
+                        .await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_get_or_create() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(unique = ["name"])]
+            struct Workshop {
+                id: Uuid,
+                name: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_get_or_create method
+        let result = codegen.generate_fn_get_or_create();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn get_or_create(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO workshops (id, name) VALUES ($1, $2) ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id, name", self.id, self.name).fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_get_or_create_requires_unique_columns() {
+        // Arrange the codegens
+        let without_unique = parse_quote! { struct Workshop { id: Uuid } };
+        let on_mysql = parse_quote! {
+            #[fabrique(unique = ["name"], backend = "mysql")]
+            struct Workshop {
+                id: u64,
+                name: String,
+            }
+        };
+        let without_unique = PersistableCodegen::from(&without_unique).unwrap();
+        let on_mysql = PersistableCodegen::from(&on_mysql).unwrap();
+
+        // Act the calls to the generate_fn_get_or_create method
+        let without_unique = without_unique.generate_fn_get_or_create();
+        let on_mysql = on_mysql.generate_fn_get_or_create();
+
+        // Assert the results
+        assert!(without_unique.is_none());
+        assert!(on_mysql.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_event() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(emit_event = "AnvilForged")]
+            struct Anvil {
+                id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_with_event method
+        let result = codegen.generate_fn_create_with_event();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn create_with_event(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<(Self, AnvilForged), <Self as ::fabrique::Persistable>::Error> {
+                    let created = <Self as ::fabrique::Persistable>::create(self, connection).await?;
+                    let event = <AnvilForged as From<&Self>>::from(&created);
+                    Ok((created, event))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_event_requires_the_attribute() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_with_event method
+        let result = codegen.generate_fn_create_with_event();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_paginate() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(paginate, default_scope = "discontinued = false")]
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: Uuid,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_paginate method
+        let result = codegen.generate_fn_paginate();
+
+        // Assert the rows are scoped, then ordered by primary key
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn paginate(limit: i64, offset: i64, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT serial_number, weight FROM anvils WHERE discontinued = false ORDER BY serial_number LIMIT $1 OFFSET $2", limit, offset).fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_paginate_with_mysql_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql", paginate)]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_paginate method
+        let result = codegen.generate_fn_paginate();
+
+        // Assert the result
+        assert!(
+            result
+                .unwrap()
+                .to_string()
+                .contains("\"SELECT id FROM anvils ORDER BY id LIMIT ? OFFSET ?\"")
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_paginate_requires_paginate() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_paginate method
+        let result = codegen.generate_fn_paginate();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_paginate_requires_a_primary_key() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(paginate)]
+            struct Anvil {
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_paginate method
+        let result = codegen.generate_fn_paginate();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_count() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(table = "forged_anvils", default_scope = "discontinued = false")]
+            struct Anvil {
+                id: Uuid,
+                discontinued: bool,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_count method
+        let result = codegen.generate_fn_count();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn count(connection: &Self::Connection) -> Result<i64, Self::Error> {
+                    sqlx::query_scalar!("SELECT COUNT(*) AS \"count!\" FROM forged_anvils WHERE discontinued = false").fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_create_batch() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(batch_insert, batch_max_params = 1000)]
+            struct Anvil {
+                #[fabrique(primary_key, generated)]
+                id: i64,
+                #[fabrique(column = "mass")]
+                weight: i32,
+                material: String,
+                #[fabrique(skip)]
+                dirty: bool,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_batch method
+        let result = codegen.generate_fn_create_batch();
+
+        // Assert each statement binds at most 1000 parameters, i.e. 500 rows of 2 columns
+        assert_eq!(
+            quote! { #result }.to_string(),
+            quote! {
+                pub async fn create_batch(rows: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    let mut created = Vec::with_capacity(rows.len());
+                    let mut rows = rows.into_iter().peekable();
+                    while rows.peek().is_some() {
+                        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new("INSERT INTO anvils (mass, material) ");
+                        builder.push_values(rows.by_ref().take(500usize), |mut values, row| {
+                            values.push_bind(row.weight);
+                            values.push_bind(row.material);
+                        });
+                        builder.push(" RETURNING id, mass AS weight, material");
+
+                        for row in builder.build().fetch_all(connection).await? {
+                            created.push(Self {
+                                id: sqlx::Row::try_get(&row, "id")?,
+                                weight: sqlx::Row::try_get(&row, "weight")?,
+                                material: sqlx::Row::try_get(&row, "material")?,
+                                dirty: Default::default(),
+                            });
+                        }
+                    }
+
+                    Ok(created)
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_create_batch_with_mysql_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(batch_insert, backend = "mysql")]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_batch method
+        let result = codegen.generate_fn_create_batch();
+
+        // Assert nothing is generated, as MySQL cannot return the inserted rows
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fns_count_distinct() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false")]
+            struct Anvil {
+                id: Uuid,
+                #[fabrique(distinct_countable, column = "alloy")]
+                material: String,
+                discontinued: bool,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fns_count_distinct method
+        let result = codegen.generate_fns_count_distinct();
+
+        // Assert the result
+        assert_eq!(
+            quote! { #(#result)* }.to_string(),
+            quote! {
+                pub async fn count_distinct_material(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<i64, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_scalar!("SELECT COUNT(DISTINCT alloy) AS \"count!\" FROM anvils WHERE discontinued = false").fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_for_update() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false", all_for_update)]
+            struct Anvil {
+                id: Uuid,
+                discontinued: bool,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_for_update method
+        let result = codegen.generate_fn_all_for_update();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn all_for_update(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT id, discontinued FROM anvils WHERE discontinued = false FOR UPDATE SKIP LOCKED").fetch_all(&mut **tx).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_for_update_requires_skip_locked() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "sqlite", all_for_update)]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_for_update method
+        let result = codegen.generate_fn_all_for_update();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_for_update_requires_all_for_update() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_for_update method
+        let result = codegen.generate_fn_all_for_update();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_reload_all() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false", reload_all)]
+            struct Anvil {
+                id: Uuid,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_reload_all method
+        let result = codegen.generate_fn_reload_all();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn reload_all(items: Vec<Self>, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    let keys = items.into_iter().map(|item| item.id).collect::<Vec<_>>();
+                    let mut reloaded = sqlx::query_as!(Self, "SELECT id, weight FROM anvils WHERE (discontinued = false) AND id = ANY($1)", &keys[..])
+                        .fetch_all(connection)
+                        .await?
+                        .into_iter()
+                        .map(|item| (item.id.clone(), item))
+                        .collect::<std::collections::HashMap<_, _>>();
+                    Ok(keys.iter().filter_map(|key| reloaded.remove(key)).collect())
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_which_exist() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(default_scope = "discontinued = false", which_exist)]
+            struct Anvil {
+                id: Uuid,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_which_exist method
+        let result = codegen.generate_fn_which_exist();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn which_exist(ids: &[Uuid], connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Uuid>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_scalar!("SELECT id FROM anvils WHERE id = ANY($1)", ids).fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_which_exist_requires_postgres() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "sqlite", which_exist)]
+            struct Anvil {
+                id: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_which_exist method
+        let result = codegen.generate_fn_which_exist();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_which_exist_requires_which_exist() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_which_exist method
+        let result = codegen.generate_fn_which_exist();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_reload_all_requires_a_primary_key() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(reload_all)]
+            struct Anvil {
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_reload_all method
+        let result = codegen.generate_fn_reload_all();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_reload_all_requires_reload_all() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_reload_all method
+        let result = codegen.generate_fn_reload_all();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_by_id_requires_a_primary_key() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(all_by_id)]
+            struct Anvil {
+                weight: u32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_by_id method
+        let result = codegen.generate_fn_all_by_id();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_by_id_requires_all_by_id() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: f64 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_by_id method
+        let result = codegen.generate_fn_all_by_id();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_all_cached() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(cache_all = "5s")]
+            struct Anvil {
+                id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all_cached method
+        let result = codegen.generate_fn_all_cached();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub async fn all_cached(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    let key = sqlx::ConnectOptions::to_url_lossy(&*connection.connect_options()).to_string();
+                    ANVIL_ALL_CACHE.get_or_fetch(key, std::time::Duration::from_millis(5000), <Self as ::fabrique::Persistable>::all(connection)).await
+                }
+
+                pub fn invalidate_all_cached() {
+                    ANVIL_ALL_CACHE.invalidate();
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_all_cache() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(cache_all = "5s")]
+            struct Anvil {
+                id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_all_cache method
+        let result = codegen.generate_all_cache();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                static ANVIL_ALL_CACHE: ::fabrique::cache::AllCache<Anvil> = ::fabrique::cache::AllCache::new();
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_cached_requires_the_attribute() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_all_cache and generate_fn_all_cached methods
+        let cache = codegen.generate_all_cache();
+        let result = codegen.generate_fn_all_cached();
+
+        // Assert nothing is generated
+        assert!(cache.is_none());
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_create_sql() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(dry_run_api)]
+            struct Anvil {
+                id: Uuid,
+                weight: u32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_sql method
+        let result = codegen.generate_fn_create_sql();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                pub fn create_sql(&self) -> (String, Vec<String>) {
+                    (
+                        "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight".to_string(),
+                        vec![format!("{:?}", self.id), format!("{:?}", self.weight),],
+                    )
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_insert_query_numbers_placeholders_in_column_order() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                id: Uuid,
+                weight: u32,
+                r#type: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the insert_query method
+        let result = codegen.insert_query();
+
+        // Assert the result
+        assert_eq!(
+            result,
+            "INSERT INTO anvils (id, weight, type) VALUES ($1, $2, $3) RETURNING id, weight, type"
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_create_binds_optional_fields_as_is() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                id: i64,
+                brand: Option<String>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
+
+        // Assert the optional field is bound without being unwrapped, so None binds NULL
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, brand) VALUES ($1, $2) RETURNING id, brand", self.id, self.brand).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, brand) VALUES ($1, $2) RETURNING id, brand", 2))
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_create_omits_generated_columns() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key, generated)]
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create and find_by_id methods
+        let result = codegen.generate_fn_create();
+        let find_by_id = codegen.generate_fn_find_by_id();
+
+        // Assert the generated column is only read back
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (weight) VALUES ($1) RETURNING id, weight", self.weight).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (weight) VALUES ($1) RETURNING id, weight", 1))
+                }
+            }
+            .to_string()
+        );
+        assert!(
+            find_by_id
+                .unwrap()
+                .to_string()
+                .contains("SELECT id, weight FROM anvils WHERE id = $1")
+        );
+    }
+
+    #[test]
+    fn test_generate_with_renamed_column() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                id: i64,
+                #[fabrique(column = "forgedAt")]
+                forged_at: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all and insert_query methods
+        let result = codegen.generate_fn_all();
+        let insert_query = codegen.insert_query();
+
+        // Assert the column is aliased to the field name
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id, forgedAt AS forged_at FROM anvils").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            insert_query,
+            "INSERT INTO anvils (id, forgedAt) VALUES ($1, $2) RETURNING id, forgedAt AS forged_at"
+        );
+    }
+
+    #[test]
+    fn test_generate_with_skipped_field() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Hammer {
+                id: Uuid,
+                #[fabrique(skip)]
+                display_name: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_all and insert_query methods
+        let result = codegen.generate_fn_all();
+        let insert_query = codegen.insert_query();
+
+        // Assert the skipped field has no column and gets its default value
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query!("SELECT id FROM hammers").map(|row| Self { id: row.id, display_name: Default::default(), }).fetch_all(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            insert_query,
+            "INSERT INTO hammers (id) VALUES ($1) RETURNING id"
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_generated_columns_only() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                #[fabrique(primary_key, generated)]
+                id: Uuid,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the insert_query method
+        let result = codegen.insert_query();
+
+        // Assert the database fills every column
+        assert_eq!(result, "INSERT INTO anvils DEFAULT VALUES RETURNING id");
+    }
+
+    #[test]
+    fn test_generate_fn_create_sql_requires_the_attribute() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create_sql method
+        let result = codegen.generate_fn_create_sql();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_from_row() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(from_row)]
+            struct Anvil {
+                id: Uuid,
+                r#type: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_from_row method
+        let result = codegen.generate_from_row();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                #[automatically_derived]
+                impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Anvil {
+                    fn from_row(row: &'r sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+                        Ok(Self {
+                            id: sqlx::Row::try_get(row, "id")?,
+                            r#type: sqlx::Row::try_get(row, "type")?,
+                        })
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_from_row_requires_the_attribute() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_from_row method
+        let result = codegen.generate_from_row();
+
+        // Assert the result
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_generate_fn_create() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid, weight: i32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let result = codegen.generate_fn_create();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", self.id, self.weight).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", 2))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_validate_not_null() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(validate_not_null)]
+            struct Hammer {
+                id: Uuid,
+                #[fabrique(nullable = false)]
+                name: Option<String>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    if self.name.is_none() {
+                        return Err(sqlx::Error::Database(Box::new(::fabrique::NotNullViolation { table: "hammers", column: "name" })));
+                    }
+                    sqlx::query_as!(Self, "INSERT INTO hammers (id, name) VALUES ($1, $2) RETURNING id, name", self.id, self.name).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "hammers", "INSERT", "INSERT INTO hammers (id, name) VALUES ($1, $2) RETURNING id, name", 2))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_copy_in_with_an_array_column() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(copy_in)]
+            struct Mold {
+                id: Uuid,
+                #[fabrique(array)]
+                dimensions: Vec<i32>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_copy_in and generate_fn_create methods
+        let copy_in = codegen.generate_fn_copy_in();
+        let create = codegen.generate_fn_create();
+
+        // Assert the array is bound as a whole and encoded as a whole
+        assert!(
+            copy_in
+                .unwrap()
+                .to_string()
+                .contains(&quote! { encode(&item.dimensions, &mut data)?; }.to_string())
+        );
+        assert_eq!(
+            create.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO molds (id, dimensions) VALUES ($1, $2) RETURNING id, dimensions", self.id, self.dimensions.as_slice()).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "molds", "INSERT", "INSERT INTO molds (id, dimensions) VALUES ($1, $2) RETURNING id, dimensions", 2))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_update() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                weight: i32,
+                #[fabrique(primary_key)]
+                serial_number: i64,
+                r#type: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "UPDATE anvils SET weight = $1, type = $2 WHERE serial_number = $3 RETURNING weight, serial_number, type", self.weight, self.r#type, self.serial_number).fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_with_returning_expr() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Smith {
+                id: i64,
+                first_name: String,
+                last_name: String,
+                #[fabrique(returning_expr = "first_name || ' ' || last_name")]
+                full_name: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the calls to the create, all and update generators
+        let create = codegen.generate_fn_create();
+        let all = codegen.generate_fn_all();
+        let update = codegen.generate_fn_update();
+
+        // Assert the expression is read back aliased to the field, and never written
+        assert_eq!(
+            quote! { #create #all }.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO smiths (id, first_name, last_name) VALUES ($1, $2, $3) RETURNING id, first_name, last_name, first_name || ' ' || last_name AS full_name", self.id, self.first_name, self.last_name).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "smiths", "INSERT", "INSERT INTO smiths (id, first_name, last_name) VALUES ($1, $2, $3) RETURNING id, first_name, last_name, first_name || ' ' || last_name AS full_name", 3))
+                }
+
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id, first_name, last_name, first_name || ' ' || last_name AS full_name FROM smiths").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        );
+        assert!(update.unwrap().to_string().contains(
+            "\"UPDATE smiths SET first_name = $1, last_name = $2 WHERE id = $3 RETURNING id, first_name, last_name, first_name || ' ' || last_name AS full_name\""
+        ));
+    }
+
+    #[test]
+    fn test_generate_fns_soft_delete() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(soft_delete = "deleted_at", default_scope = "discontinued = false")]
+            struct Anvil {
+                id: i64,
+                discontinued: bool,
+                deleted_at: Option<DateTime<Utc>>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fns_soft_delete method
+        let result = codegen.generate_fns_soft_delete();
+
+        // Assert all_with_deleted() keeps the default scope alone
+        assert_eq!(
+            result.unwrap().unwrap().to_string(),
+            quote! {
+                pub async fn soft_destroy(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<(), <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query!("UPDATE anvils SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1", self.id).execute(connection).await?;
+                    Ok(())
+                }
+
+                pub async fn restore(self, connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Self, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query!("UPDATE anvils SET deleted_at = NULL WHERE id = $1", self.id).execute(connection).await?;
+                    let mut restored = self;
+                    restored.deleted_at = None;
+                    Ok(restored)
+                }
+
+                pub async fn all_with_deleted(connection: &<Self as ::fabrique::Persistable>::Connection) -> Result<Vec<Self>, <Self as ::fabrique::Persistable>::Error> {
+                    sqlx::query_as!(Self, "SELECT id, discontinued, deleted_at FROM anvils WHERE discontinued = false").fetch_all(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_all_with_soft_delete() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(soft_delete = "deleted_at")]
+            struct Anvil {
+                id: i64,
+                deleted_at: Option<DateTime<Utc>>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the calls to the all and count generators
+        let all = codegen.generate_fn_all();
+        let count = codegen.generate_fn_count();
+
+        // Assert the soft deleted records are left out
+        assert_eq!(
+            quote! { #all #count }.to_string(),
+            quote! {
+                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                    sqlx::query_as!(Self, "SELECT id, deleted_at FROM anvils WHERE deleted_at IS NULL").fetch_all(connection).await
+                }
+
+                async fn count(connection: &Self::Connection) -> Result<i64, Self::Error> {
+                    sqlx::query_scalar!("SELECT COUNT(*) AS \"count!\" FROM anvils WHERE deleted_at IS NULL").fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_find_by_id_with_soft_delete_and_default_scope() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(soft_delete = "deleted_at", default_scope = "discontinued = false")]
+            struct Anvil {
+                id: i64,
+                discontinued: bool,
+                deleted_at: Option<DateTime<Utc>>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_find_by_id method
+        let result = codegen.generate_fn_find_by_id().unwrap().to_string();
+
+        // Assert both conditions restrict the selected rows
+        assert!(result.contains(
+            "\"SELECT id, discontinued, deleted_at FROM anvils WHERE ((discontinued = false) AND deleted_at IS NULL) AND id = $1\""
+        ));
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_timestamps() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(timestamps)]
+            struct Anvil {
+                id: i64,
+                created_at: DateTime<Utc>,
+                weight: i32,
+                updated_at: DateTime<Utc>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
+
+        // Assert the timestamps are set by the database instead of bound
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, created_at, weight, updated_at) VALUES ($1, CURRENT_TIMESTAMP, $2, CURRENT_TIMESTAMP) RETURNING id, created_at, weight, updated_at", self.id, self.weight).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, created_at, weight, updated_at) VALUES ($1, CURRENT_TIMESTAMP, $2, CURRENT_TIMESTAMP) RETURNING id, created_at, weight, updated_at", 2))
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_update_with_timestamps() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(timestamps, created_at = "forged_at", updated_at = "reworked_at")]
+            struct Anvil {
+                id: i64,
+                forged_at: DateTime<Utc>,
+                weight: i32,
+                reworked_at: DateTime<Utc>,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
 
-        Ok(Self { analysis })
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
+
+        // Assert the update time is bumped and the creation time left untouched
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "UPDATE anvils SET weight = $1, reworked_at = CURRENT_TIMESTAMP WHERE id = $2 RETURNING id, forged_at, weight, reworked_at", self.weight, self.id).fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
     }
 
-    pub fn generate(self) -> Result<TokenStream, Error> {
-        let base_struct_ident = &self.analysis.ident;
-        let fn_all = self.generate_fn_all();
-        let fn_create = self.generate_fn_create();
+    #[test]
+    fn test_generate_fn_update_with_generated_fields() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            struct Anvil {
+                id: i64,
+                weight: i32,
+                #[fabrique(generated)]
+                serial_number: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
 
-        let generated = quote! {
-            impl ::fabrique::Persistable for #base_struct_ident {
-                type Connection = sqlx::Pool<sqlx::Postgres>;
-                type Error = sqlx::Error;
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
 
-                #fn_create
-                #fn_all
+        // Assert the generated column is only read back
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "UPDATE anvils SET weight = $1 WHERE id = $2 RETURNING id, weight, serial_number", self.weight, self.id).fetch_one(connection).await
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_timestamps_and_mysql_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(timestamps, backend = "mysql")]
+            struct Anvil {
+                id: i64,
+                created_at: DateTime<Utc>,
+                updated_at: DateTime<Utc>,
             }
         };
+        let codegen = PersistableCodegen::from(&input).unwrap();
 
-        Ok(generated)
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create().to_string();
+
+        // Assert the inserted row is read back to get the timestamps
+        assert!(result.contains(
+            "\"INSERT INTO anvils (id, created_at, updated_at) VALUES (?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)\""
+        ));
+        assert!(result.contains("\"SELECT id, created_at, updated_at FROM anvils WHERE id = ?\""));
     }
 
-    /// Generates the `all()` associated function.
-    fn generate_fn_all(&self) -> TokenStream {
-        // Compute the sql column names for the query
-        let column_names = self
-            .analysis
-            .fields
-            .iter()
-            .filter_map(|field| field.ident.as_ref())
-            .map(|ident| ident.to_string())
-            .collect::<Vec<String>>()
-            .join(", ");
+    #[test]
+    fn test_generate_fn_update_with_mysql_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql")]
+            struct Anvil {
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
 
-        let query = format!("SELECT {} FROM {}", column_names, self.analysis.table_name);
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
 
-        quote! {
-            async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
-                sqlx::query_as!(Self, #query).fetch_all(connection).await
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query!("UPDATE anvils SET weight = ? WHERE id = ?", self.weight, self.id).execute(connection).await?;
+                    Ok(self)
+                }
             }
-        }
+            .to_string()
+        )
     }
 
-    /// Generates the `create()` method.
-    fn generate_fn_create(&self) -> TokenStream {
-        quote! {
-            async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
-                todo!()
+    #[test]
+    fn test_generate_fn_update_with_version() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(version = "lock_version")]
+            struct Anvil {
+                id: i64,
+                lock_version: i32,
+                weight: i32,
             }
-        }
-    }
-}
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use syn::parse_quote;
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "UPDATE anvils SET weight = $1, lock_version = lock_version + 1 WHERE id = $2 AND lock_version = $3 RETURNING id, lock_version, weight", self.weight, self.id, self.lock_version)
+                        .fetch_optional(connection)
+                        .await?
+                        .ok_or_else(|| sqlx::Error::Database(Box::new(::fabrique::StaleWrite { table: "anvils" })))
+                }
+            }
+            .to_string()
+        )
+    }
 
     #[test]
-    fn test_generate() {
+    fn test_generate_fn_update_with_version_and_mysql_backend() {
         // Arrange the codegen
-        let input = parse_quote! { struct Anvil { id: String } };
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql", version = "lock_version")]
+            struct Anvil {
+                id: i64,
+                lock_version: i32,
+            }
+        };
         let codegen = PersistableCodegen::from(&input).unwrap();
 
-        // Act the call to the generate method
-        let result = codegen.generate();
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
 
         // Assert the result
-        assert!(result.is_ok());
         assert_eq!(
             result.unwrap().to_string(),
             quote! {
-                impl ::fabrique::Persistable for Anvil {
-                    type Connection = sqlx::Pool<sqlx::Postgres>;
-                    type Error = sqlx::Error;
-
-                    async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
-                        todo!()
+                async fn update(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    let result = sqlx::query!("UPDATE anvils SET lock_version = lock_version + 1 WHERE id = ? AND lock_version = ?", self.id, self.lock_version).execute(connection).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(sqlx::Error::Database(Box::new(::fabrique::StaleWrite { table: "anvils" })));
                     }
+                    let mut updated = self;
+                    updated.lock_version += 1;
+                    Ok(updated)
+                }
+            }
+            .to_string()
+        )
+    }
 
-                    async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
-                        sqlx::query_as!(Self, "SELECT id FROM anvils").fetch_all(connection).await
-                    }
+    #[test]
+    fn test_generate_fn_update_fails_explicitly_without_primary_key() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { weight: i32 } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_update method
+        let result = codegen.generate_fn_update();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::MissingPrimaryKey(model) if model == "Anvil"
+        ));
+    }
+
+    #[test]
+    fn test_generate_fn_delete() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(table = "forged_anvils")]
+            struct Anvil {
+                #[fabrique(primary_key)]
+                serial_number: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_delete method
+        let result = codegen.generate_fn_delete();
+
+        // Assert the result
+        assert_eq!(
+            result.unwrap().to_string(),
+            quote! {
+                async fn delete(self, connection: &Self::Connection) -> Result<(), Self::Error> {
+                    sqlx::query!("DELETE FROM forged_anvils WHERE serial_number = $1", self.serial_number).execute(connection).await?;
+                    Ok(())
                 }
             }
             .to_string()
@@ -103,45 +3683,290 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_fn_all() {
+    fn test_generate_fn_delete_fails_explicitly_without_primary_key() {
         // Arrange the codegen
-        let input = parse_quote! { struct Anvil { id: String } };
+        let input = parse_quote! { struct Anvil { weight: i32 } };
         let codegen = PersistableCodegen::from(&input).unwrap();
 
-        // Act the call to the generate method
-        let result = codegen.generate_fn_all();
+        // Act the call to the generate_fn_delete method
+        let result = codegen.generate_fn_delete();
+
+        // Assert the result
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::MissingPrimaryKey(model) if model == "Anvil"
+        ));
+    }
+
+    #[test]
+    fn test_generate_with_postgres_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "postgres", copy_in)]
+            struct Anvil {
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
 
         // Assert the result
         assert_eq!(
             result.to_string(),
             quote! {
-                async fn all(connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
-                    sqlx::query_as!(Self, "SELECT id FROM anvils").fetch_all(connection).await
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", self.id, self.weight).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, weight) VALUES ($1, $2) RETURNING id, weight", 2))
+                }
+            }
+            .to_string()
+        );
+        assert!(codegen.generate_fn_copy_in().is_some());
+    }
+
+    #[test]
+    fn test_generate_with_mysql_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql", copy_in, reload_all)]
+            struct Anvil {
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create and generate methods
+        let result = codegen.generate_fn_create();
+        let generated = codegen.generate().unwrap().to_string();
+
+        // Assert the result
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    sqlx::query!("INSERT INTO anvils (id, weight) VALUES (?, ?)", self.id, self.weight).execute(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, weight) VALUES (?, ?)", 2))?;
+                    Ok(self)
+                }
+            }
+            .to_string()
+        );
+        assert!(
+            generated.contains(&quote! { type Connection = sqlx::Pool<sqlx::MySql>; }.to_string())
+        );
+        assert!(!generated.contains("copy_in"));
+        assert!(!generated.contains("reload_all"));
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_mysql_backend_reads_back_generated_keys() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql")]
+            struct Anvil {
+                #[fabrique(primary_key, generated)]
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
+
+        // Assert the inserted row is selected back through LAST_INSERT_ID()
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    let mut executor = sqlx::Acquire::acquire(connection).await?;
+                    sqlx::query!("INSERT INTO anvils (weight) VALUES (?)", self.weight).execute(&mut *executor).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (weight) VALUES (?)", 1))?;
+                    sqlx::query_as!(Self, "SELECT id, weight FROM anvils WHERE id = LAST_INSERT_ID()").fetch_one(&mut *executor).await
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_fn_create_with_mysql_backend_reads_back_generated_columns() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "mysql")]
+            struct Anvil {
+                id: i64,
+                #[fabrique(generated)]
+                forged_at: String,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create method
+        let result = codegen.generate_fn_create();
+
+        // Assert the inserted row is selected back through its primary key
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
+                    let mut executor = sqlx::Acquire::acquire(connection).await?;
+                    sqlx::query!("INSERT INTO anvils (id) VALUES (?)", self.id).execute(&mut *executor).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id) VALUES (?)", 1))?;
+                    sqlx::query_as!(Self, "SELECT id, forged_at FROM anvils WHERE id = ?", self.id).fetch_one(&mut *executor).await
                 }
             }
             .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_const_table_columns() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(table = "forged_anvils")]
+            struct Anvil {
+                id: i64,
+                #[fabrique(column = "mass")]
+                weight: i32,
+                r#type: String,
+                #[fabrique(skip)]
+                dirty: bool,
+                #[fabrique(returning_expr = "mass * 2")]
+                double_weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_const_table_columns method
+        let result = codegen.generate_const_table_columns();
+
+        // Assert the renamed columns are listed, without the skipped and computed fields
+        assert_eq!(
+            result.to_string(),
+            quote! {
+                pub const TABLE: &'static str = "forged_anvils";
+
+                pub const COLUMNS: &'static [&'static str] = &["id", "mass", "type"];
+            }
+            .to_string()
         )
     }
 
     #[test]
-    fn test_generate_fn_create() {
+    fn test_generate_const_capabilities_reflects_the_configuration() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(
+                backend = "mysql",
+                emit_event = "AnvilForged",
+                cache_all = "5s",
+                all_by_id,
+                all_for_update,
+                paginate
+            )]
+            struct Anvil {
+                #[fabrique(sortable)]
+                id: i64,
+                #[fabrique(
+                    relation = "Hammer",
+                    referenced_key = "id",
+                    related_table = "hammers",
+                    related_columns = "id"
+                )]
+                hammer_id: i64,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let generated = codegen.generate().unwrap().to_string();
+
+        // Assert the optional operations enabled by the attributes are listed
+        assert!(
+            generated.contains(
+                &quote! {
+                    pub const CAPABILITIES: &'static [&'static str] = &[
+                        "create", "all", "update", "delete", "count", "create_tx", "find_by_id",
+                        "all_for_update", "paginate", "find_with_hammer", "all_sorted_by",
+                        "create_with_event", "all_by_id", "all_cached"
+                    ];
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_const_capabilities_with_sqlite_backend() {
         // Arrange the codegen
-        let input = parse_quote! { struct Anvil {} };
+        let input = parse_quote! {
+            #[fabrique(
+                backend = "sqlite",
+                copy_in,
+                reload_all,
+                all_by_id,
+                all_for_update,
+                which_exist,
+                paginate
+            )]
+            struct Anvil {
+                id: i64,
+                weight: i32,
+            }
+        };
         let codegen = PersistableCodegen::from(&input).unwrap();
 
         // Act the call to the generate method
+        let generated = codegen.generate().unwrap().to_string();
+
+        // Assert the methods SQLite does not support are not listed
+        assert!(
+            generated.contains(
+                &quote! {
+                    pub const CAPABILITIES: &'static [&'static str] = &[
+                        "create", "all", "update", "delete", "count", "create_tx", "find_by_id",
+                        "paginate", "all_by_id"
+                    ];
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_with_sqlite_backend() {
+        // Arrange the codegen
+        let input = parse_quote! {
+            #[fabrique(backend = "sqlite", from_row, copy_in)]
+            struct Anvil {
+                id: i64,
+                weight: i32,
+            }
+        };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate_fn_create and generate methods
         let result = codegen.generate_fn_create();
+        let generated = codegen.generate().unwrap().to_string();
 
         // Assert the result
         assert_eq!(
             result.to_string(),
             quote! {
                 async fn create(self, connection: &Self::Connection) -> Result<Self, Self::Error> {
-                    todo!()
+                    sqlx::query_as!(Self, "INSERT INTO anvils (id, weight) VALUES (?, ?) RETURNING id, weight", self.id, self.weight).fetch_one(connection).await.map_err(|error| ::fabrique::StatementError::wrap(error, "anvils", "INSERT", "INSERT INTO anvils (id, weight) VALUES (?, ?) RETURNING id, weight", 2))
                 }
             }
             .to_string()
-        )
+        );
+        assert!(
+            generated.contains(&quote! { type Connection = sqlx::Pool<sqlx::Sqlite>; }.to_string())
+        );
+        assert!(generated.contains(
+            &quote! { impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Anvil }.to_string()
+        ));
+        assert!(!generated.contains("copy_in"));
     }
 
     #[test]
@@ -155,4 +3980,20 @@ mod tests {
         // Assert the result
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_generate_marks_impls_as_automatically_derived() {
+        // Arrange the codegen
+        let input = parse_quote! { struct Anvil { id: Uuid } };
+        let codegen = PersistableCodegen::from(&input).unwrap();
+
+        // Act the call to the generate method
+        let result = codegen.generate().unwrap().to_string();
+
+        // Assert the result
+        assert!(result.contains(
+            &quote! { #[automatically_derived] impl ::fabrique::Persistable for Anvil }.to_string()
+        ));
+        assert!(result.contains(&quote! { #[automatically_derived] impl Anvil }.to_string()));
+    }
 }
@@ -2,6 +2,7 @@
 fn ui() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/ui/pass/*.rs");
 
     // derive_persistable
     t.pass("tests/ui/persistable/pass/*.rs");
@@ -0,0 +1,12 @@
+use fabrique_derive::Factory;
+
+#[derive(Factory)]
+struct Anvil {
+    #[fabrique(primary_key)]
+    id: u32,
+    weight: u32,
+    #[fabrique(relation = "Hammer", relation_trait = "dyn Creatable", referenced_key = "id")]
+    tool_id: u32,
+}
+
+fn main() {}
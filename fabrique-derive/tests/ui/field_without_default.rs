@@ -0,0 +1,25 @@
+use fabrique::Persistable;
+use fabrique_derive::Factory;
+
+struct Serial(u32);
+
+#[derive(Factory)]
+struct Anvil {
+    serial: Serial,
+    weight: u32,
+}
+
+impl Persistable for Anvil {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use fabrique_derive::Factory;
+
+#[derive(Factory)]
+struct Anvil {
+    #[fabrique(primary_key)]
+    id: u32,
+    #[fabrique(primary_key)]
+    serial_number: u32,
+}
+
+fn main() {}
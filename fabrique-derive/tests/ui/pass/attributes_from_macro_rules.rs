@@ -0,0 +1,49 @@
+use fabrique::Persistable;
+use fabrique_derive::Factory;
+
+macro_rules! mock_persistable {
+    ($name:ident) => {
+        impl Persistable for $name {
+            type Connection = ();
+            type Error = ();
+
+            async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+                Ok(self)
+            }
+
+            async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                Ok(vec![])
+            }
+        }
+    };
+}
+
+#[derive(Default, Factory)]
+struct Warehouse {
+    #[fabrique(primary_key)]
+    id: u32,
+}
+
+mock_persistable!(Warehouse);
+
+macro_rules! stored_in {
+    ($name:ident, $related:ty, $key:literal, $quantity:expr) => {
+        #[derive(Factory)]
+        struct $name {
+            #[fabrique(relation = $related, referenced_key = $key)]
+            warehouse_id: u32,
+            #[factory(default = $quantity)]
+            quantity: u32,
+        }
+
+        mock_persistable!($name);
+    };
+}
+
+stored_in!(Pallet, Warehouse, "id", 40);
+stored_in!(Crate, Warehouse, "id", 12);
+
+fn main() {
+    let _pallet = Pallet::factory().for_warehouse(|factory| factory.id(1));
+    let _crate = Crate::factory().for_warehouse(|factory| factory.id(2));
+}
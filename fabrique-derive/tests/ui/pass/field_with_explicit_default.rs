@@ -0,0 +1,36 @@
+use fabrique::Persistable;
+use fabrique_derive::Factory;
+
+struct Serial(u32);
+
+fn first_serial() -> Serial {
+    Serial(1)
+}
+
+#[derive(Factory)]
+struct Anvil {
+    #[factory(default = "Serial(42)")]
+    serial: Serial,
+    #[factory(default_fn = "first_serial")]
+    hammer_serial: Serial,
+    weight: u32,
+}
+
+impl Persistable for Anvil {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn main() {
+    let anvil = Anvil::factory().build();
+    assert_eq!(anvil.serial.0, 42);
+    assert_eq!(anvil.hammer_serial.0, 1);
+}
@@ -0,0 +1,34 @@
+use fabrique::Persistable;
+use fabrique_derive::Factory;
+
+#[derive(Factory)]
+struct Crate<T>
+where
+    T: Default + Send,
+{
+    label: String,
+    content: T,
+}
+
+impl<T> Persistable for Crate<T>
+where
+    T: Default + Send,
+{
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn main() {
+    let packed: Crate<u32> = Crate::factory().content(3).build();
+    let default: CrateFactory<String> = CrateFactory::default();
+    let _ = format!("{default:?}");
+    let _ = packed.label;
+}
@@ -0,0 +1,48 @@
+use fabrique::Persistable;
+use fabrique_derive::Factory;
+
+#[derive(Default, Factory)]
+struct Warehouse {
+    #[fabrique(primary_key)]
+    id: u32,
+}
+
+impl Persistable for Warehouse {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+#[derive(Factory)]
+struct Shipment {
+    #[fabrique(relation = "Warehouse", referenced_key = "id")]
+    source_warehouse_id: u32,
+    #[fabrique(relation = "Warehouse", referenced_key = "id")]
+    destination_warehouse_id: u32,
+}
+
+impl Persistable for Shipment {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn main() {
+    let _factory = Shipment::factory()
+        .for_source_warehouse(|factory| factory.id(1))
+        .for_destination_warehouse(|factory| factory.id(2));
+}
@@ -0,0 +1,13 @@
+use fabrique_derive::Persistable;
+use uuid::Uuid;
+
+#[derive(Persistable)]
+struct Anvil {
+    id: Uuid,
+    #[fabrique(sortable)]
+    hammer_id: Uuid,
+    #[fabrique(sortable)]
+    hammer__id: Uuid,
+}
+
+fn main() {}
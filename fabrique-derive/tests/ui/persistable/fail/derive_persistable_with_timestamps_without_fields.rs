@@ -0,0 +1,10 @@
+use fabrique_derive::Persistable;
+
+#[derive(Persistable)]
+#[fabrique(timestamps)]
+struct Anvil {
+    id: i32,
+    weight: i32,
+}
+
+fn main() {}
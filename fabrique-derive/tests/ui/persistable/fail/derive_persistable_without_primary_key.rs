@@ -0,0 +1,8 @@
+use fabrique_derive::Persistable;
+
+#[derive(Persistable)]
+struct Anvil {
+    weight: i32,
+}
+
+fn main() {}
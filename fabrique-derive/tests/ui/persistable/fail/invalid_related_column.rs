@@ -0,0 +1,16 @@
+use fabrique_derive::Persistable;
+use uuid::Uuid;
+
+#[derive(Persistable)]
+struct Stand {
+    id: Uuid,
+    #[fabrique(
+        relation = "Hammer",
+        referenced_key = "id",
+        related_table = "hammers",
+        related_columns = "id, created-at"
+    )]
+    hammer_id: Uuid,
+}
+
+fn main() {}
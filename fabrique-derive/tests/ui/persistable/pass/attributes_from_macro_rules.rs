@@ -0,0 +1,22 @@
+use fabrique_derive::Persistable;
+use uuid::Uuid;
+
+macro_rules! hammer_model {
+    ($name:ident, $table:literal, $column:literal, $scope:literal) => {
+        #[derive(Persistable)]
+        #[fabrique(table = $table, default_scope = $scope)]
+        struct $name {
+            #[fabrique(primary_key)]
+            id: Uuid,
+            #[fabrique(column = $column)]
+            label: String,
+        }
+    };
+}
+
+hammer_model!(ClawHammer, "hammers", "name", "name = 'CLAW'");
+hammer_model!(SledgeHammer, "hammers", "name", "name = 'SLEDGE'");
+
+fn main() {
+    let _ = (ClawHammer::TABLE, SledgeHammer::COLUMNS);
+}
@@ -0,0 +1,13 @@
+use fabrique_derive::Persistable;
+use uuid::Uuid;
+
+// `Vec<u8>` has no `Display` implementation, which no method generated by default relies on
+#[derive(Persistable)]
+#[fabrique(table = "blueprints")]
+struct Blueprint {
+    id: Uuid,
+    drawing: Vec<u8>,
+    revision: Option<String>,
+}
+
+fn main() {}
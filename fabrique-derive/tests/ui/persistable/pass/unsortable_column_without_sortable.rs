@@ -0,0 +1,16 @@
+use fabrique_derive::Persistable;
+use sqlx::postgres::types::PgPoint;
+use uuid::Uuid;
+
+// Postgres cannot order by a `point` column, so only the id is marked as sortable
+#[derive(Persistable)]
+#[fabrique(table = "quarries")]
+struct Quarry {
+    #[fabrique(sortable)]
+    id: Uuid,
+    location: PgPoint,
+}
+
+fn main() {
+    let _ = QuarrySortColumn::Id.as_sql();
+}
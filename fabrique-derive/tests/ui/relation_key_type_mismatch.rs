@@ -0,0 +1,40 @@
+use fabrique::{Factory, Persistable};
+
+#[derive(Factory)]
+struct Hammer {
+    code: String,
+}
+
+impl Persistable for Hammer {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+#[derive(Factory)]
+struct Anvil {
+    #[fabrique(relation = "Hammer", referenced_key = "code")]
+    hammer_id: u32,
+}
+
+impl Persistable for Anvil {
+    type Connection = ();
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn main() {}
@@ -1,4 +1,9 @@
-pub use fabrique_core::Persistable;
+#[cfg(feature = "sqlx")]
+pub use fabrique_core::StatementError;
+pub use fabrique_core::{
+    Creatable, CreateStats, Deletable, FactoryDefault, NotNullViolation, Persistable, SeedLog,
+    SortDir, StaleWrite, Trackable, Updatable, Validate, cache, debug, sequence,
+};
 pub use fabrique_derive::Factory;
 
 pub use fabrique_derive::Persistable;
@@ -1,4 +1,26 @@
-use fabrique::{Factory, Persistable};
+use std::pin::Pin;
+
+use fabrique::{Creatable, CreateStats, Factory, Persistable, SeedLog, Trackable, Validate};
+
+/// Implements `Persistable` without storage: `create()` hands the object back as is and
+/// `all()` finds nothing.
+macro_rules! fake_persistable {
+    ($model:ty) => {
+        impl Persistable for $model {
+            type Connection = ();
+
+            type Error = ();
+
+            async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+                Ok(self)
+            }
+
+            async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+                Ok(vec![])
+            }
+        }
+    };
+}
 
 // Darling ?
 #[derive(Debug, Default, Eq, Factory, PartialEq)]
@@ -12,28 +34,253 @@ struct Anvil {
     weight: u32,
 }
 
-impl Persistable for Anvil {
+fake_persistable!(Anvil);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(mock)]
+struct Hammer {
+    #[fabrique(primary_key)]
+    id: u32,
+    weight: u32,
+}
+
+fake_persistable!(Hammer);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Mallet {
+    #[fabrique(primary_key)]
+    id: u32,
+    head_diameter: u32,
+}
+
+fake_persistable!(Mallet);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Toolbox {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[fabrique(relation_trait = "dyn Creatable")]
+    tool_id: u32,
+}
+
+fake_persistable!(Toolbox);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(validate)]
+struct Rocket {
+    #[fabrique(primary_key)]
+    id: u32,
+    #[factory(validate_field = "check_thrust")]
+    thrust: u32,
+}
+
+fn check_thrust(thrust: &u32) -> Result<(), String> {
+    if *thrust > 10_000 {
+        return Err("the rocket would fly off".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Furnace {
+    #[fabrique(primary_key)]
+    id: u32,
+    #[factory(
+        validate_field = "check_temperature",
+        validate_field_error = "TemperatureError"
+    )]
+    temperature: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct TemperatureError(u32);
+
+fn check_temperature(temperature: &u32) -> Result<(), TemperatureError> {
+    if *temperature > 2_000 {
+        return Err(TemperatureError(*temperature));
+    }
+    Ok(())
+}
+
+fake_persistable!(Furnace);
+
+impl Persistable for Rocket {
     type Connection = ();
 
     type Error = ();
 
     async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
-        Ok(self)
+        unreachable!("validated rockets are never persisted")
     }
 
     async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
-        Ok(vec![])
+        unreachable!("validated rockets are never persisted")
+    }
+}
+
+impl Validate for Rocket {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.thrust == 0 {
+            return Err("a rocket needs some thrust".to_string());
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug, Default, Eq, Factory, PartialEq)]
-struct Hammer {
+struct Catapult {
+    #[fabrique(primary_key)]
+    #[factory(sequence)]
+    id: u32,
+    #[factory(sequence = "inventory")]
+    inventory_number: u64,
+}
+
+fake_persistable!(Catapult);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Ingot {
+    #[fabrique(primary_key)]
+    #[factory(sequence)]
+    id: u32,
+    #[factory(sequence, sequence_format = "ingot-{n}")]
+    label: String,
+}
+
+fake_persistable!(Ingot);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Smelter {
+    #[fabrique(primary_key)]
+    #[factory(sequence)]
+    id: u32,
+}
+
+fake_persistable!(Smelter);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Ladle {
+    #[fabrique(primary_key)]
+    #[factory(sequence, sequence_format = "ladle-{n}")]
+    id: String,
+}
+
+fake_persistable!(Ladle);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Slingshot {
+    #[fabrique(primary_key)]
+    #[factory(sequence)]
+    id: u32,
+    #[factory(sequence = "inventory")]
+    inventory_number: u64,
+}
+
+fake_persistable!(Slingshot);
+
+#[derive(Debug)]
+struct Workshop {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(context = "Workshop")]
+struct Workbench {
+    #[fabrique(primary_key)]
+    id: u32,
+    #[factory(default = "self.context.as_ref().map(|workshop| workshop.id).unwrap_or_default()")]
+    workshop_id: u32,
+    #[factory(
+        default = "self.context.as_ref().map(|workshop| workshop.name.clone()).unwrap_or_default()"
+    )]
+    label: String,
+}
+
+fake_persistable!(Workbench);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(output = "Arc")]
+struct Anchor {
     #[fabrique(primary_key)]
     id: u32,
     weight: u32,
 }
 
-impl Persistable for Hammer {
+fake_persistable!(Anchor);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Node {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[fabrique(relation = "Self", referenced_key = "id")]
+    parent_id: Option<u32>,
+}
+
+// An inherent `new()` on the model must not be mistaken for the one of its factory
+impl Node {
+    fn new(id: u32, parent_id: Option<u32>) -> Self {
+        Self { id, parent_id }
+    }
+}
+
+fake_persistable!(Node);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Crucible {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[factory(no_default)]
+    capacity: u32,
+}
+
+fake_persistable!(Crucible);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Pallet {
+    #[fabrique(primary_key)]
+    #[factory(sequence)]
+    id: u32,
+}
+
+fake_persistable!(Pallet);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Shipment {
+    #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+    pallet_id: u32,
+
+    #[fabrique(relation = "Pallet", referenced_key = "id", shared = "pallet")]
+    return_pallet_id: u32,
+}
+
+fake_persistable!(Shipment);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Crate {
+    #[fabrique(relation = "Pallet", referenced_key = "id", cfg = "any()")]
+    pallet_id: u32,
+}
+
+fake_persistable!(Crate);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Label<'a> {
+    #[fabrique(primary_key)]
+    id: u32,
+    name: &'a str,
+    tags: Vec<&'a str>,
+
+    #[fabrique(relation = "Hammer", referenced_key = "id")]
+    hammer_id: u32,
+}
+
+impl<'a> Persistable for Label<'a> {
     type Connection = ();
 
     type Error = ();
@@ -47,6 +294,200 @@ impl Persistable for Hammer {
     }
 }
 
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Stand {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[fabrique(
+        relation = "Hammer",
+        referenced_key = "weight",
+        default_child = "|factory| factory.weight(100)"
+    )]
+    hammer_weight: u32,
+}
+
+fake_persistable!(Stand);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Bin {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[fabrique(relation = crate::Rivet, referenced_key = "length")]
+    rivet_length: u32,
+}
+
+fake_persistable!(Bin);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(repeat)]
+struct Bucket {
+    #[factory(sequence)]
+    id: u32,
+
+    #[fabrique(relation = "Hammer", referenced_key = "id")]
+    hammer_id: u32,
+}
+
+fake_persistable!(Bucket);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(repeat)]
+struct Rivet {
+    #[factory(sequence)]
+    id: u32,
+    length: u32,
+}
+
+fake_persistable!(Rivet);
+
+static PUMPED_BELLOWS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+async fn pump_bellows(mut bellows: Bellows, _connection: &()) -> Result<Bellows, ()> {
+    PUMPED_BELLOWS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    bellows.pressure += 1;
+    Ok(bellows)
+}
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(after_create = "pump_bellows")]
+struct Bellows {
+    pressure: u32,
+}
+
+impl Persistable for Bellows {
+    type Connection = ();
+
+    type Error = ();
+
+    async fn create(self, _connection: &Self::Connection) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pressure: self.pressure * 10,
+        })
+    }
+
+    async fn all(_connection: &Self::Connection) -> Result<Vec<Self>, Self::Error> {
+        Ok(vec![])
+    }
+}
+
+fn coal_forge() -> Forge {
+    Forge {
+        fuel: "coal".to_string(),
+        temperature: 1200,
+    }
+}
+
+#[derive(Debug, Eq, Factory, PartialEq)]
+#[factory(build_with_defaults_from = "coal_forge")]
+struct Forge {
+    fuel: String,
+    temperature: u32,
+}
+
+fake_persistable!(Forge);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Rack {
+    slots: Vec<u32>,
+}
+
+fake_persistable!(Rack);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(mut_builder)]
+struct Tongs {
+    length: u32,
+    weight: u32,
+}
+
+fake_persistable!(Tongs);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+struct Chisel {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[factory(default = "Some(\"Acme\".to_string())")]
+    brand: Option<String>,
+}
+
+fake_persistable!(Chisel);
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(tracked)]
+struct Kiln {
+    #[fabrique(primary_key)]
+    id: u32,
+}
+
+fake_persistable!(Kiln);
+
+impl Trackable for Kiln {
+    const TABLE_NAME: &'static str = "kilns";
+
+    fn primary_key(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+#[derive(Debug, Default, Eq, Factory, PartialEq)]
+#[factory(tracked)]
+struct Brick {
+    #[fabrique(primary_key)]
+    id: u32,
+
+    #[fabrique(relation = "Kiln", referenced_key = "id")]
+    kiln_id: u32,
+}
+
+fake_persistable!(Brick);
+
+impl Trackable for Brick {
+    const TABLE_NAME: &'static str = "bricks";
+
+    fn primary_key(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+impl Creatable<u32, (), ()> for HammerFactory {
+    fn create<'a>(
+        self: Box<Self>,
+        connection: &'a (),
+    ) -> Pin<Box<dyn Future<Output = Result<u32, ()>> + Send + 'a>> {
+        Box::pin(async move { Ok((*self).create(connection).await?.id) })
+    }
+}
+
+impl Creatable<u32, (), ()> for MalletFactory {
+    fn create<'a>(
+        self: Box<Self>,
+        connection: &'a (),
+    ) -> Pin<Box<dyn Future<Output = Result<u32, ()>> + Send + 'a>> {
+        Box::pin(async move { Ok((*self).create(connection).await?.id) })
+    }
+}
+
+/// Fake connection counting the queries it runs, to observe the `all_cached()` storage.
+#[derive(Default)]
+struct CountingConnection {
+    queries: std::sync::atomic::AtomicUsize,
+}
+
+impl CountingConnection {
+    async fn all(&self) -> Result<Vec<u32>, ()> {
+        self.queries
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(vec![1, 2, 3])
+    }
+
+    fn queries(&self) -> usize {
+        self.queries.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +534,691 @@ mod tests {
             }
         );
     }
+
+    #[tokio::test]
+    async fn test_factory_with_dynamic_relations() {
+        // Act - create toolboxes whose tool type is chosen at runtime
+        let with_hammer = Toolbox::factory()
+            .for_tool(Hammer::factory().id(7))
+            .create(&())
+            .await;
+        let with_mallet = Toolbox::factory()
+            .for_tool(Mallet::factory().id(12).head_diameter(80))
+            .create(&())
+            .await;
+
+        // Assert the results
+        assert_eq!(with_hammer.unwrap().tool_id, 7);
+        assert_eq!(with_mallet.unwrap().tool_id, 12);
+    }
+
+    #[test]
+    fn test_factory_build_does_not_persist() {
+        // Act - build a hammer without persisting it
+        let hammer = Hammer::factory().weight(500).build();
+
+        // Assert the result
+        assert_eq!(hammer, Hammer { id: 0, weight: 500 });
+    }
+
+    #[test]
+    fn test_factory_finalize_aliases_build() {
+        // Act - finalize a hammer without persisting it
+        let hammer = Hammer::factory().weight(500).finalize();
+
+        // Assert the result
+        assert_eq!(hammer, Hammer::factory().weight(500).build());
+    }
+
+    #[tokio::test]
+    async fn test_factory_calls_relation_created_callback() {
+        // Arrange a shared slot capturing the created hammer
+        let created = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let captured = created.clone();
+
+        // Act - create an anvil registering a callback on the hammer creation
+        let result = Anvil::factory()
+            .for_hammer(|factory| factory.id(100).weight(5))
+            .on_hammer_created(move |hammer| *captured.lock().unwrap() = Some(hammer.weight))
+            .create(&())
+            .await;
+
+        // Assert the callback received the persisted hammer
+        assert!(result.is_ok());
+        assert_eq!(*created.lock().unwrap(), Some(5));
+    }
+
+    #[test]
+    fn test_factory_can_be_defaulted() {
+        // Act - build through a defaulted factory
+        let anvil = AnvilFactory::default().weight(5).build();
+
+        // Assert the factory behaves like the one returned by new()
+        assert_eq!(anvil.weight, 5);
+        assert_eq!(anvil.hammer_id, 0);
+    }
+
+    #[test]
+    fn test_factory_can_be_debugged() {
+        // Arrange a partially configured factory
+        let factory = Anvil::factory()
+            .weight(5)
+            .for_hammer(|factory| factory.id(100));
+
+        // Act - print it
+        let printed = format!("{factory:?}");
+
+        // Assert the set values and relations are shown
+        assert_eq!(
+            printed,
+            "AnvilFactory { id: None, hammer_id: None, hardness: None, weight: Some(5), \
+             hammer_factory: <set>, hammer_on_created: <unset> }"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_factory_links_an_existing_related_object() {
+        // Arrange an already persisted hammer
+        let hammer = Hammer::factory().id(42).create(&()).await.unwrap();
+
+        // Act - create an anvil linked to it, overriding a configured relation
+        let result = Anvil::factory()
+            .for_hammer(|factory| factory.id(100))
+            .with_hammer(&hammer)
+            .create(&())
+            .await;
+
+        // Assert the anvil references the existing hammer
+        assert_eq!(result.unwrap().hammer_id, 42);
+    }
+
+    #[test]
+    fn test_factory_try_build_validated() {
+        // Act - build rockets without persisting them
+        let valid = Rocket::factory().thrust(9000).try_build_validated();
+        let invalid = Rocket::factory().try_build_validated();
+
+        // Assert the results
+        assert_eq!(
+            valid,
+            Ok(Rocket {
+                id: 0,
+                thrust: 9000
+            })
+        );
+        assert_eq!(invalid, Err("a rocket needs some thrust".to_string()));
+    }
+
+    #[test]
+    fn test_factory_sequences() {
+        // Act - build instances from two factories
+        let first_catapult = Catapult::factory().build();
+        let first_slingshot = Slingshot::factory().build();
+        let second_catapult = Catapult::factory().build();
+
+        // Assert owned sequences are independent from each other
+        assert_eq!(first_catapult.id, 1);
+        assert_eq!(second_catapult.id, 2);
+        assert_eq!(first_slingshot.id, 1);
+
+        // Assert the shared namespace is drawn from by both factories
+        assert_eq!(first_catapult.inventory_number, 1);
+        assert_eq!(first_slingshot.inventory_number, 2);
+        assert_eq!(second_catapult.inventory_number, 3);
+    }
+
+    #[tokio::test]
+    async fn test_factory_sequences_are_shared_by_every_method() {
+        // Act - alternate building and creating smelters
+        let first = Smelter::factory().build();
+        let second = Smelter::factory().create(&()).await.unwrap();
+        let third = Smelter::factory().build();
+        let (fourth, _) = Smelter::factory().create_with_stats(&()).await.unwrap();
+
+        // Assert every method drew from the same counter
+        let ids = [first.id, second.id, third.id, fourth.id];
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]), "{ids:?}");
+    }
+
+    #[tokio::test]
+    async fn test_factory_formatted_sequences_are_shared_by_every_method() {
+        // Act - alternate building and creating ladles
+        let first = Ladle::factory().build();
+        let second = Ladle::factory().create(&()).await.unwrap();
+        let third = Ladle::factory().build();
+
+        // Assert no two methods rendered the same value
+        assert_eq!(
+            [first.id, second.id, third.id],
+            ["ladle-1", "ladle-2", "ladle-3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_factory_formatted_sequences() {
+        // Act - create three ingots
+        let mut labels = Vec::new();
+        for _ in 0..3 {
+            let ingot = Ingot::factory().create(&()).await.unwrap();
+            labels.push(ingot.label);
+        }
+
+        // Assert each ingot got the next formatted value
+        assert_eq!(labels, vec!["ingot-1", "ingot-2", "ingot-3"]);
+    }
+
+    #[tokio::test]
+    async fn test_factory_defaults_read_the_context() {
+        // Arrange the shared context
+        let workshop = Workshop {
+            id: 42,
+            name: "Acme".to_string(),
+        };
+
+        // Act - create a workbench within the context
+        let result = Workbench::factory()
+            .with_context(workshop)
+            .create(&())
+            .await;
+
+        // Assert both defaults were derived from the context
+        let workbench = result.unwrap();
+        assert_eq!(workbench.workshop_id, 42);
+        assert_eq!(workbench.label, "Acme");
+    }
+
+    #[tokio::test]
+    async fn test_factory_wraps_the_output() {
+        // Act - build and create anchors
+        let built: std::sync::Arc<Anchor> = Anchor::factory().weight(80).build();
+        let created: std::sync::Arc<Anchor> =
+            Anchor::factory().weight(90).create(&()).await.unwrap();
+
+        // Assert the anchors are shared pointers
+        assert_eq!(built.weight, 80);
+        assert_eq!(created.weight, 90);
+    }
+
+    #[test]
+    fn test_factory_lists_its_relations() {
+        // Assert the relation names of the factories
+        assert_eq!(AnvilFactory::RELATIONS, &["hammer"]);
+        assert!(HammerFactory::RELATIONS.is_empty());
+        assert!(CrateFactory::RELATIONS.is_empty());
+    }
+
+    #[test]
+    fn test_mock_fills_every_field() {
+        // Act - mock a hammer
+        let hammer = Hammer::mock();
+
+        // Assert every field holds a placeholder
+        assert_eq!(hammer, Hammer { id: 1, weight: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_optional_relation_callback() {
+        // Arrange the optional callbacks
+        let some_callback = Some(|factory: HammerFactory| factory.id(3));
+        let no_callback: Option<fn(HammerFactory) -> HammerFactory> = None;
+
+        // Act - create anvils with and without a buffered relation
+        let with_hammer = Anvil::factory()
+            .for_hammer_opt(some_callback)
+            .create(&())
+            .await;
+        let without_hammer = Anvil::factory()
+            .hammer_id(7)
+            .for_hammer_opt(no_callback)
+            .create(&())
+            .await;
+
+        // Assert the relation was only created when a callback was given
+        assert_eq!(with_hammer.unwrap().hammer_id, 3);
+        assert_eq!(without_hammer.unwrap().hammer_id, 7);
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_self_relation() {
+        // Arrange a slot capturing the created parent
+        let parent = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let captured = parent.clone();
+
+        // Act - create a child node along with its parent
+        let child = Node::factory()
+            .id(2)
+            .for_parent(|factory| factory.id(1))
+            .on_parent_created(move |node| *captured.lock().unwrap() = Some(node.parent_id))
+            .create(&())
+            .await;
+
+        // Assert the child references its parent
+        assert_eq!(
+            child.unwrap(),
+            Node {
+                id: 2,
+                parent_id: Some(1)
+            }
+        );
+        assert_eq!(*parent.lock().unwrap(), Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_self_relation_creates_a_root_node() {
+        // Act - create a node without parent
+        let root = Node::factory().id(1).create(&()).await;
+
+        // Assert the root node references no parent
+        assert_eq!(root.unwrap(), Node::new(1, None));
+    }
+
+    #[tokio::test]
+    async fn test_factory_ignores_the_inherent_new_of_the_model() {
+        // Act - create a node and its parent through the factory
+        let child = Node::factory()
+            .id(2)
+            .for_parent(|factory| factory.id(1))
+            .create(&())
+            .await;
+
+        // Assert the result
+        assert_eq!(child.unwrap(), Node::new(2, Some(1)));
+    }
+
+    #[test]
+    fn test_factory_try_setter_rejects_invalid_values() {
+        // Act - set a valid then an invalid thrust
+        let valid = Rocket::factory().try_thrust(9000);
+        let invalid = Rocket::factory().try_thrust(20_000);
+
+        // Assert only the valid thrust was accepted
+        assert_eq!(valid.unwrap().build().thrust, 9000);
+        assert_eq!(invalid.err(), Some("the rocket would fly off".to_string()));
+    }
+
+    #[test]
+    fn test_factory_try_setter_returns_the_error_of_the_validator() {
+        // Act - set a valid then an invalid temperature on a model without `Validate`
+        let valid = Furnace::factory().try_temperature(1_500);
+        let invalid = Furnace::factory().try_temperature(3_000);
+
+        // Assert the invalid temperature was rejected with the validator error
+        assert_eq!(valid.unwrap().build().temperature, 1_500);
+        assert_eq!(invalid.err(), Some(TemperatureError(3_000)));
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_borrowed_fields() {
+        // Arrange borrowed data outliving the factory
+        let name = String::from("ACME");
+        let hammer_weight = 12;
+
+        // Act - create a label borrowing the data, including from a relation callback
+        let result = Label::factory()
+            .name(&name)
+            .tags(vec![name.as_str()])
+            .for_hammer(|factory| factory.id(hammer_weight).weight(hammer_weight))
+            .create(&())
+            .await;
+
+        // Assert the borrowed values were used
+        assert_eq!(
+            result.unwrap(),
+            Label {
+                id: 0,
+                name: "ACME",
+                tags: vec!["ACME"],
+                hammer_id: 12,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_shared_relations_creates_a_single_object() {
+        // Act - create a shipment whose foreign keys share a pallet
+        let result = Shipment::factory()
+            .for_return_pallet(|factory| factory)
+            .create(&())
+            .await
+            .unwrap();
+
+        // Assert both foreign keys reference the same pallet, created once
+        assert_eq!(result.pallet_id, result.return_pallet_id);
+        let next_pallet = Pallet::factory().create(&()).await.unwrap();
+        assert_eq!(next_pallet.id, result.pallet_id + 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the shared group `pallet` creates a single related object")]
+    async fn test_factory_with_shared_relations_rejects_several_callbacks() {
+        // Act - configure the shared pallet through both foreign keys
+        let _ = Shipment::factory()
+            .for_pallet(|factory| factory)
+            .for_return_pallet(|factory| factory)
+            .create(&())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_factory_requires_fields_without_default() {
+        // Act - create crucibles from their mandatory capacity
+        let built = Crucible::factory(500).build();
+        let created = Crucible::factory(500).capacity(750).id(3).create(&()).await;
+
+        // Assert the result
+        assert_eq!(
+            built,
+            Crucible {
+                id: 0,
+                capacity: 500
+            }
+        );
+        assert_eq!(
+            created.unwrap(),
+            Crucible {
+                id: 3,
+                capacity: 750
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_factory_create_with_stats_counts_related_rows() {
+        // Act - create an anvil along with its hammer
+        let result = Anvil::factory()
+            .for_hammer(|factory| factory.id(100))
+            .create_with_stats(&())
+            .await;
+
+        // Assert the anvil and its hammer were counted
+        let (anvil, stats) = result.unwrap();
+        assert_eq!(anvil.hammer_id, 100);
+        assert_eq!(stats, CreateStats { rows: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_default_child_configures_the_related_factory() {
+        // Act - create stands with the default hammer, then with overrides
+        let default = Stand::factory().create(&()).await.unwrap();
+        let composed = Stand::factory()
+            .for_hammer(|factory| factory.id(7))
+            .create(&())
+            .await
+            .unwrap();
+        let overridden = Stand::factory()
+            .for_hammer(|factory| factory.weight(120))
+            .create(&())
+            .await
+            .unwrap();
+
+        // Assert the defaults apply unless the callback overrides them
+        assert_eq!(default.hammer_weight, 100);
+        assert_eq!(composed.hammer_weight, 100);
+        assert_eq!(overridden.hammer_weight, 120);
+    }
+
+    #[tokio::test]
+    async fn test_factory_with_a_relation_given_as_a_bare_path() {
+        // Act - create a bin along with its rivet
+        let bin = Bin::factory()
+            .for_rivet(|factory| factory.length(12))
+            .create(&())
+            .await
+            .unwrap();
+
+        // Assert the rivet was created through the factory at the given path
+        assert_eq!(bin.rivet_length, 12);
+    }
+
+    #[test]
+    fn test_factory_with_mut_builder_keeps_the_factory() {
+        // Arrange a factory held by a longer-lived owner
+        let mut factory = Tongs::factory();
+        factory.length(40).weight(2);
+
+        // Act - build twice from the same factory
+        let first = factory.build();
+        let second = factory.weight(3).finalize();
+
+        // Assert the result
+        assert_eq!(
+            first,
+            Tongs {
+                length: 40,
+                weight: 2
+            }
+        );
+        assert_eq!(
+            second,
+            Tongs {
+                length: 40,
+                weight: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_factory_extends_vec_fields_from_iterators() {
+        // Act - build a rack from a range and a mapped iterator
+        let rack = Rack::factory()
+            .extend_slots(0..5)
+            .extend_slots([1, 2].iter().map(|slot| slot * 10))
+            .build();
+
+        // Assert the items were appended in order
+        assert_eq!(rack.slots, vec![0, 1, 2, 3, 4, 10, 20]);
+    }
+
+    #[tokio::test]
+    async fn test_factory_runs_the_after_create_hook_on_the_persisted_row() {
+        // Act - create bellows through a factory declaring a hook
+        let bellows = Bellows::factory().pressure(4).create(&()).await;
+
+        // Assert the hook ran once and received the persisted row
+        assert_eq!(bellows.unwrap().pressure, 41);
+        assert_eq!(PUMPED_BELLOWS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_factory_overlays_set_fields_on_the_template() {
+        // Act - create a forge overriding a single field of the template
+        let forge = Forge::factory().temperature(900).create(&()).await;
+
+        // Assert the unset field comes from the template
+        assert_eq!(
+            forge.unwrap(),
+            Forge {
+                fuel: "coal".to_string(),
+                temperature: 900,
+            }
+        );
+    }
+
+    #[test]
+    fn test_factory_option_setter_overrides_the_default_both_ways() {
+        // Act - build chisels keeping, replacing and clearing the default brand
+        let default = Chisel::factory().build();
+        let replaced = Chisel::factory().brand("Anvil Co".to_string()).build();
+        let cleared = Chisel::factory().brand(None).build();
+
+        // Assert the brand was set to the given value, or to None
+        assert_eq!(default.brand.as_deref(), Some("Acme"));
+        assert_eq!(replaced.brand.as_deref(), Some("Anvil Co"));
+        assert_eq!(cleared.brand, None);
+    }
+
+    #[test]
+    fn test_factory_repeat_builds_several_objects() {
+        // Act - build three rivets of the same length
+        let result = Rivet::factory().length(12).repeat(3).collect::<Vec<_>>();
+
+        // Assert each rivet was built with its own sequence value, shared with the other
+        // tests creating rivets
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|rivet| rivet.length == 12));
+        assert!(result.windows(2).all(|pair| pair[0].id < pair[1].id));
+    }
+
+    #[tokio::test]
+    async fn test_factory_create_without_relations_skips_the_related_objects() {
+        // Arrange a relation callback recording whether it was called
+        let called = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let captured = called.clone();
+
+        // Act - create the anvil alone
+        let result = Anvil::factory()
+            .hammer_id(7)
+            .for_hammer(move |factory| {
+                captured.store(true, std::sync::atomic::Ordering::Relaxed);
+                factory.id(100)
+            })
+            .create_without_relations(&())
+            .await;
+
+        // Assert the anvil keeps its foreign key and no hammer was created
+        assert_eq!(result.unwrap().hammer_id, 7);
+        assert!(!called.load(std::sync::atomic::Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_factory_create_tracked_logs_every_created_object() {
+        // Arrange an empty seed log
+        let mut log = SeedLog::new();
+
+        // Act - create a brick along with its kiln
+        let result = Brick::factory()
+            .id(1)
+            .for_kiln(|factory| factory.id(7))
+            .create_tracked(&(), &mut log)
+            .await;
+
+        // Assert the kiln and the brick were logged in creation order
+        assert_eq!(result.unwrap().kiln_id, 7);
+        assert_eq!(
+            log,
+            vec![("kilns", "7".to_string()), ("bricks", "1".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_factory_create_many_with_customizes_each_object() {
+        // Act - create five rivets of increasing length
+        let result = Rivet::factory()
+            .create_many_with(5, |index, factory| factory.length(index as u32 * 10), &())
+            .await;
+
+        // Assert each rivet got the length of its index
+        let lengths = result.unwrap().into_iter().map(|rivet| rivet.length);
+        assert_eq!(lengths.collect::<Vec<_>>(), [0, 10, 20, 30, 40]);
+    }
+
+    #[tokio::test]
+    async fn test_factory_create_many_resolves_relations_for_each_object() {
+        // Arrange a relation callback drawing a new hammer id on each call
+        let next_id = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(1));
+
+        // Act - create three buckets
+        let result = Bucket::factory()
+            .for_hammer(move |factory| {
+                factory.id(next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+            })
+            .create_many(3, &())
+            .await;
+
+        // Assert each bucket was created along with its own hammer
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                Bucket {
+                    id: 1,
+                    hammer_id: 1
+                },
+                Bucket {
+                    id: 2,
+                    hammer_id: 2
+                },
+                Bucket {
+                    id: 3,
+                    hammer_id: 3
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_cache_does_not_requery_within_the_ttl() {
+        // Arrange an empty cache and a fake connection
+        let cache = fabrique::cache::AllCache::new();
+        let connection = CountingConnection::default();
+        let ttl = std::time::Duration::from_secs(3600);
+
+        // Act - fetch twice within the ttl, then after an invalidation
+        let first = cache
+            .get_or_fetch("acme".to_string(), ttl, connection.all())
+            .await;
+        let second = cache
+            .get_or_fetch("acme".to_string(), ttl, connection.all())
+            .await;
+        let queries_within_ttl = connection.queries();
+        cache.invalidate();
+        let invalidated = cache
+            .get_or_fetch("acme".to_string(), ttl, connection.all())
+            .await;
+
+        // Assert only the first and the invalidated fetches queried the connection
+        assert_eq!(first, Ok(vec![1, 2, 3]));
+        assert_eq!(second, Ok(vec![1, 2, 3]));
+        assert_eq!(invalidated, Ok(vec![1, 2, 3]));
+        assert_eq!(queries_within_ttl, 1);
+        assert_eq!(connection.queries(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_all_cache_requeries_once_the_ttl_elapsed() {
+        // Arrange an empty cache and a fake connection
+        let cache = fabrique::cache::AllCache::new();
+        let connection = CountingConnection::default();
+
+        // Act - fetch twice with a ttl already elapsed
+        let _ = cache
+            .get_or_fetch(
+                "acme".to_string(),
+                std::time::Duration::ZERO,
+                connection.all(),
+            )
+            .await;
+        let _ = cache
+            .get_or_fetch(
+                "acme".to_string(),
+                std::time::Duration::ZERO,
+                connection.all(),
+            )
+            .await;
+
+        // Assert both fetches queried the connection
+        assert_eq!(connection.queries(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_all_cache_is_keyed_per_pool() {
+        // Arrange an empty cache and a fake connection per pool
+        let cache = fabrique::cache::AllCache::new();
+        let acme = CountingConnection::default();
+        let anvil = CountingConnection::default();
+        let ttl = std::time::Duration::from_secs(3600);
+
+        // Act - fetch through both pools, twice each
+        for _ in 0..2 {
+            let _ = cache
+                .get_or_fetch("postgres://acme".to_string(), ttl, acme.all())
+                .await;
+            let _ = cache
+                .get_or_fetch("postgres://anvil".to_string(), ttl, anvil.all())
+                .await;
+        }
+
+        // Assert each pool was queried once
+        assert_eq!(acme.queries(), 1);
+        assert_eq!(anvil.queries(), 1);
+    }
 }
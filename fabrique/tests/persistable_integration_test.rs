@@ -3,13 +3,14 @@
 
 #[cfg(test)]
 mod tests {
-    use fabrique::Persistable;
+    use fabrique::{Deletable, Persistable, SortDir, StaleWrite, Trackable, Updatable};
     use sqlx::{Pool, Postgres};
     use uuid::Uuid;
 
     // Simple struct to test derive macro compilation
     // Note: We use SQLX_OFFLINE=true mode to avoid needing a live database
     #[derive(Debug, Persistable)]
+    #[fabrique(copy_in, all_by_id)]
     struct Anvil {
         #[allow(dead_code)]
         id: Uuid,
@@ -21,4 +22,1005 @@ mod tests {
         println!("result: {:?}", &result);
         assert!(result.is_ok());
     }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(copy_in, trackable, reload_all, all_for_update, which_exist, paginate)]
+    struct Hammer {
+        id: Uuid,
+        name: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_returns_the_stored_row(connection: Pool<Postgres>) {
+        // Arrange a hammer whose name is normalized by a database trigger
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "acme".to_string(),
+        };
+
+        // Act - persist it
+        let result = hammer.create(&connection).await;
+
+        // Assert the returned hammer reflects the stored row
+        let hammer = result.unwrap();
+        assert_eq!(hammer.id, Uuid::from_u128(1));
+        assert_eq!(hammer.name, "ACME");
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "tongs")]
+    struct Tongs {
+        id: Uuid,
+        grip: Option<String>,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_binds_none_as_null(connection: Pool<Postgres>) {
+        // Arrange tongs without grip
+        let tongs = Tongs {
+            id: Uuid::from_u128(1),
+            grip: None,
+        };
+
+        // Act - persist them
+        let result = tongs.create(&connection).await;
+
+        // Assert the column was stored as NULL
+        assert!(result.unwrap().grip.is_none());
+        let is_null: Option<bool> =
+            sqlx::query_scalar("SELECT grip IS NULL FROM tongs WHERE id = $1")
+                .bind(Uuid::from_u128(1))
+                .fetch_one(&connection)
+                .await
+                .unwrap();
+        assert_eq!(is_null, Some(true));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_tx_is_rolled_back_with_the_transaction(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange a transaction
+        let mut tx = connection.begin().await.unwrap();
+
+        // Act - create an anvil within it, then roll it back
+        let anvil = Anvil {
+            id: Uuid::from_u128(1),
+        };
+        let created = anvil.create_tx(&mut tx).await;
+        tx.rollback().await.unwrap();
+
+        // Assert the anvil was created then discarded
+        assert_eq!(created.unwrap().id, Uuid::from_u128(1));
+        let stored = <Anvil as Persistable>::all(&connection).await.unwrap();
+        assert!(stored.is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_copy_in_loads_rows(connection: Pool<Postgres>) {
+        // Arrange a thousand anvils
+        let anvils = (0..1000)
+            .map(|index| Anvil {
+                id: Uuid::from_u128(index + 1),
+            })
+            .collect();
+
+        // Act - bulk load them
+        let result = Anvil::copy_in(anvils, &connection).await;
+
+        // Assert every row was loaded
+        assert_eq!(result.unwrap(), 1000);
+        let stored = <Anvil as Persistable>::all(&connection).await.unwrap();
+        assert_eq!(stored.len(), 1000);
+    }
+
+    #[derive(Debug, PartialEq, Persistable)]
+    #[fabrique(copy_in)]
+    struct Blueprint {
+        id: Uuid,
+        drawing: Vec<u8>,
+        revision: Option<String>,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_copy_in_encodes_binary_and_null_values(connection: Pool<Postgres>) {
+        // Arrange a blueprint holding COPY text delimiters, and one without revision
+        let blueprints = vec![
+            Blueprint {
+                id: Uuid::from_u128(1),
+                drawing: vec![0, b'\\', b'\t', b'\n', 255],
+                revision: Some("Acme\tA\\1".to_string()),
+            },
+            Blueprint {
+                id: Uuid::from_u128(2),
+                drawing: vec![],
+                revision: None,
+            },
+        ];
+
+        // Act - bulk load them, then read them back
+        Blueprint::copy_in(blueprints, &connection).await.unwrap();
+        let result = Blueprint::find_by_id(Uuid::from_u128(1), &connection).await;
+        let empty = Blueprint::find_by_id(Uuid::from_u128(2), &connection).await;
+
+        // Assert the values were stored as given
+        assert_eq!(
+            result.unwrap().unwrap(),
+            Blueprint {
+                id: Uuid::from_u128(1),
+                drawing: vec![0, b'\\', b'\t', b'\n', 255],
+                revision: Some("Acme\tA\\1".to_string()),
+            }
+        );
+        assert_eq!(
+            empty.unwrap().unwrap(),
+            Blueprint {
+                id: Uuid::from_u128(2),
+                drawing: vec![],
+                revision: None,
+            }
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_all_by_id_indexes_by_primary_key(connection: Pool<Postgres>) {
+        // Arrange two anvils
+        let ids = [Uuid::from_u128(1), Uuid::from_u128(2)];
+        let anvils = ids.iter().map(|id| Anvil { id: *id }).collect();
+        Anvil::copy_in(anvils, &connection).await.unwrap();
+
+        // Act - fetch them indexed by primary key
+        let result = Anvil::all_by_id(&connection).await;
+
+        // Assert the keys are the primary keys
+        let anvils = result.unwrap();
+        assert_eq!(anvils.len(), 2);
+        for id in ids {
+            assert_eq!(anvils[&id].id, id);
+        }
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(timestamps, modified_since)]
+    struct Casting {
+        id: Uuid,
+        created_at: String,
+        updated_at: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_last_created_orders_by_creation_time(connection: Pool<Postgres>) {
+        // Arrange two castings, the last one with the smallest primary key
+        for id in [2, 1] {
+            let casting = Casting {
+                id: Uuid::from_u128(id),
+                created_at: String::new(),
+                updated_at: String::new(),
+            };
+            casting.create(&connection).await.unwrap();
+        }
+
+        // Act - fetch the last created casting
+        let result = Casting::last_created(&connection).await;
+
+        // Assert it is the one created last, stamped by the database
+        let casting = result.unwrap().unwrap();
+        assert_eq!(casting.id, Uuid::from_u128(1));
+        assert!(!casting.created_at.is_empty());
+        assert_eq!(casting.updated_at, casting.created_at);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_modified_since_fetches_the_rows_updated_after_a_cutoff(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange two castings, then update the first one after the creation of the second
+        let mut castings = Vec::new();
+        for id in [1, 2] {
+            let casting = Casting {
+                id: Uuid::from_u128(id),
+                created_at: String::new(),
+                updated_at: String::new(),
+            };
+            castings.push(casting.create(&connection).await.unwrap());
+        }
+        let cutoff = castings[1].updated_at.clone();
+        let updated = castings.remove(0).update(&connection).await.unwrap();
+
+        // Act - fetch the castings modified since the cutoff
+        let result = Casting::modified_since(cutoff, &connection).await;
+
+        // Assert only the updated casting is returned
+        let modified = result.unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].id, updated.id);
+        assert_eq!(modified[0].updated_at, updated.updated_at);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "anvils", from_row, dry_run_api)]
+    struct Forge {
+        id: Uuid,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_from_row_reads_runtime_queries(connection: Pool<Postgres>) {
+        // Arrange an anvil row
+        let id: Uuid = sqlx::query_scalar("INSERT INTO anvils DEFAULT VALUES RETURNING id")
+            .fetch_one(&connection)
+            .await
+            .unwrap();
+
+        // Act - fetch it through a runtime query
+        let result = sqlx::query_as::<_, Forge>("SELECT id FROM anvils")
+            .fetch_one(&connection)
+            .await;
+
+        // Assert the row was mapped
+        assert_eq!(result.unwrap().id, id);
+    }
+
+    #[test]
+    fn test_persistable_create_sql_does_not_need_a_connection() {
+        // Arrange a forge
+        let forge = Forge {
+            id: Uuid::from_u128(1),
+        };
+
+        // Act - inspect the insert it would run
+        let (query, params) = forge.create_sql();
+
+        // Assert the query and its parameters
+        assert_eq!(query, "INSERT INTO anvils (id) VALUES ($1) RETURNING id");
+        assert_eq!(params, vec![format!("{:?}", Uuid::from_u128(1))]);
+    }
+
+    #[test]
+    fn test_persistable_create_sql_is_the_same_for_every_instance() {
+        // Arrange two different forges
+        let first = Forge {
+            id: Uuid::from_u128(1),
+        };
+        let second = Forge {
+            id: Uuid::from_u128(2),
+        };
+
+        // Act - inspect the inserts they would run
+        let (first_query, _) = first.create_sql();
+        let (second_query, _) = second.create_sql();
+
+        // Assert the statements are byte-identical
+        assert_eq!(first_query.as_bytes(), second_query.as_bytes());
+    }
+
+    #[derive(Clone, Debug, Persistable)]
+    #[fabrique(table = "anvils", cache_all = "1h")]
+    struct CachedAnvil {
+        id: Uuid,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_all_cached_does_not_requery_within_the_ttl(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange a first fetch filling the cache, then a new anvil row
+        let cached = CachedAnvil::all_cached(&connection).await.unwrap();
+        sqlx::query("INSERT INTO anvils DEFAULT VALUES")
+            .execute(&connection)
+            .await
+            .unwrap();
+
+        // Act - fetch again within the ttl, then after an invalidation
+        let within_ttl = CachedAnvil::all_cached(&connection).await.unwrap();
+        CachedAnvil::invalidate_all_cached();
+        let invalidated = CachedAnvil::all_cached(&connection).await.unwrap();
+
+        // Assert only the invalidated fetch sees the new row
+        assert_eq!(within_ttl.len(), cached.len());
+        assert_eq!(invalidated.len(), cached.len() + 1);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_reload_all_keeps_the_order_and_drops_deleted_rows(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange four hammers, then rename one and delete another
+        let hammers = ["claw", "sledge", "mallet", "peen"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| Hammer {
+                id: Uuid::from_u128(index as u128 + 1),
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        Hammer::copy_in(hammers, &connection).await.unwrap();
+        sqlx::query("UPDATE hammers SET name = 'ACME' WHERE id = $1")
+            .bind(Uuid::from_u128(2))
+            .execute(&connection)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM hammers WHERE id = $1")
+            .bind(Uuid::from_u128(4))
+            .execute(&connection)
+            .await
+            .unwrap();
+        let stale = [3, 4, 2, 1]
+            .into_iter()
+            .map(|id| Hammer {
+                id: Uuid::from_u128(id),
+                name: String::new(),
+            })
+            .collect();
+
+        // Act - reload the stale hammers
+        let result = Hammer::reload_all(stale, &connection).await.unwrap();
+
+        // Assert the rows are refreshed in order, without the deleted one
+        let names = result.iter().map(|hammer| hammer.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["MALLET", "ACME", "CLAW"]);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "hammers", validate_not_null)]
+    struct RequiredHammer {
+        id: Uuid,
+
+        #[fabrique(nullable = false)]
+        name: Option<String>,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_rejects_missing_required_values(connection: Pool<Postgres>) {
+        // Arrange a hammer without name
+        let hammer = RequiredHammer {
+            id: Uuid::from_u128(1),
+            name: None,
+        };
+
+        // Act - create it
+        let result = hammer.create(&connection).await;
+
+        // Assert the client-side check named the column, reported as the constraint error
+        let Err(sqlx::Error::Database(error)) = result else {
+            panic!("expected a client-side not null error, got {result:?}");
+        };
+        assert_eq!(error.kind(), sqlx::error::ErrorKind::NotNullViolation);
+        assert_eq!(
+            error.to_string(),
+            "column `name` of `hammers` cannot be null"
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_failure_names_the_table_and_operation(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange a stored anvil
+        let id = Uuid::from_u128(1);
+        Anvil { id }.create(&connection).await.unwrap();
+
+        // Act - create another anvil with the same primary key
+        let result = Anvil { id }.create(&connection).await;
+
+        // Assert the database error is kept, along with the failed insert
+        let Err(sqlx::Error::Database(error)) = result else {
+            panic!("expected a database error, got {result:?}");
+        };
+        assert_eq!(error.kind(), sqlx::error::ErrorKind::UniqueViolation);
+        assert_eq!(error.constraint(), Some("anvils_pkey"));
+        assert!(error.to_string().starts_with("INSERT on `anvils` failed: "));
+        assert!(error.to_string().ends_with(
+            "(query: `INSERT INTO anvils (id) VALUES ($1) RETURNING id`, 1 bound parameters)"
+        ));
+    }
+
+    #[derive(Debug, PartialEq, Persistable)]
+    #[fabrique(copy_in)]
+    struct Mold {
+        #[fabrique(sortable)]
+        id: Uuid,
+
+        #[fabrique(array)]
+        dimensions: Vec<i32>,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_round_trips_array_columns(connection: Pool<Postgres>) {
+        // Arrange two molds, inserted one by one and in bulk
+        let created = Mold {
+            id: Uuid::from_u128(1),
+            dimensions: vec![10, 20, -30],
+        };
+        let copied = Mold {
+            id: Uuid::from_u128(2),
+            dimensions: vec![],
+        };
+
+        // Act - insert them, then read them back
+        created.create(&connection).await.unwrap();
+        Mold::copy_in(vec![copied], &connection).await.unwrap();
+        let result = Mold::all_sorted_by(MoldSortColumn::Id, SortDir::Asc, &connection).await;
+
+        // Assert the arrays were kept whole
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                Mold {
+                    id: Uuid::from_u128(1),
+                    dimensions: vec![10, 20, -30],
+                },
+                Mold {
+                    id: Uuid::from_u128(2),
+                    dimensions: vec![],
+                },
+            ]
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_find_by_id(connection: Pool<Postgres>) {
+        // Arrange a hammer
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        };
+        hammer.create(&connection).await.unwrap();
+
+        // Act - find it, then an unknown hammer
+        let found = Hammer::find_by_id(Uuid::from_u128(1), &connection).await;
+        let missing = Hammer::find_by_id(Uuid::from_u128(2), &connection).await;
+
+        // Assert the result
+        assert_eq!(found.unwrap().unwrap().name, "CLAW");
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_update(connection: Pool<Postgres>) {
+        // Arrange a persisted hammer
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+
+        // Act - rename it
+        let result = Hammer {
+            name: "sledge".to_string(),
+            ..hammer
+        }
+        .update(&connection)
+        .await;
+
+        // Assert the refreshed row is returned and stored
+        assert_eq!(result.unwrap().name, "sledge");
+        let stored = Hammer::find_by_id(Uuid::from_u128(1), &connection).await;
+        assert_eq!(stored.unwrap().unwrap().name, "sledge");
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_delete(connection: Pool<Postgres>) {
+        // Arrange a persisted hammer
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+
+        // Act - delete it
+        let result = hammer.delete(&connection).await;
+
+        // Assert the row is gone
+        assert!(result.is_ok());
+        let stored = Hammer::find_by_id(Uuid::from_u128(1), &connection).await;
+        assert!(stored.unwrap().is_none());
+    }
+
+    #[derive(Clone, Debug, Persistable)]
+    #[fabrique(version = "lock_version")]
+    struct Ingot {
+        id: Uuid,
+        weight: i32,
+        lock_version: i32,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_update_rejects_stale_writes(connection: Pool<Postgres>) {
+        // Arrange an ingot read twice
+        let ingot = Ingot {
+            id: Uuid::from_u128(1),
+            weight: 10,
+            lock_version: 0,
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+        let stale = ingot.clone();
+
+        // Act - update it from both copies
+        let updated = Ingot {
+            weight: 20,
+            ..ingot
+        }
+        .update(&connection)
+        .await;
+        let result = Ingot {
+            weight: 30,
+            ..stale
+        }
+        .update(&connection)
+        .await;
+
+        // Assert the second update was rejected
+        assert_eq!(updated.unwrap().lock_version, 1);
+        let error = result.unwrap_err();
+        let sqlx::Error::Database(error) = error else {
+            panic!("expected a stale write, got {error:?}");
+        };
+        assert_eq!(
+            error.try_downcast_ref::<StaleWrite>(),
+            Some(&StaleWrite { table: "ingots" })
+        );
+        let stored = Ingot::find_by_id(Uuid::from_u128(1), &connection).await;
+        assert_eq!(stored.unwrap().unwrap().weight, 20);
+    }
+
+    #[derive(Clone, Debug, Persistable)]
+    #[fabrique(soft_delete = "deleted_at")]
+    struct Grindstone {
+        id: Uuid,
+        deleted_at: Option<String>,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_soft_destroy_and_restore(connection: Pool<Postgres>) {
+        // Arrange two grindstones
+        let mut grindstones = Vec::new();
+        for id in 1..=2 {
+            let grindstone = Grindstone {
+                id: Uuid::from_u128(id),
+                deleted_at: None,
+            };
+            grindstones.push(grindstone.create(&connection).await.unwrap());
+        }
+
+        // Act - soft delete the first one
+        let result = grindstones[0].clone().soft_destroy(&connection).await;
+
+        // Assert it is hidden from all() but kept in the table
+        assert!(result.is_ok());
+        let visible = <Grindstone as Persistable>::all(&connection).await.unwrap();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].id, Uuid::from_u128(2));
+        assert_eq!(Grindstone::count(&connection).await.unwrap(), 1);
+        let stored = Grindstone::all_with_deleted(&connection).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(
+            stored
+                .iter()
+                .any(|grindstone| grindstone.deleted_at.is_some())
+        );
+
+        // Act - restore it
+        let restored = grindstones[0].clone().restore(&connection).await;
+
+        // Assert it is visible again
+        assert!(restored.unwrap().deleted_at.is_none());
+        let visible = <Grindstone as Persistable>::all(&connection).await.unwrap();
+        assert_eq!(visible.len(), 2);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "anvils")]
+    struct GeneratedAnvil {
+        #[fabrique(primary_key, generated)]
+        id: Uuid,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_lets_the_database_generate_keys(connection: Pool<Postgres>) {
+        // Arrange an anvil without a meaningful id
+        let anvil = GeneratedAnvil { id: Uuid::nil() };
+
+        // Act - create it
+        let result = anvil.create(&connection).await;
+
+        // Assert the id was assigned by the database
+        let created = result.unwrap();
+        assert_ne!(created.id, Uuid::nil());
+        let stored = GeneratedAnvil::find_by_id(created.id, &connection).await;
+        assert!(stored.unwrap().is_some());
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "ingots")]
+    struct GeneratedIngot {
+        id: Uuid,
+        weight: i32,
+        #[fabrique(generated)]
+        lock_version: i32,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_update_leaves_generated_columns_to_the_database(
+        connection: Pool<Postgres>,
+    ) {
+        // Arrange a stored ingot, whose lock version defaults to 0
+        let ingot = GeneratedIngot {
+            id: Uuid::from_u128(1),
+            weight: 10,
+            lock_version: 42,
+        };
+        let mut ingot = ingot.create(&connection).await.unwrap();
+        ingot.weight = 20;
+        ingot.lock_version = 7;
+
+        // Act - update it
+        let result = ingot.update(&connection).await;
+
+        // Assert only the weight was written
+        let updated = result.unwrap();
+        assert_eq!(updated.weight, 20);
+        assert_eq!(updated.lock_version, 0);
+    }
+
+    #[derive(Debug, PartialEq, Persistable)]
+    #[fabrique(table = "hammers")]
+    struct DisplayedHammer {
+        id: Uuid,
+        name: String,
+        #[fabrique(skip)]
+        display_name: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_skips_fields_without_column(connection: Pool<Postgres>) {
+        // Arrange a hammer with a computed display name
+        let hammer = DisplayedHammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+            display_name: "Claw hammer".to_string(),
+        };
+
+        // Act - create it, then read it back
+        hammer.create(&connection).await.unwrap();
+        let result = DisplayedHammer::all(&connection).await;
+
+        // Assert the skipped field was defaulted
+        assert_eq!(
+            result.unwrap(),
+            vec![DisplayedHammer {
+                id: Uuid::from_u128(1),
+                name: "CLAW".to_string(),
+                display_name: String::new(),
+            }]
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_all_for_update_skips_locked_rows(connection: Pool<Postgres>) {
+        // Arrange a hammer
+        Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+
+        // Act - claim it from a first worker, then from a second one
+        let mut first_worker = connection.begin().await.unwrap();
+        let mut second_worker = connection.begin().await.unwrap();
+        let claimed = Hammer::all_for_update(&mut first_worker).await;
+        let skipped = Hammer::all_for_update(&mut second_worker).await;
+
+        // Assert the locked row was only claimed once
+        assert_eq!(claimed.unwrap().len(), 1);
+        assert!(skipped.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_count(connection: Pool<Postgres>) {
+        // Arrange three hammers
+        for id in 1..=3 {
+            Hammer {
+                id: Uuid::from_u128(id),
+                name: "claw".to_string(),
+            }
+            .create(&connection)
+            .await
+            .unwrap();
+        }
+
+        // Act the call to the count method
+        let result = Hammer::count(&connection).await;
+
+        // Assert the result
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "hammers", batch_insert, batch_max_params = 500)]
+    struct BatchedHammer {
+        id: Uuid,
+        name: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_batch_inserts_rows_per_statement(connection: Pool<Postgres>) {
+        // Arrange a thousand hammers
+        let hammers = (1..=1000)
+            .map(|id| BatchedHammer {
+                id: Uuid::from_u128(id),
+                name: format!("claw-{id}"),
+            })
+            .collect();
+
+        // Act - insert them in batches
+        let result = BatchedHammer::create_batch(hammers, &connection).await;
+
+        // Assert every row was created and read back, 250 rows of 2 parameters per statement
+        let created = result.unwrap();
+        assert_eq!(created.len(), 1000);
+        assert_eq!(created[999].name, "CLAW-1000");
+        assert_eq!(BatchedHammer::count(&connection).await.unwrap(), 1000);
+        let statements: i64 = sqlx::query_scalar("SELECT COUNT(DISTINCT xmin::text) FROM hammers")
+            .fetch_one(&connection)
+            .await
+            .unwrap();
+        assert_eq!(statements, 4);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "hammers")]
+    struct NamedHammer {
+        id: Uuid,
+        #[fabrique(distinct_countable)]
+        name: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_count_distinct(connection: Pool<Postgres>) {
+        // Arrange three hammers sharing two names
+        for (id, name) in [(1, "claw"), (2, "claw"), (3, "sledge")] {
+            NamedHammer {
+                id: Uuid::from_u128(id),
+                name: name.to_string(),
+            }
+            .create(&connection)
+            .await
+            .unwrap();
+        }
+
+        // Act the call to the count_distinct_name method
+        let result = NamedHammer::count_distinct_name(&connection).await;
+
+        // Assert the result
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(table = "hammers", emit_event = "HammerForged")]
+    struct ForgedHammer {
+        id: Uuid,
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct HammerForged {
+        hammer_id: Uuid,
+        name: String,
+    }
+
+    impl From<&ForgedHammer> for HammerForged {
+        fn from(hammer: &ForgedHammer) -> Self {
+            Self {
+                hammer_id: hammer.id,
+                name: hammer.name.clone(),
+            }
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_create_with_event(connection: Pool<Postgres>) {
+        // Arrange a hammer
+        let hammer = ForgedHammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        };
+
+        // Act - create it along with its event
+        let result = hammer.create_with_event(&connection).await;
+
+        // Assert the event was built from the persisted row
+        let (_, event) = result.unwrap();
+        assert_eq!(
+            event,
+            HammerForged {
+                hammer_id: Uuid::from_u128(1),
+                name: "CLAW".to_string(),
+            }
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_paginate(connection: Pool<Postgres>) {
+        // Arrange three hammers, inserted out of order
+        for id in [2, 3, 1] {
+            Hammer {
+                id: Uuid::from_u128(id),
+                name: "claw".to_string(),
+            }
+            .create(&connection)
+            .await
+            .unwrap();
+        }
+
+        // Act - fetch them two by two
+        let first_page = Hammer::paginate(2, 0, &connection).await.unwrap();
+        let second_page = Hammer::paginate(2, 2, &connection).await.unwrap();
+
+        // Assert the pages hold the hammers ordered by primary key
+        let ids = |page: Vec<Hammer>| page.into_iter().map(|hammer| hammer.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(first_page),
+            vec![Uuid::from_u128(1), Uuid::from_u128(2)]
+        );
+        assert_eq!(ids(second_page), vec![Uuid::from_u128(3)]);
+    }
+
+    #[derive(Debug, Persistable)]
+    struct Stand {
+        id: Uuid,
+        #[fabrique(
+            relation = "Hammer",
+            referenced_key = "id",
+            related_table = "hammers",
+            related_columns = "id, name"
+        )]
+        hammer_id: Uuid,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_find_with_relation(connection: Pool<Postgres>) {
+        // Arrange a stand holding a hammer
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+        Stand {
+            id: Uuid::from_u128(2),
+            hammer_id: hammer.id,
+        }
+        .create(&connection)
+        .await
+        .unwrap();
+
+        // Act - fetch the stand along with its hammer
+        let found = Stand::find_with_hammer(Uuid::from_u128(2), &connection).await;
+        let missing = Stand::find_with_hammer(Uuid::from_u128(3), &connection).await;
+
+        // Assert both records were read from the joined row
+        let (stand, hammer) = found.unwrap().unwrap();
+        assert_eq!(stand.hammer_id, Uuid::from_u128(1));
+        assert_eq!(hammer.id, Uuid::from_u128(1));
+        assert_eq!(hammer.name, "CLAW");
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_persistable_trackable_renders_the_table_and_primary_key() {
+        // Arrange a hammer opted into tracking
+        let hammer = Hammer {
+            id: Uuid::from_u128(1),
+            name: "claw".to_string(),
+        };
+
+        // Act - render its primary key
+        let primary_key = Trackable::primary_key(&hammer);
+
+        // Assert the record is identified by its table and primary key
+        assert_eq!(Hammer::TABLE_NAME, "hammers");
+        assert_eq!(primary_key, Uuid::from_u128(1).to_string());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_which_exist(connection: Pool<Postgres>) {
+        // Arrange two stored hammers
+        for id in [1, 3] {
+            Hammer {
+                id: Uuid::from_u128(id),
+                name: "claw".to_string(),
+            }
+            .create(&connection)
+            .await
+            .unwrap();
+        }
+
+        // Act - check a mix of stored and missing ids
+        let ids = [1, 2, 3, 4].map(Uuid::from_u128);
+        let result = Hammer::which_exist(&ids, &connection).await;
+
+        // Assert only the stored ids are returned
+        let mut existing = result.unwrap();
+        existing.sort();
+        assert_eq!(existing, vec![Uuid::from_u128(1), Uuid::from_u128(3)]);
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(unique = ["name"])]
+    struct Workshop {
+        id: Uuid,
+        name: String,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_get_or_create_is_idempotent(connection: Pool<Postgres>) {
+        // Arrange two workshops sharing the same unique name
+        let first = Workshop {
+            id: Uuid::from_u128(1),
+            name: "acme".to_string(),
+        };
+        let second = Workshop {
+            id: Uuid::from_u128(2),
+            name: "acme".to_string(),
+        };
+
+        // Act - get or create both
+        let created = first.get_or_create(&connection).await.unwrap();
+        let existing = second.get_or_create(&connection).await.unwrap();
+
+        // Assert the second call returned the row stored by the first one
+        assert_eq!(created.id, Uuid::from_u128(1));
+        assert_eq!(existing.id, Uuid::from_u128(1));
+        assert_eq!(Workshop::count(&connection).await.unwrap(), 1);
+    }
+
+    thread_local! {
+        static CURRENT_TENANT: std::cell::Cell<Uuid> = const { std::cell::Cell::new(Uuid::nil()) };
+    }
+
+    fn current_tenant() -> Uuid {
+        CURRENT_TENANT.get()
+    }
+
+    #[derive(Debug, Persistable)]
+    #[fabrique(default_scope = "tenant_id = $TENANT", scope_param = "current_tenant")]
+    struct Rack {
+        id: Uuid,
+        tenant_id: Uuid,
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_persistable_parameterized_default_scope(connection: Pool<Postgres>) {
+        // Arrange a rack for each of two tenants
+        for id in [1, 2] {
+            Rack {
+                id: Uuid::from_u128(id),
+                tenant_id: Uuid::from_u128(id * 10),
+            }
+            .create(&connection)
+            .await
+            .unwrap();
+        }
+
+        // Act - read the racks as the first tenant
+        CURRENT_TENANT.set(Uuid::from_u128(10));
+        let all = Rack::all(&connection).await.unwrap();
+        let count = Rack::count(&connection).await.unwrap();
+        let other = Rack::find_by_id(Uuid::from_u128(2), &connection).await;
+
+        // Assert only the racks of the first tenant are visible
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].tenant_id, Uuid::from_u128(10));
+        assert_eq!(count, 1);
+        assert!(other.unwrap().is_none());
+    }
 }
@@ -0,0 +1,15 @@
+CREATE TABLE hammers (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  name TEXT NOT NULL
+);
+
+CREATE FUNCTION normalize_hammer_name() RETURNS TRIGGER AS $$
+BEGIN
+  NEW.name := upper(NEW.name);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER normalize_hammer_name
+  BEFORE INSERT ON hammers
+  FOR EACH ROW EXECUTE FUNCTION normalize_hammer_name();
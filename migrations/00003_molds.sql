@@ -0,0 +1,4 @@
+CREATE TABLE molds (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  dimensions INTEGER[] NOT NULL
+);
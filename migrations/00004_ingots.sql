@@ -0,0 +1,5 @@
+CREATE TABLE ingots (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  weight INTEGER NOT NULL,
+  lock_version INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,4 @@
+CREATE TABLE stands (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  hammer_id UUID NOT NULL REFERENCES hammers (id)
+);
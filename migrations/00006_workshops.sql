@@ -0,0 +1,4 @@
+CREATE TABLE workshops (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  name TEXT NOT NULL UNIQUE
+);
@@ -0,0 +1,4 @@
+CREATE TABLE tongs (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  grip TEXT
+);
@@ -0,0 +1,5 @@
+-- The deletion time is stored as text, so the tests need no date and time crate
+CREATE TABLE grindstones (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  deleted_at TEXT
+);
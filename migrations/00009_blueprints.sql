@@ -0,0 +1,5 @@
+CREATE TABLE blueprints (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  drawing BYTEA NOT NULL,
+  revision TEXT
+);
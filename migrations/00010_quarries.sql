@@ -0,0 +1,4 @@
+CREATE TABLE quarries (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  location POINT NOT NULL
+);
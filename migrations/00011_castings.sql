@@ -0,0 +1,6 @@
+-- The timestamps are stored as text, so the tests need no date and time crate
+CREATE TABLE castings (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
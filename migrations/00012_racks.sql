@@ -0,0 +1,4 @@
+CREATE TABLE racks (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  tenant_id UUID NOT NULL
+);